@@ -0,0 +1,198 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Logic for the per-block checksums of container files.
+//!
+//! The `Writer` can record a CRC32 checksum of each block it writes, covering its number of
+//! values and its bytes as written (i.e. compressed, and encrypted if it is). The checksums are
+//! kept in a `BlockChecksums` index, meant to be stored in a sidecar file next to the container
+//! file, which [`Reader::verify`](struct.Reader.html#method.verify) checks the blocks against.
+use crate::{
+    reader::from_avro_datum, schema::Schema, types::Value, writer::to_avro_datum, AvroResult, Error,
+};
+use crc32fast::Hasher;
+use lazy_static::lazy_static;
+use std::{
+    convert::TryFrom,
+    io::{Read, Write},
+};
+
+lazy_static! {
+    static ref BLOCK_CHECKSUMS_SCHEMA: Schema = Schema::parse_str(
+        r#"{
+            "type": "record",
+            "name": "BlockChecksums",
+            "namespace": "org.apache.avro.rust",
+            "fields": [
+                {"name": "marker", "type": {"type": "fixed", "name": "Sync", "size": 16}},
+                {"name": "blocks", "type": {"type": "array", "items": {
+                    "type": "record",
+                    "name": "BlockChecksum",
+                    "fields": [
+                        {"name": "offset", "type": "long"},
+                        {"name": "crc32", "type": "long"}
+                    ]
+                }}}
+            ]
+        }"#
+    )
+    .unwrap();
+}
+
+/// Checksum of a block of a container file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BlockChecksum {
+    /// Offset of the block in the file.
+    pub offset: u64,
+    /// CRC32 of the number of values of the block and of its bytes.
+    pub crc32: u32,
+}
+
+/// Index of the checksums of the blocks of a container file, as recorded by a `Writer`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlockChecksums {
+    /// Sync marker of the file the blocks belong to.
+    pub marker: [u8; 16],
+    /// Checksums of the blocks, by increasing offset.
+    pub blocks: Vec<BlockChecksum>,
+}
+
+impl BlockChecksums {
+    /// Write the index, in Avro binary format, e.g. to a sidecar file.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> AvroResult<usize> {
+        let blocks = self
+            .blocks
+            .iter()
+            .map(|block| {
+                Value::Record(vec![
+                    ("offset".to_string(), Value::Long(block.offset as i64)),
+                    ("crc32".to_string(), Value::Long(block.crc32.into())),
+                ])
+            })
+            .collect();
+        let value = Value::Record(vec![
+            ("marker".to_string(), Value::Fixed(16, self.marker.to_vec())),
+            ("blocks".to_string(), Value::Array(blocks)),
+        ]);
+        let bytes = to_avro_datum(&BLOCK_CHECKSUMS_SCHEMA, value)?;
+        writer.write_all(&bytes).map_err(Error::WriteBytes)?;
+        Ok(bytes.len())
+    }
+
+    /// Read an index written by `write_to`.
+    pub fn read_from<R: Read>(mut reader: R) -> AvroResult<Self> {
+        let value = from_avro_datum(&BLOCK_CHECKSUMS_SCHEMA, &mut reader, None)?;
+        match value {
+            Value::Record(fields) => match &fields[..] {
+                [(_, Value::Fixed(16, marker)), (_, Value::Array(blocks))] => {
+                    let mut checksums = BlockChecksums {
+                        marker: [0; 16],
+                        blocks: Vec::with_capacity(blocks.len()),
+                    };
+                    checksums.marker.copy_from_slice(marker);
+                    for block in blocks {
+                        checksums.blocks.push(read_block_checksum(block)?);
+                    }
+                    Ok(checksums)
+                }
+                _ => Err(Error::BadBlockChecksums),
+            },
+            _ => Err(Error::BadBlockChecksums),
+        }
+    }
+}
+
+fn read_block_checksum(value: &Value) -> AvroResult<BlockChecksum> {
+    match value {
+        Value::Record(fields) => match &fields[..] {
+            [(_, Value::Long(offset)), (_, Value::Long(crc32))] => Ok(BlockChecksum {
+                offset: u64::try_from(*offset).map_err(|_| Error::BadBlockChecksums)?,
+                crc32: u32::try_from(*crc32).map_err(|_| Error::BadBlockChecksums)?,
+            }),
+            _ => Err(Error::BadBlockChecksums),
+        },
+        _ => Err(Error::BadBlockChecksums),
+    }
+}
+
+/// Compute the checksum of a block from its number of values and its bytes as written.
+pub(crate) fn block_crc32(num_values: usize, data: &[u8]) -> u32 {
+    let mut hasher = Hasher::new();
+    hasher.update(&(num_values as u64).to_le_bytes());
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// A block which failed verification.
+#[derive(Debug)]
+pub struct BlockFailure {
+    /// Offset of the block in the file.
+    pub offset: u64,
+    /// What is wrong with the block.
+    pub error: Error,
+}
+
+/// Outcome of [`Reader::verify`](struct.Reader.html#method.verify).
+#[derive(Debug, Default)]
+pub struct VerificationReport {
+    /// Number of blocks found in the file, whether they are valid or not.
+    pub blocks: usize,
+    /// Blocks which failed verification, by increasing offset.
+    pub failures: Vec<BlockFailure>,
+}
+
+impl VerificationReport {
+    /// Whether every block has been verified successfully.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_block_checksums_roundtrip() {
+        let checksums = BlockChecksums {
+            marker: [3; 16],
+            blocks: vec![
+                BlockChecksum {
+                    offset: 120,
+                    crc32: block_crc32(10, b"first"),
+                },
+                BlockChecksum {
+                    offset: 160,
+                    crc32: u32::MAX,
+                },
+            ],
+        };
+        let mut bytes = Vec::new();
+        let n = checksums.write_to(&mut bytes).unwrap();
+        assert_eq!(n, bytes.len());
+        assert_eq!(BlockChecksums::read_from(&bytes[..]).unwrap(), checksums);
+        assert!(BlockChecksums::read_from(&bytes[..n - 1]).is_err());
+    }
+
+    #[test]
+    fn test_block_crc32_covers_num_values() {
+        assert_eq!(block_crc32(10, b"block"), block_crc32(10, b"block"));
+        assert_ne!(block_crc32(10, b"block"), block_crc32(11, b"block"));
+        assert_ne!(block_crc32(10, b"block"), block_crc32(10, b"blocK"));
+    }
+}
@@ -16,10 +16,18 @@
 // under the License.
 
 //! Logic for all supported compression codecs in Avro.
+#[cfg(feature = "zstandard")]
+use crate::{schema::Schema, writer::to_avro_datum};
 use crate::{types::Value, AvroResult, Error};
-use libflate::deflate::{Decoder, Encoder};
-use std::io::{Read, Write};
-use strum_macros::{EnumIter, EnumString, IntoStaticStr};
+use lazy_static::lazy_static;
+use libflate::deflate::{Decoder, EncodeOptions, Encoder};
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+use strum_macros::{EnumIter, EnumString};
 
 #[cfg(feature = "bzip")]
 use bzip2::{
@@ -32,10 +40,18 @@ extern crate crc32fast;
 use crc32fast::Hasher;
 #[cfg(feature = "xz")]
 use xz2::read::{XzDecoder, XzEncoder};
+#[cfg(feature = "zstandard")]
+use zstd::{
+    stream::raw::Operation,
+    zstd_safe::{DParameter, InBuffer, OutBuffer},
+};
 
 /// The compression codec used to compress blocks.
-#[derive(Clone, Copy, Debug, Eq, PartialEq, EnumIter, EnumString, IntoStaticStr)]
+///
+/// More variants may be added, e.g. with new features, so matches on it need a wildcard arm.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, EnumIter, EnumString)]
 #[strum(serialize_all = "kebab_case")]
+#[non_exhaustive]
 pub enum Codec {
     /// The `Null` codec simply passes through data uncompressed.
     Null,
@@ -58,6 +74,28 @@ pub enum Codec {
     /// The `Xz` codec uses [Xz utils](https://tukaani.org/xz/)
     /// compression library.
     Xz,
+    /// A codec registered with [`register_custom_codec`](fn.register_custom_codec.html),
+    /// identified by its name.
+    #[strum(disabled)]
+    Custom(&'static str),
+}
+
+impl From<Codec> for &'static str {
+    fn from(value: Codec) -> Self {
+        match value {
+            Codec::Null => "null",
+            Codec::Deflate => "deflate",
+            #[cfg(feature = "snappy")]
+            Codec::Snappy => "snappy",
+            #[cfg(feature = "zstandard")]
+            Codec::Zstandard => "zstandard",
+            #[cfg(feature = "bzip")]
+            Codec::Bzip2 => "bzip2",
+            #[cfg(feature = "xz")]
+            Codec::Xz => "xz",
+            Codec::Custom(name) => name,
+        }
+    }
 }
 
 impl From<Codec> for Value {
@@ -66,13 +104,224 @@ impl From<Codec> for Value {
     }
 }
 
+/// A user-defined codec, for the codecs this library does not support out of the box.
+///
+/// Once registered with [`register_custom_codec`](fn.register_custom_codec.html), it is used by
+/// `Reader` for files whose `avro.codec` is its name, and by `Writer` when given the returned
+/// `Codec::Custom`.
+pub trait CustomCodec: Send + Sync {
+    /// The name of the codec, written as the `avro.codec` of the files using it.
+    fn name(&self) -> &'static str;
+
+    /// Compress a stream of bytes in-place.
+    fn compress(&self, stream: &mut Vec<u8>) -> AvroResult<()>;
+
+    /// Decompress a stream of bytes in-place.
+    fn decompress(&self, stream: &mut Vec<u8>) -> AvroResult<()>;
+
+    /// Decompress `input` into the empty `output`, whose allocation can be reused. By default the
+    /// input is copied into `output` and decompressed in-place.
+    fn decompress_into(&self, input: &[u8], output: &mut Vec<u8>) -> AvroResult<()> {
+        output.extend_from_slice(input);
+        self.decompress(output)
+    }
+}
+
+lazy_static! {
+    static ref CUSTOM_CODECS: RwLock<HashMap<&'static str, Arc<dyn CustomCodec>>> =
+        RwLock::new(HashMap::new());
+}
+
+/// Register a `CustomCodec`, returning the `Codec` to give to a `Writer` to use it.
+///
+/// Registering a codec with the name of an already registered one replaces it. The names of the
+/// codecs supported by this library cannot be used, even when their feature is not enabled.
+pub fn register_custom_codec(codec: impl CustomCodec + 'static) -> AvroResult<Codec> {
+    let name = codec.name();
+    if ["null", "deflate", "snappy", "zstandard", "bzip2", "xz"].contains(&name) {
+        return Err(Error::CustomCodecName(name.to_string()));
+    }
+    CUSTOM_CODECS.write().unwrap().insert(name, Arc::new(codec));
+    Ok(Codec::Custom(name))
+}
+
+fn custom_codec(name: &str) -> AvroResult<Arc<dyn CustomCodec>> {
+    CUSTOM_CODECS
+        .read()
+        .unwrap()
+        .get(name)
+        .cloned()
+        .ok_or_else(|| Error::CodecNotSupported(name.to_string()))
+}
+
+/// Wraps the error of a `CustomCodec`, unless it is already wrapped.
+fn custom_codec_error(name: &'static str, error: Error) -> Error {
+    match error {
+        Error::CustomCodec { .. } => error,
+        _ => Error::CustomCodec {
+            codec: name,
+            source: Box::new(error),
+        },
+    }
+}
+
+impl Codec {
+    /// Get the `Codec` with the given name, which is either supported by this library or
+    /// registered as a `CustomCodec`.
+    pub(crate) fn from_name(name: &str) -> AvroResult<Codec> {
+        match Codec::from_str(name) {
+            Ok(codec) => Ok(codec),
+            Err(_) => custom_codec(name).map(|codec| Codec::Custom(codec.name())),
+        }
+    }
+}
+
+/// Options tuning how a `Codec` compresses blocks.
+///
+/// They only affect compression: the `avro.codec` written in the file header is the same whatever
+/// the options, and files can be read without knowing them.
+#[derive(Clone, Debug, Default, Eq, PartialEq, typed_builder::TypedBuilder)]
+pub struct CodecOptions {
+    /// Compression level, whose meaning and range depend on the codec:
+    /// `Deflate` only accepts `0` (no compression), `Zstandard` accepts
+    /// `-131072..=22`, `Bzip2` `1..=9` and `Xz` `0..=9`. When not set, each codec uses its own
+    /// default level. It is ignored by `Null` and `Snappy`.
+    #[builder(default, setter(strip_option))]
+    pub level: Option<i32>,
+    /// Base 2 logarithm of the `Zstandard` window size, between `10` and `31` (`30` on 32-bit
+    /// targets). Windows bigger than 128MiB (a log of `27`) need more memory to be decompressed.
+    #[builder(default, setter(strip_option))]
+    pub zstd_window_log: Option<u32>,
+    /// Enables `Zstandard` long-distance matching, which improves the compression of big blocks
+    /// with matches far apart.
+    #[builder(default)]
+    pub zstd_long_distance_matching: bool,
+    /// Dictionary used by `Zstandard` to compress blocks. The id of the dictionary is written in
+    /// the file header, under the `avro.zstd.dictionary.id` key, so that readers can find it.
+    #[cfg(feature = "zstandard")]
+    #[builder(default, setter(strip_option))]
+    pub zstd_dictionary: Option<Arc<ZstdDictionary>>,
+    /// Also writes the `zstd_dictionary` itself in the file header, under the
+    /// `avro.zstd.dictionary` key, so that the file can be read without supplying it.
+    #[cfg(feature = "zstandard")]
+    #[builder(default)]
+    pub zstd_embed_dictionary: bool,
+}
+
+/// The metadata key under which the id of the `Zstandard` dictionary is written.
+#[cfg(feature = "zstandard")]
+pub(crate) const ZSTD_DICTIONARY_ID_KEY: &str = "avro.zstd.dictionary.id";
+/// The metadata key under which the `Zstandard` dictionary is embedded.
+#[cfg(feature = "zstandard")]
+pub(crate) const ZSTD_DICTIONARY_KEY: &str = "avro.zstd.dictionary";
+/// The biggest `Zstandard` window log, which depends on the target's pointer width.
+#[cfg(feature = "zstandard")]
+const ZSTD_WINDOW_LOG_MAX: u32 = if cfg!(target_pointer_width = "32") {
+    30
+} else {
+    31
+};
+
+/// A `Zstandard` dictionary, improving the compression of small blocks with content similar to
+/// the samples it has been trained on.
+#[cfg(feature = "zstandard")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ZstdDictionary {
+    id: u32,
+    bytes: Vec<u8>,
+}
+
+#[cfg(feature = "zstandard")]
+impl ZstdDictionary {
+    /// Creates a dictionary from its bytes, as returned by [`bytes`](#method.bytes). Raw content
+    /// dictionaries, which are not trained, have the id `0`.
+    pub fn new(bytes: Vec<u8>) -> Self {
+        let id = zstd::zstd_safe::get_dict_id_from_dict(&bytes).map_or(0, |id| id.get());
+        ZstdDictionary { id, bytes }
+    }
+
+    /// Trains a dictionary of at most `max_size` bytes from samples of data.
+    pub fn train<S: AsRef<[u8]>>(samples: &[S], max_size: usize) -> AvroResult<Self> {
+        zstd::dict::from_samples(samples, max_size)
+            .map(Self::new)
+            .map_err(Error::ZstdTrainDictionary)
+    }
+
+    /// Trains a dictionary of at most `max_size` bytes from sample values, encoded with `schema`
+    /// the way they are in container file blocks.
+    pub fn train_from_values<I>(schema: &Schema, samples: I, max_size: usize) -> AvroResult<Self>
+    where
+        I: IntoIterator<Item = Value>,
+    {
+        let samples = samples
+            .into_iter()
+            .map(|value| to_avro_datum(schema, value))
+            .collect::<AvroResult<Vec<_>>>()?;
+        Self::train(&samples, max_size)
+    }
+
+    /// The id of the dictionary, identifying it in the header of the files using it.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The bytes of the dictionary.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// A source of `Zstandard` dictionaries, keyed by their id, for the `Reader` of files whose
+/// dictionary is not embedded.
+#[cfg(feature = "zstandard")]
+pub trait ZstdDictionaryProvider {
+    /// Returns the dictionary with the given id, if known.
+    fn zstd_dictionary(&self, id: u32) -> Option<Arc<ZstdDictionary>>;
+}
+
+#[cfg(feature = "zstandard")]
+impl ZstdDictionaryProvider for HashMap<u32, Arc<ZstdDictionary>> {
+    fn zstd_dictionary(&self, id: u32) -> Option<Arc<ZstdDictionary>> {
+        self.get(&id).cloned()
+    }
+}
+
+#[cfg(feature = "zstandard")]
+impl<F> ZstdDictionaryProvider for F
+where
+    F: Fn(u32) -> Option<Arc<ZstdDictionary>>,
+{
+    fn zstd_dictionary(&self, id: u32) -> Option<Arc<ZstdDictionary>> {
+        self(id)
+    }
+}
+
 impl Codec {
     /// Compress a stream of bytes in-place.
     pub fn compress(self, stream: &mut Vec<u8>) -> AvroResult<()> {
+        self.compress_with_options(stream, &CodecOptions::default())
+    }
+
+    /// Compress a stream of bytes in-place, using the given `CodecOptions`.
+    pub fn compress_with_options(
+        self,
+        stream: &mut Vec<u8>,
+        options: &CodecOptions,
+    ) -> AvroResult<()> {
         match self {
             Codec::Null => (),
+            Codec::Custom(name) => custom_codec(name)?
+                .compress(stream)
+                .map_err(|e| custom_codec_error(name, e))?,
             Codec::Deflate => {
-                let mut encoder = Encoder::new(Vec::new());
+                let mut encoder = match options.level {
+                    None => Encoder::new(Vec::new()),
+                    Some(0) => {
+                        Encoder::with_options(Vec::new(), EncodeOptions::new().no_compression())
+                    }
+                    // the encoder has a single compression level
+                    Some(level) => return Err(self.invalid_level(level)),
+                };
                 encoder.write_all(stream).map_err(Error::DeflateCompress)?;
                 // Deflate errors seem to just be io::Error
                 *stream = encoder
@@ -99,23 +348,56 @@ impl Codec {
             }
             #[cfg(feature = "zstandard")]
             Codec::Zstandard => {
-                let mut encoder = zstd::Encoder::new(Vec::new(), 0).unwrap();
+                let level = options.level.unwrap_or(0);
+                if !zstd::compression_level_range().contains(&level) {
+                    return Err(self.invalid_level(level));
+                }
+                let mut encoder = match options.zstd_dictionary {
+                    Some(ref dictionary) => {
+                        zstd::Encoder::with_dictionary(Vec::new(), level, dictionary.bytes())
+                    }
+                    None => zstd::Encoder::new(Vec::new(), level),
+                }
+                .map_err(Error::ZstdCompress)?;
+                if let Some(window_log) = options.zstd_window_log {
+                    encoder
+                        .window_log(window_log)
+                        .map_err(Error::ZstdCompress)?;
+                }
+                if options.zstd_long_distance_matching {
+                    encoder
+                        .long_distance_matching(true)
+                        .map_err(Error::ZstdCompress)?;
+                }
                 encoder.write_all(stream).map_err(Error::ZstdCompress)?;
-                *stream = encoder.finish().unwrap();
+                *stream = encoder.finish().map_err(Error::ZstdCompress)?;
             }
             #[cfg(feature = "bzip")]
             Codec::Bzip2 => {
-                let mut encoder = BzEncoder::new(&stream[..], Compression::best());
+                let compression = match options.level {
+                    None => Compression::best(),
+                    Some(level @ 1..=9) => Compression::new(level as u32),
+                    Some(level) => return Err(self.invalid_level(level)),
+                };
+                let mut encoder = BzEncoder::new(&stream[..], compression);
                 let mut buffer = Vec::new();
-                encoder.read_to_end(&mut buffer).unwrap();
+                encoder
+                    .read_to_end(&mut buffer)
+                    .map_err(Error::Bzip2Compress)?;
                 *stream = buffer;
             }
             #[cfg(feature = "xz")]
             Codec::Xz => {
-                let compression_level = 9;
+                let compression_level = match options.level {
+                    None => 9,
+                    Some(level @ 0..=9) => level as u32,
+                    Some(level) => return Err(self.invalid_level(level)),
+                };
                 let mut encoder = XzEncoder::new(&stream[..], compression_level);
                 let mut buffer = Vec::new();
-                encoder.read_to_end(&mut buffer).unwrap();
+                encoder
+                    .read_to_end(&mut buffer)
+                    .map_err(Error::XzCompress)?;
                 *stream = buffer;
             }
         };
@@ -125,61 +407,181 @@ impl Codec {
 
     /// Decompress a stream of bytes in-place.
     pub fn decompress(self, stream: &mut Vec<u8>) -> AvroResult<()> {
-        *stream = match self {
-            Codec::Null => return Ok(()),
+        self.decompress_with_options(stream, &CodecOptions::default())
+    }
+
+    /// Decompress a stream of bytes in-place, using the given `CodecOptions`. Only the
+    /// `Zstandard` dictionary is needed to decompress, the other options are ignored.
+    pub fn decompress_with_options(
+        self,
+        stream: &mut Vec<u8>,
+        options: &CodecOptions,
+    ) -> AvroResult<()> {
+        if self == Codec::Null {
+            return Ok(());
+        }
+        let mut decoded = Vec::new();
+        self.decompress_into(stream, &mut decoded, options)?;
+        *stream = decoded;
+        Ok(())
+    }
+
+    /// Decompress `input` into `output`, replacing its content but reusing its allocation, so
+    /// that decompressing many blocks with the same `output` does not allocate once it is big
+    /// enough. Only the `Zstandard` dictionary of the `CodecOptions` is used.
+    pub fn decompress_into(
+        self,
+        input: &[u8],
+        output: &mut Vec<u8>,
+        options: &CodecOptions,
+    ) -> AvroResult<()> {
+        Decompressor::default().decompress_into(self, input, output, options)
+    }
+
+    fn invalid_level(self, level: i32) -> Error {
+        Error::InvalidCompressionLevel { codec: self, level }
+    }
+}
+
+/// Decompresses blocks one after the other, reusing what it can from block to block: the
+/// `Zstandard` decompression context, into which the dictionary is loaded once.
+///
+/// The `Deflate`, `Bzip2` and `Xz` decoders cannot be reset by their libraries, so they are still
+/// set up for each block.
+#[derive(Default)]
+pub(crate) struct Decompressor {
+    /// The `Zstandard` context, with the dictionary loaded into it, if any.
+    #[cfg(feature = "zstandard")]
+    zstd: Option<(
+        Option<Arc<ZstdDictionary>>,
+        zstd::stream::raw::Decoder<'static>,
+    )>,
+}
+
+impl std::fmt::Debug for Decompressor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Decompressor").finish_non_exhaustive()
+    }
+}
+
+impl Decompressor {
+    /// Decompress `input` into `output`, as `Codec::decompress_into` does.
+    #[allow(unused_variables)]
+    pub(crate) fn decompress_into(
+        &mut self,
+        codec: Codec,
+        input: &[u8],
+        output: &mut Vec<u8>,
+        options: &CodecOptions,
+    ) -> AvroResult<()> {
+        output.clear();
+        match codec {
+            Codec::Null => output.extend_from_slice(input),
+            Codec::Custom(name) => custom_codec(name)?
+                .decompress_into(input, output)
+                .map_err(|e| custom_codec_error(name, e))?,
             Codec::Deflate => {
-                let mut decoded = Vec::new();
-                let mut decoder = Decoder::new(&stream[..]);
-                decoder
-                    .read_to_end(&mut decoded)
+                Decoder::new(input)
+                    .read_to_end(output)
                     .map_err(Error::DeflateDecompress)?;
-                decoded
             }
             #[cfg(feature = "snappy")]
             Codec::Snappy => {
                 use byteorder::ByteOrder;
 
-                let decompressed_size = snap::raw::decompress_len(&stream[..stream.len() - 4])
-                    .map_err(Error::GetSnappyDecompressLen)?;
-                let mut decoded = vec![0; decompressed_size];
+                if input.len() < 4 {
+                    return Err(Error::SnappyMissingCrc32(input.len()));
+                }
+                let (compressed, checksum) = input.split_at(input.len() - 4);
+                let decompressed_size =
+                    snap::raw::decompress_len(compressed).map_err(Error::GetSnappyDecompressLen)?;
+                output.resize(crate::util::safe_len(decompressed_size)?, 0);
                 snap::raw::Decoder::new()
-                    .decompress(&stream[..stream.len() - 4], &mut decoded[..])
+                    .decompress(compressed, &mut output[..])
                     .map_err(Error::SnappyDecompress)?;
 
-                let expected = byteorder::BigEndian::read_u32(&stream[stream.len() - 4..]);
                 let mut hasher = Hasher::new();
-                hasher.update(&decoded);
+                hasher.update(output);
                 let actual = hasher.finalize();
-
+                let expected = byteorder::BigEndian::read_u32(checksum);
                 if expected != actual {
                     return Err(Error::SnappyCrc32 { expected, actual });
                 }
-                decoded
             }
             #[cfg(feature = "zstandard")]
             Codec::Zstandard => {
-                let mut decoded = Vec::new();
-                let mut decoder = zstd::Decoder::new(&stream[..]).unwrap();
-                std::io::copy(&mut decoder, &mut decoded).map_err(Error::ZstdDecompress)?;
-                decoded
+                if options.zstd_dictionary.is_none() {
+                    if let Some(id) = zstd::zstd_safe::get_dict_id_from_frame(input) {
+                        return Err(Error::MissingZstdDictionary(id.get()));
+                    }
+                }
+                self.zstd_decompress(input, output, &options.zstd_dictionary)
+                    .map_err(Error::ZstdDecompress)?;
             }
             #[cfg(feature = "bzip")]
             Codec::Bzip2 => {
-                let mut decoder = BzDecoder::new(&stream[..]);
-                let mut decoded = Vec::new();
-                decoder.read_to_end(&mut decoded).unwrap();
-                decoded
+                BzDecoder::new(input)
+                    .read_to_end(output)
+                    .map_err(Error::Bzip2Decompress)?;
             }
             #[cfg(feature = "xz")]
             Codec::Xz => {
-                let mut decoder = XzDecoder::new(&stream[..]);
-                let mut decoded: Vec<u8> = Vec::new();
-                decoder.read_to_end(&mut decoded).unwrap();
-                decoded
+                XzDecoder::new(input)
+                    .read_to_end(output)
+                    .map_err(Error::XzDecompress)?;
             }
         };
         Ok(())
     }
+
+    /// Decompress the `Zstandard` frames of `input` into `output`, with the context of the
+    /// previous blocks, unless their dictionary was another one.
+    #[cfg(feature = "zstandard")]
+    fn zstd_decompress(
+        &mut self,
+        input: &[u8],
+        output: &mut Vec<u8>,
+        dictionary: &Option<Arc<ZstdDictionary>>,
+    ) -> std::io::Result<()> {
+        let reusable = match (&self.zstd, dictionary) {
+            (Some((Some(previous), _)), Some(dictionary)) => Arc::ptr_eq(previous, dictionary),
+            (Some((None, _)), None) => true,
+            _ => false,
+        };
+        let decoder = if reusable {
+            let decoder = &mut self.zstd.as_mut().expect("checked above").1;
+            decoder.reinit()?;
+            decoder
+        } else {
+            // loading the dictionary is the expensive part, done once for all the blocks
+            let mut decoder = zstd::stream::raw::Decoder::with_dictionary(
+                dictionary.as_ref().map_or(&[][..], |d| d.bytes()),
+            )?;
+            // accept the biggest windows `CodecOptions::zstd_window_log` allows
+            decoder.set_parameter(DParameter::WindowLogMax(ZSTD_WINDOW_LOG_MAX))?;
+            &mut self.zstd.insert((dictionary.clone(), decoder)).1
+        };
+
+        let mut input = InBuffer::around(input);
+        loop {
+            if output.len() == output.capacity() {
+                output.reserve(input.src.len().max(1024));
+            }
+            let pos = output.len();
+            let remaining = decoder.run(&mut input, &mut OutBuffer::around_pos(output, pos))?;
+            let input_consumed = input.pos() == input.src.len();
+            if input_consumed && remaining == 0 {
+                return Ok(());
+            }
+            // the decoder needs more input than there is to finish the frame
+            if input_consumed && output.len() < output.capacity() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "incomplete frame",
+                ));
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -210,6 +612,19 @@ mod tests {
         compress_and_decompress(Codec::Snappy);
     }
 
+    #[cfg(feature = "snappy")]
+    #[test]
+    fn snappy_truncated_checksum() {
+        let mut stream = INPUT.to_vec();
+        Codec::Snappy.compress(&mut stream).unwrap();
+        for len in [0, 3] {
+            assert!(matches!(
+                Codec::Snappy.decompress(&mut stream[..len].to_vec()),
+                Err(Error::SnappyMissingCrc32(actual)) if actual == len
+            ));
+        }
+    }
+
     #[cfg(feature = "zstandard")]
     #[test]
     fn zstd_compress_and_decompress() {
@@ -233,10 +648,309 @@ mod tests {
         codec.compress(&mut stream).unwrap();
         assert_ne!(INPUT, stream.as_slice());
         assert!(INPUT.len() > stream.len());
+
+        // the previous content of the output is replaced, and its allocation reused
+        let mut output = Vec::with_capacity(2 * INPUT.len());
+        output.extend_from_slice(b"previous block");
+        let allocation = output.as_ptr();
+        codec
+            .decompress_into(&stream, &mut output, &CodecOptions::default())
+            .unwrap();
+        assert_eq!(INPUT, output.as_slice());
+        assert_eq!(allocation, output.as_ptr());
+
+        codec.decompress(&mut stream).unwrap();
+        assert_eq!(INPUT, stream.as_slice());
+    }
+
+    #[test]
+    fn deflate_without_compression() {
+        let options = CodecOptions::builder().level(0).build();
+        compress_and_decompress_with_options(Codec::Deflate, options.clone());
+
+        let mut stream = INPUT.to_vec();
+        Codec::Deflate
+            .compress_with_options(&mut stream, &options)
+            .unwrap();
+        assert!(stream.len() > INPUT.len());
+        Codec::Deflate.decompress(&mut stream).unwrap();
+        assert_eq!(INPUT, stream.as_slice());
+    }
+
+    #[cfg(feature = "zstandard")]
+    #[test]
+    fn zstd_compress_with_options() {
+        compress_and_decompress_with_options(
+            Codec::Zstandard,
+            CodecOptions::builder()
+                .level(19)
+                .zstd_window_log(28)
+                .zstd_long_distance_matching(true)
+                .build(),
+        );
+        compress_and_decompress_with_options(
+            Codec::Zstandard,
+            CodecOptions::builder().level(-5).build(),
+        );
+    }
+
+    #[cfg(feature = "zstandard")]
+    fn train_dictionary() -> ZstdDictionary {
+        let schema = Schema::parse_str(
+            r#"{"type": "record", "name": "Event", "fields": [
+                {"name": "id", "type": "long"},
+                {"name": "kind", "type": "string"}
+            ]}"#,
+        )
+        .unwrap();
+        let samples = (0..1000).map(|i| {
+            Value::Record(vec![
+                ("id".to_string(), Value::Long(i)),
+                (
+                    "kind".to_string(),
+                    Value::String(format!("theanswertolifetheuniverse-{}", i % 7)),
+                ),
+            ])
+        });
+        ZstdDictionary::train_from_values(&schema, samples, 1024).unwrap()
+    }
+
+    #[cfg(feature = "zstandard")]
+    #[test]
+    fn zstd_compress_with_dictionary() {
+        let dictionary = Arc::new(train_dictionary());
+        assert_ne!(dictionary.id(), 0);
+        assert_eq!(
+            ZstdDictionary::new(dictionary.bytes().to_vec()),
+            *dictionary
+        );
+
+        let options = CodecOptions::builder()
+            .zstd_dictionary(dictionary.clone())
+            .build();
+        let mut with_dictionary = INPUT.to_vec();
+        Codec::Zstandard
+            .compress_with_options(&mut with_dictionary, &options)
+            .unwrap();
+        let mut without_dictionary = INPUT.to_vec();
+        Codec::Zstandard.compress(&mut without_dictionary).unwrap();
+        assert!(with_dictionary.len() < without_dictionary.len());
+
+        match Codec::Zstandard.decompress(&mut with_dictionary.clone()) {
+            Err(Error::MissingZstdDictionary(id)) => assert_eq!(id, dictionary.id()),
+            other => panic!("Expected a missing dictionary error, got {other:?}"),
+        }
+        Codec::Zstandard
+            .decompress_with_options(&mut with_dictionary, &options)
+            .unwrap();
+        assert_eq!(INPUT, with_dictionary.as_slice());
+    }
+
+    #[cfg(feature = "zstandard")]
+    #[test]
+    fn zstd_decompressor_reuses_its_context() {
+        let dictionary = Arc::new(train_dictionary());
+        let with_dictionary = CodecOptions::builder()
+            .zstd_dictionary(dictionary.clone())
+            .build();
+        let without_dictionary = CodecOptions::default();
+        let compress = |options: &CodecOptions| {
+            let mut stream = INPUT.to_vec();
+            Codec::Zstandard
+                .compress_with_options(&mut stream, options)
+                .unwrap();
+            stream
+        };
+        let mut decompressor = Decompressor::default();
+        let mut output = Vec::new();
+        let mut decompress = |decompressor: &mut Decompressor, stream: &[u8], options| {
+            decompressor.decompress_into(Codec::Zstandard, stream, &mut output, options)?;
+            assert_eq!(INPUT, output.as_slice());
+            Ok::<_, Error>(())
+        };
+
+        let block = compress(&with_dictionary);
+        for _ in 0..3 {
+            decompress(&mut decompressor, &block, &with_dictionary).unwrap();
+        }
+        let (loaded, _) = decompressor.zstd.as_ref().unwrap();
+        assert!(Arc::ptr_eq(loaded.as_ref().unwrap(), &dictionary));
+
+        // a block cut short fails, without breaking the next ones
+        assert!(matches!(
+            decompress(
+                &mut decompressor,
+                &block[..block.len() / 2],
+                &with_dictionary
+            ),
+            Err(Error::ZstdDecompress(_))
+        ));
+        decompress(&mut decompressor, &block, &with_dictionary).unwrap();
+
+        // the context is set up again when the dictionary changes
+        decompress(
+            &mut decompressor,
+            &compress(&without_dictionary),
+            &without_dictionary,
+        )
+        .unwrap();
+        assert!(decompressor.zstd.as_ref().unwrap().0.is_none());
+
+        // a block made of several frames
+        let mut frames = compress(&without_dictionary);
+        frames.extend(compress(&without_dictionary));
+        decompressor
+            .decompress_into(Codec::Zstandard, &frames, &mut output, &without_dictionary)
+            .unwrap();
+        assert_eq!([INPUT, INPUT].concat(), output);
+    }
+
+    #[cfg(feature = "bzip")]
+    #[test]
+    fn bzip_compress_with_options() {
+        compress_and_decompress_with_options(
+            Codec::Bzip2,
+            CodecOptions::builder().level(1).build(),
+        );
+    }
+
+    #[cfg(feature = "xz")]
+    #[test]
+    fn xz_compress_with_options() {
+        compress_and_decompress_with_options(Codec::Xz, CodecOptions::builder().level(0).build());
+    }
+
+    fn compress_and_decompress_with_options(codec: Codec, options: CodecOptions) {
+        let mut stream = INPUT.to_vec();
+        codec.compress_with_options(&mut stream, &options).unwrap();
+        assert_ne!(INPUT, stream.as_slice());
         codec.decompress(&mut stream).unwrap();
         assert_eq!(INPUT, stream.as_slice());
     }
 
+    #[test]
+    fn invalid_compression_levels() {
+        assert_invalid_level(Codec::Deflate, -1);
+        assert_invalid_level(Codec::Deflate, 6);
+        #[cfg(feature = "zstandard")]
+        assert_invalid_level(Codec::Zstandard, 23);
+        #[cfg(feature = "bzip")]
+        assert_invalid_level(Codec::Bzip2, 0);
+        #[cfg(feature = "xz")]
+        assert_invalid_level(Codec::Xz, 10);
+    }
+
+    fn assert_invalid_level(codec: Codec, level: i32) {
+        let options = CodecOptions::builder().level(level).build();
+        match codec.compress_with_options(&mut INPUT.to_vec(), &options) {
+            Err(Error::InvalidCompressionLevel {
+                codec: actual_codec,
+                level: actual_level,
+            }) => {
+                assert_eq!(actual_codec, codec);
+                assert_eq!(actual_level, level);
+            }
+            other => panic!("Expected an invalid level error for {codec:?}, got {other:?}"),
+        }
+    }
+
+    #[cfg(feature = "xz")]
+    #[test]
+    fn xz_decompress_invalid_data() {
+        match Codec::Xz.decompress(&mut INPUT.to_vec()) {
+            Err(Error::XzDecompress(_)) => {}
+            other => panic!("Expected a decompression error, got {other:?}"),
+        }
+    }
+
+    /// Reverses the bytes of blocks, so that they are not readable without the codec.
+    struct ReverseCodec(&'static str);
+
+    impl CustomCodec for ReverseCodec {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn compress(&self, stream: &mut Vec<u8>) -> AvroResult<()> {
+            stream.reverse();
+            Ok(())
+        }
+
+        fn decompress(&self, stream: &mut Vec<u8>) -> AvroResult<()> {
+            stream.reverse();
+            Ok(())
+        }
+    }
+
+    /// Fails to decompress anything.
+    struct FailingCodec;
+
+    impl CustomCodec for FailingCodec {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        fn compress(&self, _stream: &mut Vec<u8>) -> AvroResult<()> {
+            Ok(())
+        }
+
+        fn decompress(&self, _stream: &mut Vec<u8>) -> AvroResult<()> {
+            Err(Error::BadCodecMetadata)
+        }
+    }
+
+    #[test]
+    fn custom_codec_compress_and_decompress() {
+        let codec = register_custom_codec(ReverseCodec("reverse")).unwrap();
+        assert_eq!(codec, Codec::Custom("reverse"));
+        assert_eq!(<&str>::from(codec), "reverse");
+        assert_eq!(Codec::from_name("reverse").unwrap(), codec);
+        compress_and_decompress_with_options(codec, CodecOptions::default());
+
+        match Codec::Custom("not registered").compress(&mut INPUT.to_vec()) {
+            Err(Error::CodecNotSupported(name)) => assert_eq!(name, "not registered"),
+            other => panic!("Expected an unsupported codec error, got {other:?}"),
+        }
+        assert!(matches!(
+            register_custom_codec(ReverseCodec("xz")),
+            Err(Error::CustomCodecName(_))
+        ));
+
+        let codec = register_custom_codec(FailingCodec).unwrap();
+        match codec.decompress(&mut INPUT.to_vec()) {
+            Err(Error::CustomCodec { codec, source }) => {
+                assert_eq!(codec, "failing");
+                assert!(matches!(
+                    source.downcast_ref::<Error>(),
+                    Some(Error::BadCodecMetadata)
+                ));
+            }
+            other => panic!("Expected a custom codec error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn write_and_read_with_custom_codec() {
+        use crate::{Reader, Schema, Writer};
+
+        let codec = register_custom_codec(ReverseCodec("reverse-blocks")).unwrap();
+        let schema = Schema::parse_str(r#""string""#).unwrap();
+        let mut writer = Writer::with_codec(&schema, Vec::new(), codec);
+        writer.append("theanswertolifetheuniverse").unwrap();
+        let bytes = writer.into_inner().unwrap();
+        assert!(bytes.windows(14).any(|w| w == b"reverse-blocks"));
+        assert!(bytes.windows(9).any(|w| w == b"esrevinue"));
+
+        let values: Vec<_> = Reader::new(&bytes[..])
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            values,
+            vec![Value::String("theanswertolifetheuniverse".to_string())]
+        );
+    }
+
     #[test]
     fn codec_to_str() {
         assert_eq!(<&str>::from(Codec::Null), "null");
@@ -0,0 +1,717 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Generation of Rust types from Avro schemas.
+//!
+//! Every named type of the schemas becomes a Rust type deriving `Serialize`, `Deserialize` and
+//! `AvroSchema`, so the crate using the generated code depends on `serde` and on `apache-avro`
+//! with the `derive` feature:
+//! - records are structs, with their fields in snake_case, renamed back with `#[serde(rename)]`
+//! - enums are enums of unit variants
+//! - fixed are newtypes of byte arrays
+//! - `["null", T]` unions are `Option<T>`, the other unions are enums with a variant per branch,
+//!   named after the record and the field they are defined in, which are `#[serde(untagged)]`
+//!   with a `Null` unit variant if the union has a `null` branch
+//!
+//! The `doc`s are kept, as Rust documentation and in the derived schemas. Logical types and
+//! `bytes` are only supported as the type of a record field or of its optional value, since the
+//! derive macro needs a field attribute to tell them apart from the types they annotate.
+//!
+//! Only the JSON format of schemas (`.avsc` files) is supported, not Avro IDL.
+//!
+//! # Build scripts
+//!
+//! [`compile_schemas`] generates the types of schema files from a `build.rs`, which cargo reruns
+//! whenever they change:
+//!
+//! ```no_run
+//! // build.rs
+//! let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+//! apache_avro::codegen::compile_schemas(&["schemas"], out_dir.join("schemas.rs")).unwrap();
+//! ```
+//!
+//! The generated file is then included in the crate:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/schemas.rs"));
+//! ```
+use crate::{
+    schema::{Name, Namespace, RecordField, Schema},
+    AvroResult, Error,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+const DERIVES: &str =
+    "Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, apache_avro::AvroSchema";
+
+/// The derives of the enums and fixed, which are plain values.
+const VALUE_DERIVES: &str = "Debug, Clone, Copy, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize, apache_avro::AvroSchema";
+
+/// The largest fixed serde can (de)serialize as a byte array.
+const MAX_FIXED_SIZE: usize = 32;
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+/// Generates the Rust code of the types of the named types of some schemas, e.g. as returned by
+/// [`Schema::parse_list`](../schema/enum.Schema.html#method.parse_list).
+pub fn generate(schemas: &[Schema]) -> AvroResult<String> {
+    let mut generator = Generator::default();
+    for schema in schemas {
+        generator.collect(schema, &None);
+    }
+    generator.generate()
+}
+
+/// Generates the Rust types of the schemas of some `.avsc` files into `output`, for build
+/// scripts. The inputs are either files or directories, whose `.avsc` files are all compiled.
+///
+/// Cargo is told to rerun the build script when the inputs change, and the output is only
+/// written when it changes.
+pub fn compile_schemas<P: AsRef<Path>>(inputs: &[P], output: impl AsRef<Path>) -> AvroResult<()> {
+    let mut files = Vec::new();
+    for input in inputs {
+        let input = input.as_ref();
+        println!("cargo:rerun-if-changed={}", input.display());
+        if input.is_dir() {
+            schema_files(input, &mut files)?;
+        } else {
+            files.push(input.to_path_buf());
+        }
+    }
+    let sources = files
+        .iter()
+        .map(|file| fs::read_to_string(file).map_err(|e| Error::ReadFile(file.clone(), e)))
+        .collect::<AvroResult<Vec<_>>>()?;
+    let sources: Vec<&str> = sources.iter().map(String::as_str).collect();
+    let code = generate(&Schema::parse_list(&sources)?)?;
+
+    let output = output.as_ref();
+    if fs::read_to_string(output).ok().as_deref() != Some(code.as_str()) {
+        fs::write(output, code).map_err(|e| Error::CreateFile(output.to_path_buf(), e))?;
+    }
+    Ok(())
+}
+
+/// Collects the `.avsc` files of a directory and of its subdirectories, sorted by path.
+fn schema_files(dir: &Path, files: &mut Vec<PathBuf>) -> AvroResult<()> {
+    let entries = fs::read_dir(dir).map_err(|e| Error::ReadFile(dir.to_path_buf(), e))?;
+    let mut paths = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| Error::ReadFile(dir.to_path_buf(), e))?;
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            schema_files(&path, files)?;
+        } else if path
+            .extension()
+            .map_or(false, |extension| extension == "avsc")
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct Generator<'s> {
+    /// The named types to generate, in the order they are defined.
+    named: Vec<(Name, &'s Schema)>,
+    /// The named types each record contains directly, i.e. not through an array or a map.
+    contains: HashMap<Name, HashSet<Name>>,
+    /// The Rust types generated so far, to detect name clashes.
+    type_names: HashSet<String>,
+    code: String,
+    /// The enums of the unions of the type being generated, which follow it.
+    unions: String,
+}
+
+impl<'s> Generator<'s> {
+    /// Registers the named types defined by a schema.
+    fn collect(&mut self, schema: &'s Schema, enclosing_namespace: &Namespace) {
+        match schema {
+            Schema::Record { name, fields, .. } => {
+                let name = name.fully_qualified_name(enclosing_namespace);
+                if self.is_collected(&name) {
+                    return;
+                }
+                self.named.push((name.clone(), schema));
+                let mut contains = HashSet::new();
+                for field in fields {
+                    direct_names(&field.schema, &name.namespace, &mut contains);
+                    self.collect(&field.schema, &name.namespace);
+                }
+                self.contains.insert(name, contains);
+            }
+            Schema::Enum { name, .. } | Schema::Fixed { name, .. } => {
+                let name = name.fully_qualified_name(enclosing_namespace);
+                if !self.is_collected(&name) {
+                    self.named.push((name, schema));
+                }
+            }
+            Schema::Array(inner) | Schema::Map(inner) => self.collect(inner, enclosing_namespace),
+            Schema::Union(union_schema) => {
+                for variant in union_schema.variants() {
+                    self.collect(variant, enclosing_namespace);
+                }
+            }
+            Schema::Decimal { inner, .. } => self.collect(inner, enclosing_namespace),
+            _ => {}
+        }
+    }
+
+    fn is_collected(&self, name: &Name) -> bool {
+        self.named.iter().any(|(collected, _)| collected == name)
+    }
+
+    fn generate(mut self) -> AvroResult<String> {
+        self.code
+            .push_str("// Generated by apache_avro::codegen, do not edit.\n");
+        for (name, _) in &self.named {
+            if !self.type_names.insert(type_name(name)?) {
+                return Err(unsupported(format!(
+                    "several named types are named {:?}",
+                    name.name
+                )));
+            }
+        }
+        for (name, schema) in std::mem::take(&mut self.named) {
+            self.code.push('\n');
+            match schema {
+                Schema::Record {
+                    doc,
+                    aliases,
+                    fields,
+                    ..
+                } => {
+                    let aliases = aliases.iter().flatten().map(|alias| alias.fullname(None));
+                    let header = named_type_header(&name, DERIVES, doc, aliases.collect());
+                    self.generate_record(&name, header, fields)?
+                }
+                Schema::Enum {
+                    doc,
+                    aliases,
+                    symbols,
+                    ..
+                } => {
+                    let aliases = aliases.iter().flatten().map(|alias| alias.fullname(None));
+                    let header = named_type_header(&name, VALUE_DERIVES, doc, aliases.collect());
+                    self.generate_enum(&name, header, symbols)?
+                }
+                Schema::Fixed {
+                    doc, aliases, size, ..
+                } => {
+                    let aliases = aliases.iter().flatten().map(|alias| alias.fullname(None));
+                    let header = named_type_header(&name, VALUE_DERIVES, doc, aliases.collect());
+                    self.generate_fixed(&name, header, *size)?
+                }
+                _ => unreachable!("only named types are collected"),
+            }
+            let unions = std::mem::take(&mut self.unions);
+            self.code.push_str(&unions);
+        }
+        Ok(self.code)
+    }
+
+    fn generate_record(
+        &mut self,
+        name: &Name,
+        header: String,
+        fields: &[RecordField],
+    ) -> AvroResult<()> {
+        let mut body = String::new();
+        let mut idents = HashSet::new();
+        for field in fields {
+            let ident = field_ident(&field.name);
+            if !idents.insert(ident.clone()) {
+                return Err(unsupported(format!(
+                    "several fields of {name} are named {ident} in Rust"
+                )));
+            }
+            let context = format!("{}{}", name.name, pascal_case(&field.name));
+            let (ty, attributes) = self.field_type(&field.schema, name, &context)?;
+
+            writeln!(body).unwrap();
+            write_doc(&mut body, "    ", &field.doc);
+            if ident.trim_start_matches("r#") != field.name {
+                writeln!(body, "    #[serde(rename = {:?})]", field.name).unwrap();
+            }
+            for alias in field.aliases.iter().flatten() {
+                writeln!(body, "    #[avro(alias = {alias:?})]").unwrap();
+            }
+            if let Some(default) = &field.default {
+                let default = serde_json::to_string(default).map_err(Error::ConvertJsonToString)?;
+                writeln!(body, "    #[avro(default = {default:?})]").unwrap();
+            }
+            for attribute in attributes {
+                writeln!(body, "    #[avro({attribute})]").unwrap();
+            }
+            writeln!(body, "    pub {ident}: {ty},").unwrap();
+        }
+        writeln!(self.code, "{header}pub struct {} {{{body}}}", name.name).unwrap();
+        Ok(())
+    }
+
+    fn generate_enum(&mut self, name: &Name, header: String, symbols: &[String]) -> AvroResult<()> {
+        let mut body = String::new();
+        let mut idents = HashSet::new();
+        for symbol in symbols {
+            let ident = pascal_case(symbol);
+            if !idents.insert(ident.clone()) {
+                return Err(unsupported(format!(
+                    "several symbols of {name} are named {ident} in Rust"
+                )));
+            }
+            if &ident != symbol {
+                writeln!(body, "    #[serde(rename = {symbol:?})]").unwrap();
+            }
+            writeln!(body, "    {ident},").unwrap();
+        }
+        writeln!(self.code, "{header}pub enum {} {{\n{body}}}", name.name).unwrap();
+        Ok(())
+    }
+
+    fn generate_fixed(&mut self, name: &Name, header: String, size: usize) -> AvroResult<()> {
+        if size > MAX_FIXED_SIZE {
+            return Err(unsupported(format!(
+                "the size of {name} is greater than {MAX_FIXED_SIZE}"
+            )));
+        }
+        writeln!(
+            self.code,
+            "{header}#[avro(fixed)]\npub struct {}(pub [u8; {size}]);",
+            name.name
+        )
+        .unwrap();
+        Ok(())
+    }
+
+    /// The Rust type of a field of a record, along with the `#[avro(...)]` attributes it needs.
+    fn field_type(
+        &mut self,
+        schema: &Schema,
+        record: &Name,
+        context: &str,
+    ) -> AvroResult<(String, Vec<String>)> {
+        let optional = match schema {
+            Schema::Union(union_schema) => match union_schema.variants() {
+                [Schema::Null, inner] => Some(inner),
+                _ => None,
+            },
+            _ => None,
+        };
+        let annotated = optional.unwrap_or(schema);
+        let annotation = match annotated {
+            Schema::Bytes => Some(("Vec<u8>", "bytes".to_owned())),
+            Schema::Decimal {
+                precision,
+                scale,
+                inner,
+            } if matches!(**inner, Schema::Bytes) => Some((
+                "Vec<u8>",
+                format!("decimal(precision = {precision}, scale = {scale})"),
+            )),
+            Schema::Uuid => Some(("String", "logical_type = \"uuid\"".to_owned())),
+            Schema::Date => Some(("i32", "logical_type = \"date\"".to_owned())),
+            Schema::TimeMillis => Some(("i32", "logical_type = \"time-millis\"".to_owned())),
+            Schema::TimeMicros => Some(("i64", "logical_type = \"time-micros\"".to_owned())),
+            Schema::TimestampMillis => {
+                Some(("i64", "logical_type = \"timestamp-millis\"".to_owned()))
+            }
+            Schema::TimestampMicros => {
+                Some(("i64", "logical_type = \"timestamp-micros\"".to_owned()))
+            }
+            _ => None,
+        };
+        match (annotation, optional) {
+            (Some((ty, attribute)), Some(_)) => Ok((format!("Option<{ty}>"), vec![attribute])),
+            (Some((ty, attribute)), None) => Ok((ty.to_owned(), vec![attribute])),
+            (None, _) => Ok((self.rust_type(schema, record, context, false)?, vec![])),
+        }
+    }
+
+    /// The Rust type of a schema, generating the enums of its unions. Values contained `indirect`ly,
+    /// i.e. in an array or a map, do not need to be boxed when they contain the record.
+    fn rust_type(
+        &mut self,
+        schema: &Schema,
+        record: &Name,
+        context: &str,
+        indirect: bool,
+    ) -> AvroResult<String> {
+        let ty = match schema {
+            Schema::Boolean => "bool".to_owned(),
+            Schema::Int => "i32".to_owned(),
+            Schema::Long => "i64".to_owned(),
+            Schema::Float => "f32".to_owned(),
+            Schema::Double => "f64".to_owned(),
+            Schema::String => "String".to_owned(),
+            Schema::Array(items) => {
+                let items = self.rust_type(items, record, &format!("{context}Item"), true)?;
+                format!("Vec<{items}>")
+            }
+            Schema::Map(values) => {
+                let values = self.rust_type(values, record, &format!("{context}Value"), true)?;
+                format!("std::collections::HashMap<String, {values}>")
+            }
+            Schema::Union(union_schema) => match union_schema.variants() {
+                [Schema::Null, inner] => {
+                    let inner = self.rust_type(inner, record, context, indirect)?;
+                    format!("Option<{inner}>")
+                }
+                variants => self.generate_union(variants, record, context, indirect)?,
+            },
+            Schema::Record { name, .. }
+            | Schema::Enum { name, .. }
+            | Schema::Fixed { name, .. }
+            | Schema::Ref { name } => {
+                let name = name.fully_qualified_name(&record.namespace);
+                let ty = type_name(&name)?;
+                // records containing themselves have to be boxed
+                if !indirect && self.reaches(&name, record) {
+                    format!("Box<{ty}>")
+                } else {
+                    ty
+                }
+            }
+            Schema::Null => {
+                return Err(unsupported(
+                    "null is only supported as a branch of a union".to_owned(),
+                ))
+            }
+            schema => {
+                return Err(unsupported(format!(
+                    "{schema:?} is only supported as the type of a record field or of its optional value"
+                )))
+            }
+        };
+        Ok(ty)
+    }
+
+    /// Generates the enum of a union, returning its name.
+    fn generate_union(
+        &mut self,
+        variants: &[Schema],
+        record: &Name,
+        context: &str,
+        indirect: bool,
+    ) -> AvroResult<String> {
+        let name = context.to_owned();
+        if !self.type_names.insert(name.clone()) {
+            return Err(unsupported(format!("several types are named {name:?}")));
+        }
+        let nullable = variants.iter().any(|variant| variant == &Schema::Null);
+        let mut body = String::new();
+        for variant in variants {
+            if variant == &Schema::Null {
+                writeln!(body, "    Null,").unwrap();
+                continue;
+            }
+            let ident = match variant {
+                Schema::Record { name, .. }
+                | Schema::Enum { name, .. }
+                | Schema::Fixed { name, .. }
+                | Schema::Ref { name } => name.name.clone(),
+                Schema::Array(_) => "Array".to_owned(),
+                Schema::Map(_) => "Map".to_owned(),
+                primitive => format!("{:?}", crate::schema::SchemaKind::from(primitive)),
+            };
+            let ty = self.rust_type(variant, record, &format!("{name}{ident}"), indirect)?;
+            writeln!(body, "    {ident}({ty}),").unwrap();
+        }
+
+        let unions = &mut self.unions;
+        writeln!(unions).unwrap();
+        writeln!(unions, "#[derive({DERIVES})]").unwrap();
+        if let Some(namespace) = &record.namespace {
+            writeln!(unions, "#[avro(namespace = {namespace:?})]").unwrap();
+        }
+        if nullable {
+            writeln!(unions, "#[serde(untagged)]").unwrap();
+        }
+        writeln!(unions, "pub enum {name} {{\n{body}}}").unwrap();
+        Ok(name)
+    }
+
+    /// Whether a named type contains another one, directly or not.
+    fn reaches(&self, from: &Name, to: &Name) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = vec![from];
+        while let Some(name) = stack.pop() {
+            if name == to {
+                return true;
+            }
+            if visited.insert(name) {
+                stack.extend(self.contains.get(name).into_iter().flatten());
+            }
+        }
+        false
+    }
+}
+
+/// Collects the named types a value of a schema contains without indirection.
+fn direct_names(schema: &Schema, enclosing_namespace: &Namespace, names: &mut HashSet<Name>) {
+    match schema {
+        Schema::Record { name, .. } | Schema::Ref { name } => {
+            names.insert(name.fully_qualified_name(enclosing_namespace));
+        }
+        Schema::Union(union_schema) => {
+            for variant in union_schema.variants() {
+                direct_names(variant, enclosing_namespace, names);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The doc comments, derives and `#[avro(...)]` attributes of a named type.
+fn named_type_header(
+    name: &Name,
+    derives: &str,
+    doc: &Option<String>,
+    aliases: Vec<String>,
+) -> String {
+    let mut header = String::new();
+    write_doc_comment(&mut header, "", doc);
+    writeln!(header, "#[derive({derives})]").unwrap();
+    if let Some(doc) = doc {
+        writeln!(header, "#[avro(doc = {doc:?})]").unwrap();
+    }
+    if let Some(namespace) = &name.namespace {
+        writeln!(header, "#[avro(namespace = {namespace:?})]").unwrap();
+    }
+    for alias in aliases {
+        writeln!(header, "#[avro(alias = {alias:?})]").unwrap();
+    }
+    if name.name.contains('_') || name.name.starts_with(|c: char| c.is_ascii_lowercase()) {
+        writeln!(header, "#[allow(non_camel_case_types)]").unwrap();
+    }
+    header
+}
+
+/// Writes the `doc` of a field both as Rust documentation and for the derived schema.
+fn write_doc(code: &mut String, indent: &str, doc: &Option<String>) {
+    write_doc_comment(code, indent, doc);
+    if let Some(doc) = doc {
+        writeln!(code, "{indent}#[avro(doc = {doc:?})]").unwrap();
+    }
+}
+
+fn write_doc_comment(code: &mut String, indent: &str, doc: &Option<String>) {
+    for line in doc.iter().flat_map(|doc| doc.lines()) {
+        writeln!(code, "{indent}/// {line}").unwrap();
+    }
+}
+
+/// The Rust type of a named type, which has to keep its name to derive the same schema.
+fn type_name(name: &Name) -> AvroResult<String> {
+    if KEYWORDS.contains(&name.name.as_str()) || name.name == "Self" {
+        return Err(unsupported(format!(
+            "{name} is a Rust keyword, which cannot name a type"
+        )));
+    }
+    Ok(name.name.clone())
+}
+
+/// The Rust identifier of a field, in snake_case.
+fn field_ident(name: &str) -> String {
+    let mut ident = String::with_capacity(name.len());
+    let mut previous: Option<char> = None;
+    for ch in name.chars() {
+        if ch.is_ascii_uppercase()
+            && previous.map_or(false, |previous| {
+                previous.is_ascii_lowercase() || previous.is_ascii_digit()
+            })
+        {
+            ident.push('_');
+        }
+        ident.push(ch.to_ascii_lowercase());
+        previous = Some(ch);
+    }
+    match ident.as_str() {
+        "self" | "super" | "crate" => ident + "_",
+        keyword if KEYWORDS.contains(&keyword) => format!("r#{ident}"),
+        _ => ident,
+    }
+}
+
+/// The Rust identifier of a symbol or of a field in PascalCase, e.g. for `DARK_RED` or `dark_red`
+/// it is `DarkRed`.
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            let rest: String = chars.collect();
+            if part.chars().all(|ch| !ch.is_ascii_lowercase()) {
+                format!("{first}{}", rest.to_ascii_lowercase())
+            } else {
+                format!("{first}{rest}")
+            }
+        })
+        .collect()
+}
+
+fn unsupported(reason: String) -> Error {
+    Error::GenerateCode(reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(field_ident("userId"), "user_id");
+        assert_eq!(field_ident("user_id"), "user_id");
+        assert_eq!(field_ident("HTTPCode2xx"), "httpcode2xx");
+        assert_eq!(field_ident("type"), "r#type");
+        assert_eq!(field_ident("self"), "self_");
+        assert_eq!(pascal_case("DARK_RED"), "DarkRed");
+        assert_eq!(pascal_case("darkRed"), "DarkRed");
+        assert_eq!(pascal_case("A"), "A");
+    }
+
+    #[test]
+    fn test_generate_record() {
+        let schema = Schema::parse_str(
+            r#"{
+                "type": "record",
+                "name": "User",
+                "namespace": "com.example",
+                "doc": "A user",
+                "fields": [
+                    {"name": "userId", "type": "long", "doc": "The id"},
+                    {"name": "type", "type": {"type": "enum", "name": "Kind", "symbols": ["ADMIN", "Guest"]}, "default": "Guest"},
+                    {"name": "avatar", "type": ["null", "bytes"], "default": null},
+                    {"name": "friends", "type": {"type": "array", "items": "User"}},
+                    {"name": "best_friend", "type": ["null", "User"]},
+                    {"name": "id", "type": {"type": "fixed", "name": "Id", "size": 16}},
+                    {"name": "created", "type": {"type": "long", "logicalType": "timestamp-millis"}}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let expected = r#"// Generated by apache_avro::codegen, do not edit.
+
+/// A user
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, apache_avro::AvroSchema)]
+#[avro(doc = "A user")]
+#[avro(namespace = "com.example")]
+pub struct User {
+    /// The id
+    #[avro(doc = "The id")]
+    #[serde(rename = "userId")]
+    pub user_id: i64,
+
+    #[avro(default = "\"Guest\"")]
+    pub r#type: Kind,
+
+    #[avro(default = "null")]
+    #[avro(bytes)]
+    pub avatar: Option<Vec<u8>>,
+
+    pub friends: Vec<User>,
+
+    pub best_friend: Option<Box<User>>,
+
+    pub id: Id,
+
+    #[avro(logical_type = "timestamp-millis")]
+    pub created: i64,
+}
+
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize, apache_avro::AvroSchema)]
+#[avro(namespace = "com.example")]
+pub enum Kind {
+    #[serde(rename = "ADMIN")]
+    Admin,
+    Guest,
+}
+
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize, apache_avro::AvroSchema)]
+#[avro(namespace = "com.example")]
+#[avro(fixed)]
+pub struct Id(pub [u8; 16]);
+"#;
+        assert_eq!(generate(&[schema]).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_generate_unions() {
+        let schema = Schema::parse_str(
+            r#"{
+                "type": "record",
+                "name": "Event",
+                "fields": [
+                    {"name": "payload", "type": ["int", "string", {"type": "array", "items": ["long", "double"]}]},
+                    {"name": "extra", "type": ["string", "null"]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let code = generate(&[schema]).unwrap();
+        assert!(code.contains("    pub payload: EventPayload,\n"));
+        assert!(code.contains("    pub extra: EventExtra,\n"));
+        assert!(code.contains(
+            "pub enum EventPayload {\n    Int(i32),\n    String(String),\n    Array(Vec<EventPayloadArrayItem>),\n}"
+        ));
+        assert!(
+            code.contains("pub enum EventPayloadArrayItem {\n    Long(i64),\n    Double(f64),\n}")
+        );
+        assert!(code.contains(
+            "#[serde(untagged)]\npub enum EventExtra {\n    String(String),\n    Null,\n}"
+        ));
+    }
+
+    #[test]
+    fn test_generate_enum_with_derives_in_doc() {
+        let schema = Schema::parse_str(
+            r#"{"type": "enum", "name": "Kind", "doc": "Clone, or not", "symbols": ["A"]}"#,
+        )
+        .unwrap();
+        let code = generate(&[schema]).unwrap();
+        assert!(code.contains("/// Clone, or not\n#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize, apache_avro::AvroSchema)]\n#[avro(doc = \"Clone, or not\")]\n"));
+    }
+
+    #[test]
+    fn test_generate_unsupported() {
+        for schema in [
+            r#"{"type": "record", "name": "A", "fields": [{"name": "a", "type": {"type": "array", "items": "bytes"}}]}"#,
+            r#"{"type": "record", "name": "A", "fields": [{"name": "a", "type": {"type": "fixed", "name": "B", "size": 64}}]}"#,
+            r#"{"type": "record", "name": "A", "fields": [{"name": "a", "type": "null"}]}"#,
+            r#"{"type": "record", "name": "type", "fields": []}"#,
+            r#"{"type": "enum", "name": "A", "symbols": ["DARK_RED", "DarkRed"]}"#,
+        ] {
+            let schema = Schema::parse_str(schema).unwrap();
+            assert!(matches!(generate(&[schema]), Err(Error::GenerateCode(_))));
+        }
+    }
+}
@@ -0,0 +1,412 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Logic handling the Confluent wire format, where each message is framed by a zero magic byte
+//! and the 4-byte big-endian id of its schema in a schema registry.
+use crate::{
+    decode::decode_internal,
+    from_value,
+    schema::{AvroSchema, ResolvedOwnedSchema, Schema},
+    ser::Serializer,
+    types::Value,
+    writer::write_value_ref_owned_resolved,
+    AvroResult, Error,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    convert::TryFrom,
+    fs,
+    io::{ErrorKind, Read, Write},
+    marker::PhantomData,
+    path::PathBuf,
+};
+
+/// The magic byte starting every message in the Confluent wire format.
+pub const CONFLUENT_MAGIC_BYTE: u8 = 0;
+
+/// A source of writer schemas, keyed by their schema registry id.
+pub trait SchemaIdStore {
+    /// Returns the schema with the given id, or `None` if there is no such schema.
+    fn lookup(&self, id: u32) -> AvroResult<Option<Schema>>;
+}
+
+impl<S: SchemaIdStore + ?Sized> SchemaIdStore for &S {
+    fn lookup(&self, id: u32) -> AvroResult<Option<Schema>> {
+        (**self).lookup(id)
+    }
+}
+
+/// A `SchemaIdStore` keeping all of its schemas in memory.
+#[derive(Debug, Default, Clone)]
+pub struct InMemorySchemaIdStore {
+    schemas: HashMap<u32, Schema>,
+}
+
+impl InMemorySchemaIdStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a schema with the given id to the store, replacing any previous one.
+    pub fn add(&mut self, id: u32, schema: Schema) {
+        self.schemas.insert(id, schema);
+    }
+}
+
+impl SchemaIdStore for InMemorySchemaIdStore {
+    fn lookup(&self, id: u32) -> AvroResult<Option<Schema>> {
+        Ok(self.schemas.get(&id).cloned())
+    }
+}
+
+/// A `SchemaIdStore` reading the schema with id `N` from the file `N.avsc` of a directory.
+#[derive(Debug, Clone)]
+pub struct DirectorySchemaIdStore {
+    dir: PathBuf,
+}
+
+impl DirectorySchemaIdStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl SchemaIdStore for DirectorySchemaIdStore {
+    fn lookup(&self, id: u32) -> AvroResult<Option<Schema>> {
+        let path = self.dir.join(format!("{id}.avsc"));
+        match fs::read_to_string(&path) {
+            Ok(schema) => Schema::parse_str(&schema).map(Some),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::ReadSchemaFile(path, e)),
+        }
+    }
+}
+
+/// Reader of messages in the Confluent wire format.
+///
+/// The writer schema of each message is looked up by its id in a [`SchemaIdStore`] and cached.
+/// In case a reader `Schema` is provided, schema resolution will also be performed.
+pub struct GenericConfluentReader<S: SchemaIdStore> {
+    store: S,
+    reader_schema: Option<Schema>,
+    writer_schemas: HashMap<u32, ResolvedOwnedSchema>,
+}
+
+impl<S: SchemaIdStore> GenericConfluentReader<S> {
+    /// Creates a `GenericConfluentReader` returning values as written.
+    pub fn new(store: S) -> GenericConfluentReader<S> {
+        GenericConfluentReader {
+            store,
+            reader_schema: None,
+            writer_schemas: HashMap::new(),
+        }
+    }
+
+    /// Creates a `GenericConfluentReader` resolving values to the given reader `Schema`.
+    pub fn with_schema(store: S, reader_schema: Schema) -> GenericConfluentReader<S> {
+        GenericConfluentReader {
+            store,
+            reader_schema: Some(reader_schema),
+            writer_schemas: HashMap::new(),
+        }
+    }
+
+    /// Get a reference to the optional reader `Schema`.
+    pub fn reader_schema(&self) -> Option<&Schema> {
+        self.reader_schema.as_ref()
+    }
+
+    pub fn read_value<R: Read>(&mut self, reader: &mut R) -> AvroResult<Value> {
+        let mut header = [0; 5];
+        reader.read_exact(&mut header).map_err(Error::ReadHeader)?;
+        if header[0] != CONFLUENT_MAGIC_BYTE {
+            return Err(Error::ConfluentMagic(header[0]));
+        }
+        let id = u32::from_be_bytes([header[1], header[2], header[3], header[4]]);
+
+        let writer_schema = match self.writer_schemas.entry(id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let schema = self.store.lookup(id)?.ok_or(Error::UnknownSchemaId(id))?;
+                entry.insert(ResolvedOwnedSchema::try_from(schema)?)
+            }
+        };
+        let value = decode_internal(
+            writer_schema.get_root_schema(),
+            writer_schema.get_names(),
+            &None,
+            reader,
+        )?;
+        match self.reader_schema {
+            Some(ref schema) => value.resolve(schema),
+            None => Ok(value),
+        }
+    }
+}
+
+/// Reader of messages in the Confluent wire format, resolved to the schema of `T`.
+pub struct SpecificConfluentReader<T, S>
+where
+    T: AvroSchema,
+    S: SchemaIdStore,
+{
+    inner: GenericConfluentReader<S>,
+    _model: PhantomData<T>,
+}
+
+impl<T, S> SpecificConfluentReader<T, S>
+where
+    T: AvroSchema,
+    S: SchemaIdStore,
+{
+    pub fn new(store: S) -> SpecificConfluentReader<T, S> {
+        SpecificConfluentReader {
+            inner: GenericConfluentReader::with_schema(store, T::get_schema()),
+            _model: PhantomData,
+        }
+    }
+}
+
+impl<T, S> SpecificConfluentReader<T, S>
+where
+    T: AvroSchema + From<Value>,
+    S: SchemaIdStore,
+{
+    pub fn read_from_value<R: Read>(&mut self, reader: &mut R) -> AvroResult<T> {
+        self.inner.read_value(reader).map(|v| v.into())
+    }
+}
+
+impl<T, S> SpecificConfluentReader<T, S>
+where
+    T: AvroSchema + DeserializeOwned,
+    S: SchemaIdStore,
+{
+    pub fn read<R: Read>(&mut self, reader: &mut R) -> AvroResult<T> {
+        from_value::<T>(&self.inner.read_value(reader)?)
+    }
+}
+
+/// Writer that encodes messages in the Confluent wire format, for a schema registered with the
+/// given id.
+/// Writes all message bytes at once, and drains internal buffer
+pub struct GenericConfluentWriter {
+    buffer: Vec<u8>,
+    resolved: ResolvedOwnedSchema,
+}
+
+impl GenericConfluentWriter {
+    pub fn new(id: u32, schema: Schema) -> AvroResult<GenericConfluentWriter> {
+        let mut buffer = Vec::with_capacity(1024);
+        buffer.push(CONFLUENT_MAGIC_BYTE);
+        buffer.extend_from_slice(&id.to_be_bytes());
+
+        Ok(GenericConfluentWriter {
+            buffer,
+            resolved: ResolvedOwnedSchema::try_from(schema)?,
+        })
+    }
+
+    /// Write the referenced Value to the provided Write object. Returns a result with the number
+    /// of bytes written including the header
+    pub fn write_value_ref<W: Write>(&mut self, v: &Value, writer: &mut W) -> AvroResult<usize> {
+        let result = write_value_ref_owned_resolved(&self.resolved, v, &mut self.buffer)
+            .and_then(|_| writer.write_all(&self.buffer).map_err(Error::WriteBytes))
+            .map(|_| self.buffer.len());
+        self.buffer.truncate(5);
+        result
+    }
+
+    /// Write the Value to the provided Write object. Returns a result with the number of bytes
+    /// written including the header
+    pub fn write_value<W: Write>(&mut self, v: Value, writer: &mut W) -> AvroResult<usize> {
+        self.write_value_ref(&v, writer)
+    }
+}
+
+/// Writer that encodes values of `T` in the Confluent wire format.
+pub struct SpecificConfluentWriter<T>
+where
+    T: AvroSchema,
+{
+    inner: GenericConfluentWriter,
+    _model: PhantomData<T>,
+}
+
+impl<T> SpecificConfluentWriter<T>
+where
+    T: AvroSchema,
+{
+    /// Creates a writer for the schema of `T`, registered with the given id.
+    pub fn new(id: u32) -> AvroResult<SpecificConfluentWriter<T>> {
+        Ok(SpecificConfluentWriter {
+            inner: GenericConfluentWriter::new(id, T::get_schema())?,
+            _model: PhantomData,
+        })
+    }
+}
+
+impl<T> SpecificConfluentWriter<T>
+where
+    T: AvroSchema + Into<Value>,
+{
+    /// Write the `Into<Value>` to the provided Write object. Returns a result with the number
+    /// of bytes written including the header
+    pub fn write_value<W: Write>(&mut self, data: T, writer: &mut W) -> AvroResult<usize> {
+        let v: Value = data.into();
+        self.inner.write_value_ref(&v, writer)
+    }
+}
+
+impl<T> SpecificConfluentWriter<T>
+where
+    T: AvroSchema + Serialize,
+{
+    /// Write the referenced Serialize object to the provided Write object. Returns a result with
+    /// the number of bytes written including the header
+    pub fn write_ref<W: Write>(&mut self, data: &T, writer: &mut W) -> AvroResult<usize> {
+        let mut serializer = Serializer::default();
+        let v = data.serialize(&mut serializer)?;
+        self.inner.write_value_ref(&v, writer)
+    }
+
+    /// Write the Serialize object to the provided Write object. Returns a result with the number
+    /// of bytes written including the header
+    pub fn write<W: Write>(&mut self, data: T, writer: &mut W) -> AvroResult<usize> {
+        self.write_ref(&data, writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Record;
+    use pretty_assertions::assert_eq;
+    use serde::Deserialize;
+
+    const V1: &str = r#"{"type": "record", "name": "Event", "fields": [
+        {"name": "a", "type": "long"}
+    ]}"#;
+    const V2: &str = r#"{"type": "record", "name": "Event", "fields": [
+        {"name": "a", "type": "long"},
+        {"name": "b", "type": "string", "default": "none"}
+    ]}"#;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        a: i64,
+        b: String,
+    }
+
+    impl AvroSchema for Event {
+        fn get_schema() -> Schema {
+            Schema::parse_str(V2).unwrap()
+        }
+    }
+
+    fn write_v1(id: u32, a: i64, out: &mut Vec<u8>) -> usize {
+        let schema = Schema::parse_str(V1).unwrap();
+        let mut record = Record::new(&schema).unwrap();
+        record.put("a", a);
+        GenericConfluentWriter::new(id, schema.clone())
+            .unwrap()
+            .write_value(record.into(), out)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_confluent_framing() {
+        let mut bytes = Vec::new();
+        let written = write_v1(0x01020304, 1, &mut bytes);
+        assert_eq!(written, bytes.len());
+        assert_eq!(bytes, vec![0, 1, 2, 3, 4, 2]);
+    }
+
+    #[test]
+    fn test_generic_confluent_reader() {
+        let mut store = InMemorySchemaIdStore::new();
+        store.add(1, Schema::parse_str(V1).unwrap());
+
+        let mut bytes = Vec::new();
+        write_v1(1, 7, &mut bytes);
+        write_v1(2, 8, &mut bytes);
+
+        let mut reader = GenericConfluentReader::new(&store);
+        let mut input = &bytes[..];
+        assert_eq!(
+            reader.read_value(&mut input).unwrap(),
+            Value::Record(vec![("a".to_string(), Value::Long(7))])
+        );
+        match reader.read_value(&mut input) {
+            Err(Error::UnknownSchemaId(2)) => {}
+            other => panic!("Expected an unknown schema id error, got {other:?}"),
+        }
+
+        match reader.read_value(&mut &[0xC3, 0x01, 0, 0, 0][..]) {
+            Err(Error::ConfluentMagic(0xC3)) => {}
+            other => panic!("Expected a wrong magic byte error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_specific_confluent_reader_and_writer() {
+        let mut store = InMemorySchemaIdStore::new();
+        store.add(1, Schema::parse_str(V1).unwrap());
+        store.add(2, Event::get_schema());
+
+        let event = Event {
+            a: 2,
+            b: "two".to_string(),
+        };
+        let mut bytes = Vec::new();
+        write_v1(1, 1, &mut bytes);
+        SpecificConfluentWriter::<Event>::new(2)
+            .unwrap()
+            .write_ref(&event, &mut bytes)
+            .unwrap();
+
+        let mut reader = SpecificConfluentReader::<Event, _>::new(store);
+        let mut input = &bytes[..];
+        assert_eq!(
+            reader.read(&mut input).unwrap(),
+            Event {
+                a: 1,
+                b: "none".to_string()
+            }
+        );
+        assert_eq!(reader.read(&mut input).unwrap(), event);
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn test_directory_schema_id_store() {
+        let dir = std::env::temp_dir().join(format!("avro_schema_ids_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("3.avsc"), V1).unwrap();
+
+        let store = DirectorySchemaIdStore::new(&dir);
+        assert_eq!(
+            store.lookup(3).unwrap(),
+            Some(Schema::parse_str(V1).unwrap())
+        );
+        assert_eq!(store.lookup(4).unwrap(), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
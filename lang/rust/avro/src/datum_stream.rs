@@ -0,0 +1,393 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Logic handling streams of length-delimited datums, for transports such as sockets and pipes.
+//!
+//! Each message of the stream is the length of its payload, as an Avro `long`, followed by the
+//! payload: the datum encoded in Avro binary format, optionally preceded by the header of the
+//! [single object encoding](https://avro.apache.org/docs/current/spec.html#single_object_encoding).
+use crate::{
+    decode::decode,
+    from_value,
+    rabin::Rabin,
+    schema::{ResolvedSchema, Schema},
+    ser::Serializer,
+    types::Value,
+    util::{self, zig_i64},
+    writer::{single_object_header, write_value_ref_resolved},
+    AvroResult, Error,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    convert::TryFrom,
+    io::{ErrorKind, Read, Write},
+};
+
+/// Writer of a stream of length-delimited datums.
+pub struct DatumStreamWriter<'a, W> {
+    schema: &'a Schema,
+    resolved_schema: ResolvedSchema<'a>,
+    writer: W,
+    header: Option<[u8; 10]>,
+    payload: Vec<u8>,
+    message: Vec<u8>,
+}
+
+impl<'a, W: Write> DatumStreamWriter<'a, W> {
+    /// Creates a `DatumStreamWriter` writing values of the given `Schema` to something
+    /// implementing the `io::Write` trait.
+    pub fn new(schema: &'a Schema, writer: W) -> AvroResult<Self> {
+        Ok(DatumStreamWriter {
+            schema,
+            resolved_schema: ResolvedSchema::try_from(schema)?,
+            writer,
+            header: None,
+            payload: Vec::new(),
+            message: Vec::new(),
+        })
+    }
+
+    /// Precede the datum of each message with the single object encoding header, identifying
+    /// the schema it has been written with.
+    pub fn with_single_object_header(mut self) -> Self {
+        self.header = Some(single_object_header(&self.schema.fingerprint::<Rabin>()));
+        self
+    }
+
+    /// Write a compatible value (implementing the `ToAvro` trait) as a message of the stream,
+    /// also performing schema validation.
+    ///
+    /// Return the number of bytes written.
+    pub fn write<T: Into<Value>>(&mut self, value: T) -> AvroResult<usize> {
+        self.write_value_ref(&value.into())
+    }
+
+    /// Write a referenced `Value` as a message of the stream, also performing schema validation.
+    ///
+    /// Return the number of bytes written.
+    pub fn write_value_ref(&mut self, value: &Value) -> AvroResult<usize> {
+        self.payload.clear();
+        if let Some(header) = self.header {
+            self.payload.extend_from_slice(&header);
+        }
+        write_value_ref_resolved(self.schema, &self.resolved_schema, value, &mut self.payload)?;
+
+        self.message.clear();
+        zig_i64(self.payload.len() as i64, &mut self.message);
+        self.message.extend_from_slice(&self.payload);
+        self.writer
+            .write_all(&self.message)
+            .map_err(Error::WriteBytes)?;
+        Ok(self.message.len())
+    }
+
+    /// Write a value implementing `Serialize` as a message of the stream, also performing
+    /// schema validation.
+    ///
+    /// Return the number of bytes written.
+    pub fn write_ser<S: Serialize>(&mut self, value: &S) -> AvroResult<usize> {
+        let avro_value = value.serialize(&mut Serializer::default())?;
+        self.write_value_ref(&avro_value)
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> AvroResult<()> {
+        self.writer.flush().map_err(Error::WriteBytes)
+    }
+
+    /// Return what the `DatumStreamWriter` is writing to, after flushing it.
+    pub fn into_inner(mut self) -> AvroResult<W> {
+        self.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reader of a stream of length-delimited datums, as written by a `DatumStreamWriter`.
+///
+/// Messages are read whole with `read_exact`, so the underlying reader may return them in as
+/// many pieces as it likes, as sockets and pipes do.
+pub struct DatumStreamReader<'a, R> {
+    writer_schema: &'a Schema,
+    reader_schema: Option<&'a Schema>,
+    reader: R,
+    header: Option<[u8; 10]>,
+    max_message_size: Option<usize>,
+    payload: Vec<u8>,
+    errored: bool,
+}
+
+impl<'a, R: Read> DatumStreamReader<'a, R> {
+    /// Creates a `DatumStreamReader` reading values written with the given `Schema` from
+    /// something implementing the `io::Read` trait.
+    pub fn new(writer_schema: &'a Schema, reader: R) -> Self {
+        DatumStreamReader {
+            writer_schema,
+            reader_schema: None,
+            reader,
+            header: None,
+            max_message_size: None,
+            payload: Vec::new(),
+            errored: false,
+        }
+    }
+
+    /// Resolve the values read to the given reader `Schema`.
+    pub fn with_reader_schema(mut self, reader_schema: &'a Schema) -> Self {
+        self.reader_schema = Some(reader_schema);
+        self
+    }
+
+    /// Expect the datum of each message to be preceded by the single object encoding header of
+    /// the writer `Schema`.
+    pub fn with_single_object_header(mut self) -> Self {
+        self.header = Some(single_object_header(
+            &self.writer_schema.fingerprint::<Rabin>(),
+        ));
+        self
+    }
+
+    /// Fail on messages bigger than `max_message_size` bytes, without reading them. Messages are
+    /// always limited by [`max_allocation_bytes`](fn.max_allocation_bytes.html).
+    pub fn with_max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = Some(max_message_size);
+        self
+    }
+
+    /// Read the next message of the stream, returning `None` if the stream ended cleanly before
+    /// it.
+    pub fn read_value(&mut self) -> AvroResult<Option<Value>> {
+        let size = match self.read_message_size()? {
+            Some(size) => size,
+            None => return Ok(None),
+        };
+        self.payload.resize(size, 0);
+        self.reader
+            .read_exact(&mut self.payload)
+            .map_err(Error::ReadIntoBuf)?;
+
+        let mut datum = &self.payload[..];
+        if let Some(expected) = self.header {
+            let mut actual = [0; 10];
+            datum.read_exact(&mut actual).map_err(Error::ReadHeader)?;
+            if actual != expected {
+                return Err(Error::SingleObjectHeaderMismatch(expected, actual));
+            }
+        }
+        let value = decode(self.writer_schema, &mut datum)?;
+        if !datum.is_empty() {
+            return Err(Error::DatumStreamTrailingBytes(datum.len()));
+        }
+        match self.reader_schema {
+            Some(schema) => value.resolve(schema).map(Some),
+            None => Ok(Some(value)),
+        }
+    }
+
+    /// Read the next message of the stream, deserializing it with serde.
+    pub fn read<T: DeserializeOwned>(&mut self) -> AvroResult<Option<T>> {
+        match self.read_value()? {
+            Some(value) => from_value::<T>(&value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Return what the `DatumStreamReader` is reading from.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read_message_size(&mut self) -> AvroResult<Option<usize>> {
+        let mut first = [0u8; 1];
+        loop {
+            match self.reader.read(&mut first) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(Error::ReadVariableIntegerBytes(e)),
+            }
+        }
+        let size = util::read_long(&mut (&first[..]).chain(&mut self.reader))?;
+        let size = usize::try_from(size).map_err(|_| Error::DatumStreamMessageSize(size))?;
+        match self.max_message_size {
+            Some(max) if size > max => Err(Error::DatumStreamMessageTooBig { size, max }),
+            _ => util::safe_len(size).map(Some),
+        }
+    }
+}
+
+impl<'a, R: Read> Iterator for DatumStreamReader<'a, R> {
+    type Item = AvroResult<Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // to prevent keep on reading after the first error occurs
+        if self.errored {
+            return None;
+        };
+        match self.read_value() {
+            Ok(opt) => opt.map(Ok),
+            Err(e) => {
+                self.errored = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Record;
+    use pretty_assertions::assert_eq;
+    use serde::Deserialize;
+
+    const SCHEMA: &str = r#"{"type": "record", "name": "Event", "fields": [
+        {"name": "a", "type": "long"},
+        {"name": "b", "type": "string"}
+    ]}"#;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event {
+        a: i64,
+        b: String,
+    }
+
+    /// Returns at most one byte per read, like a slow socket.
+    struct OneByteReader<'b>(&'b [u8]);
+
+    impl Read for OneByteReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(self.0.len()).min(1);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    fn write_events(single_object_header: bool) -> Vec<u8> {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let mut writer = DatumStreamWriter::new(&schema, Vec::new()).unwrap();
+        if single_object_header {
+            writer = writer.with_single_object_header();
+        }
+        let mut record = Record::new(&schema).unwrap();
+        record.put("a", 1_i64);
+        record.put("b", "one");
+        let mut n = writer.write(record).unwrap();
+        n += writer
+            .write_ser(&Event {
+                a: 2,
+                b: "two".to_string(),
+            })
+            .unwrap();
+        let bytes = writer.into_inner().unwrap();
+        assert_eq!(n, bytes.len());
+        bytes
+    }
+
+    #[test]
+    fn test_datum_stream_format() {
+        let bytes = write_events(false);
+        assert_eq!(&bytes[..6], &[10, 2, 6, b'o', b'n', b'e']);
+
+        let bytes = write_events(true);
+        assert_eq!(bytes[0], 30);
+        assert_eq!(&bytes[1..3], &[0xC3, 0x01]);
+    }
+
+    #[test]
+    fn test_datum_stream_roundtrip() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        for single_object_header in [false, true] {
+            let bytes = write_events(single_object_header);
+            let mut reader = DatumStreamReader::new(&schema, OneByteReader(&bytes));
+            if single_object_header {
+                reader = reader.with_single_object_header();
+            }
+            assert_eq!(
+                reader.read::<Event>().unwrap(),
+                Some(Event {
+                    a: 1,
+                    b: "one".to_string()
+                })
+            );
+            assert_eq!(
+                reader.read::<Event>().unwrap(),
+                Some(Event {
+                    a: 2,
+                    b: "two".to_string()
+                })
+            );
+            assert_eq!(reader.read::<Event>().unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn test_datum_stream_schema_resolution() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let reader_schema = Schema::parse_str(
+            r#"{"type": "record", "name": "Event", "fields": [
+                {"name": "b", "type": "string"},
+                {"name": "c", "type": "int", "default": 3}
+            ]}"#,
+        )
+        .unwrap();
+        let bytes = write_events(false);
+        let values: Vec<_> = DatumStreamReader::new(&schema, &bytes[..])
+            .with_reader_schema(&reader_schema)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(
+            values[1],
+            Value::Record(vec![
+                ("b".to_string(), Value::String("two".to_string())),
+                ("c".to_string(), Value::Int(3)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_datum_stream_errors() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let bytes = write_events(false);
+
+        let mut reader = DatumStreamReader::new(&schema, &bytes[..]).with_max_message_size(4);
+        assert!(matches!(
+            reader.read_value(),
+            Err(Error::DatumStreamMessageTooBig { size: 5, max: 4 })
+        ));
+
+        let mut reader = DatumStreamReader::new(&schema, &bytes[..bytes.len() - 1]);
+        assert!(reader.read_value().unwrap().is_some());
+        assert!(matches!(reader.read_value(), Err(Error::ReadIntoBuf(_))));
+
+        let mut reader = DatumStreamReader::new(&schema, &bytes[..]).with_single_object_header();
+        assert!(reader.read_value().is_err());
+
+        // a datum shorter than its message
+        let mut reader = DatumStreamReader::new(&schema, &[12, 2, 6, b'o', b'n', b'e', 0][..]);
+        assert!(matches!(
+            reader.read_value(),
+            Err(Error::DatumStreamTrailingBytes(1))
+        ));
+
+        let mut reader = DatumStreamReader::new(&schema, &[1][..]);
+        assert!(matches!(
+            reader.read_value(),
+            Err(Error::DatumStreamMessageSize(-1))
+        ));
+    }
+}
@@ -18,7 +18,7 @@
 //! Logic for serde-compatible deserialization.
 use crate::{types::Value, Error};
 use serde::{
-    de::{self, DeserializeSeed, Visitor},
+    de::{self, DeserializeSeed, IntoDeserializer, Visitor},
     forward_to_deserialize_any, Deserialize,
 };
 use std::{
@@ -55,6 +55,18 @@ pub struct EnumDeserializer<'de> {
     input: &'de [(String, Value)],
 }
 
+/// Deserializer of the variants of enums with data, which are represented as unions whose
+/// branches are the variants.
+struct UnionEnumDeserializer<'de> {
+    index: u32,
+    input: &'de Value,
+}
+
+/// Deserializer of the fields of a record as a sequence, e.g. for tuple variants.
+struct RecordFieldsSeqDeserializer<'de> {
+    input: Iter<'de, (String, Value)>,
+}
+
 impl<'de> Deserializer<'de> {
     pub fn new(input: &'de Value) -> Self {
         Deserializer { input }
@@ -177,6 +189,65 @@ impl<'de> de::EnumAccess<'de> for EnumDeserializer<'de> {
     }
 }
 
+impl<'de> de::EnumAccess<'de> for UnionEnumDeserializer<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        // serde identifies variants by their index as well as by their name
+        let index: de::value::U32Deserializer<Error> = self.index.into_deserializer();
+        Ok((seed.deserialize(index)?, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for UnionEnumDeserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.input {
+            Value::Null => Ok(()),
+            Value::Record(fields) if fields.is_empty() => Ok(()),
+            _ => Err(de::Error::custom(format!(
+                "Expected an empty Record for a unit variant, got {:?}",
+                self.input
+            ))),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(&Deserializer::new(self.input))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.input {
+            Value::Record(fields) => visitor.visit_seq(RecordFieldsSeqDeserializer {
+                input: fields.iter(),
+            }),
+            _ => de::Deserializer::deserialize_seq(&Deserializer::new(self.input), visitor),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_struct(&Deserializer::new(self.input), "", fields, visitor)
+    }
+}
+
 impl<'de> de::VariantAccess<'de> for EnumDeserializer<'de> {
     type Error = Error;
 
@@ -346,8 +417,9 @@ impl<'a, 'de> de::Deserializer<'de> for &'a Deserializer<'de> {
             Value::String(ref s) => visitor.visit_bytes(s.as_bytes()),
             Value::Bytes(ref bytes) | Value::Fixed(_, ref bytes) => visitor.visit_bytes(bytes),
             Value::Uuid(ref u) => visitor.visit_bytes(u.as_bytes()),
+            Value::Decimal(ref d) => visitor.visit_byte_buf(Vec::try_from(d)?),
             _ => Err(de::Error::custom(format!(
-                "Expected a String|Bytes|Fixed|Uuid, but got {:?}",
+                "Expected a String|Bytes|Fixed|Uuid|Decimal, but got {:?}",
                 self.input
             ))),
         }
@@ -362,8 +434,9 @@ impl<'a, 'de> de::Deserializer<'de> for &'a Deserializer<'de> {
             Value::Bytes(ref bytes) | Value::Fixed(_, ref bytes) => {
                 visitor.visit_byte_buf(bytes.to_owned())
             }
+            Value::Decimal(ref d) => visitor.visit_byte_buf(Vec::try_from(d)?),
             _ => Err(de::Error::custom(format!(
-                "Expected a String|Bytes|Fixed, but got {:?}",
+                "Expected a String|Bytes|Fixed|Decimal, but got {:?}",
                 self.input
             ))),
         }
@@ -431,6 +504,13 @@ impl<'a, 'de> de::Deserializer<'de> for &'a Deserializer<'de> {
     {
         match *self.input {
             Value::Array(ref items) => visitor.visit_seq(SeqDeserializer::new(items)),
+            // e.g. byte arrays, which are fixed
+            Value::Bytes(ref bytes) | Value::Fixed(_, ref bytes) => visitor.visit_seq(
+                de::value::SeqDeserializer::<_, Error>::new(bytes.iter().copied()),
+            ),
+            Value::Decimal(ref d) => visitor.visit_seq(
+                de::value::SeqDeserializer::<_, Error>::new(Vec::try_from(d)?.into_iter()),
+            ),
             Value::Union(_i, ref inner) => match **inner {
                 Value::Array(ref items) => visitor.visit_seq(SeqDeserializer::new(items)),
                 Value::Null => visitor.visit_seq(SeqDeserializer::new(&[])),
@@ -454,14 +534,23 @@ impl<'a, 'de> de::Deserializer<'de> for &'a Deserializer<'de> {
 
     fn deserialize_tuple_struct<V>(
         self,
-        _struct_name: &'static str,
-        _len: usize,
+        struct_name: &'static str,
+        len: usize,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        match *self.input {
+            // tuple structs are records with positional field names
+            Value::Record(ref fields) => visitor.visit_seq(RecordFieldsSeqDeserializer {
+                input: fields.iter(),
+            }),
+            Value::Union(_i, ref inner) if matches!(**inner, Value::Record(_)) => {
+                Deserializer::new(inner).deserialize_tuple_struct(struct_name, len, visitor)
+            }
+            _ => self.deserialize_seq(visitor),
+        }
     }
 
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -517,8 +606,12 @@ impl<'a, 'de> de::Deserializer<'de> for &'a Deserializer<'de> {
             Value::Record(ref fields) => visitor.visit_enum(EnumDeserializer::new(fields)),
             // This has to be a unit Enum
             Value::Enum(_index, ref field) => visitor.visit_enum(EnumUnitDeserializer::new(field)),
+            Value::Union(index, ref inner) => visitor.visit_enum(UnionEnumDeserializer {
+                index,
+                input: inner,
+            }),
             _ => Err(de::Error::custom(format!(
-                "Expected a Record|Enum, but got {:?}",
+                "Expected a Record|Enum|Union, but got {:?}",
                 self.input
             ))),
         }
@@ -553,6 +646,20 @@ impl<'de> de::SeqAccess<'de> for SeqDeserializer<'de> {
     }
 }
 
+impl<'de> de::SeqAccess<'de> for RecordFieldsSeqDeserializer<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.input.next() {
+            Some((_, item)) => seed.deserialize(&Deserializer::new(item)).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
 impl<'de> de::MapAccess<'de> for MapDeserializer<'de> {
     type Error = Error;
 
@@ -1006,8 +1113,97 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_value_tuple_struct() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Point(i32, i32);
+
+        let test = Value::Record(vec![
+            ("field_0".to_owned(), Value::Int(1)),
+            ("field_1".to_owned(), Value::Int(2)),
+        ]);
+        let final_value: Point = from_value(&test).unwrap();
+        assert_eq!(final_value, Point(1, 2));
+
+        let test = Value::Union(1, Box::new(test));
+        let final_value: Point = from_value(&test).unwrap();
+        assert_eq!(final_value, Point(1, 2));
+
+        let test = Value::Array(vec![Value::Int(3), Value::Int(4)]);
+        let final_value: Point = from_value(&test).unwrap();
+        assert_eq!(final_value, Point(3, 4));
+    }
+
+    #[test]
+    fn test_from_value_union_enum() -> TestResult<()> {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        enum Event {
+            Created(String),
+            Deleted { id: i64 },
+            Noop,
+            Pair(i32, String),
+        }
+
+        let cases = vec![
+            (
+                Event::Created("a".to_owned()),
+                Value::Union(0, Box::new(Value::String("a".to_owned()))),
+            ),
+            (
+                Event::Deleted { id: 3 },
+                Value::Union(
+                    1,
+                    Box::new(Value::Record(vec![("id".to_owned(), Value::Long(3))])),
+                ),
+            ),
+            (
+                Event::Noop,
+                Value::Union(2, Box::new(Value::Record(Vec::new()))),
+            ),
+            (
+                Event::Pair(1, "b".to_owned()),
+                Value::Union(
+                    3,
+                    Box::new(Value::Record(vec![
+                        ("field_0".to_owned(), Value::Int(1)),
+                        ("field_1".to_owned(), Value::String("b".to_owned())),
+                    ])),
+                ),
+            ),
+        ];
+        for (expected, value) in cases {
+            assert_eq!(from_value::<Event>(&value)?, expected);
+            assert_eq!(from_value::<Event>(&crate::to_value(&expected)?)?, expected);
+        }
+        assert_eq!(
+            from_value::<Event>(&Value::Enum(2, "Noop".to_owned()))?,
+            Event::Noop
+        );
+        assert!(from_value::<Event>(&Value::Union(4, Box::new(Value::Null))).is_err());
+        Ok(())
+    }
+
     type TestResult<T> = Result<T, Box<dyn std::error::Error>>;
 
+    #[test]
+    fn test_from_value_byte_array() -> TestResult<()> {
+        let expected = [0u8, 1, 2, 255];
+        assert_eq!(
+            from_value::<[u8; 4]>(&Value::Fixed(4, vec![0, 1, 2, 255]))?,
+            expected
+        );
+        assert_eq!(
+            from_value::<[u8; 4]>(&Value::Bytes(vec![0, 1, 2, 255]))?,
+            expected
+        );
+        assert!(from_value::<[u8; 4]>(&Value::Fixed(3, vec![0, 1, 2])).is_err());
+        assert_eq!(
+            from_value::<Vec<u8>>(&Value::Decimal(crate::Decimal::from(vec![1, 24])))?,
+            vec![1, 24]
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_date() -> TestResult<()> {
         let raw_value = 1;
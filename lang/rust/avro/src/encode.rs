@@ -66,6 +66,35 @@ pub(crate) fn encode_internal<S: Borrow<Schema>>(
         return encode_internal(value, resolved.borrow(), names, enclosing_namespace, buffer);
     }
 
+    if let (Value::Union(..), Schema::Record { .. }) = (value, schema) {
+        // e.g. a variant of an enum with data, written with a schema of its former representation
+        if let Some(legacy) = value.as_legacy_variant(schema, names, enclosing_namespace) {
+            return encode_internal(&legacy, schema, names, enclosing_namespace, buffer);
+        }
+    }
+
+    if let Schema::Union(union) = schema {
+        // whether the value knows its position in the union
+        let tagged = match value {
+            Value::Union(..) => true,
+            // the unit variants of enums with data are serialized as `Enum`s, which encode their
+            // index just like the empty records they are represented by
+            Value::Enum(i, _) => union.is_unit_variant(*i, names, enclosing_namespace),
+            _ => false,
+        };
+        if !tagged {
+            // e.g. the values of untagged enums, serialized without their variant
+            let (idx, inner_schema) = union
+                .find_schema_with_known_schemata(value, names, enclosing_namespace)
+                .ok_or(Error::EncodeValueAsSchemaError {
+                    value_kind: ValueKind::from(value),
+                    supported_schema: vec![SchemaKind::Union],
+                })?;
+            encode_long(idx as i64, buffer);
+            return encode_internal(value, inner_schema, names, enclosing_namespace, buffer);
+        }
+    }
+
     match value {
         Value::Null => (),
         Value::Boolean(b) => buffer.push(u8::from(*b)),
@@ -114,6 +143,9 @@ pub(crate) fn encode_internal<S: Borrow<Schema>>(
         Value::Bytes(bytes) => match *schema {
             Schema::Bytes => encode_bytes(bytes, buffer),
             Schema::Fixed { .. } => buffer.extend(bytes),
+            Schema::Decimal { ref inner, .. } => {
+                encode_internal(value, inner, names, enclosing_namespace, buffer)?
+            }
             _ => {
                 return Err(Error::EncodeValueAsSchemaError {
                     value_kind: ValueKind::Bytes,
@@ -167,11 +199,54 @@ pub(crate) fn encode_internal<S: Borrow<Schema>>(
                     }
                 }
                 buffer.push(0u8);
+            } else if let Schema::Fixed { size, .. } = *schema {
+                // e.g. byte arrays, which serde serializes as tuples
+                if items.len() != size {
+                    return Err(Error::CompareFixedSizes {
+                        size,
+                        n: items.len(),
+                    });
+                }
+                for item in items.iter() {
+                    buffer.push(item.clone().try_u8()?);
+                }
+            } else if let Schema::Bytes = *schema {
+                // e.g. byte vectors, which serde serializes as sequences
+                let bytes = items
+                    .iter()
+                    .map(|item| item.clone().try_u8())
+                    .collect::<Result<Vec<_>, _>>()?;
+                encode_bytes(&bytes, buffer);
+            } else if let Schema::Decimal { ref inner, .. } = *schema {
+                encode_internal(value, inner, names, enclosing_namespace, buffer)?;
+            } else if let Schema::Record {
+                ref name,
+                fields: ref schema_fields,
+                ..
+            } = *schema
+            {
+                // e.g. tuple structs, which serde serializes as sequences of their fields
+                if items.len() != schema_fields.len() {
+                    return Err(Error::CompareRecordFields {
+                        fields: schema_fields.len(),
+                        n: items.len(),
+                    });
+                }
+                let record_namespace = name.fully_qualified_name(enclosing_namespace).namespace;
+                for (item, field) in items.iter().zip(schema_fields) {
+                    encode_internal(item, &field.schema, names, &record_namespace, buffer)?;
+                }
             } else {
                 error!("invalid schema type for Array: {:?}", schema);
                 return Err(Error::EncodeValueAsSchemaError {
                     value_kind: ValueKind::Array,
-                    supported_schema: vec![SchemaKind::Array],
+                    supported_schema: vec![
+                        SchemaKind::Array,
+                        SchemaKind::Fixed,
+                        SchemaKind::Bytes,
+                        SchemaKind::Decimal,
+                        SchemaKind::Record,
+                    ],
                 });
             }
         }
@@ -185,11 +260,34 @@ pub(crate) fn encode_internal<S: Borrow<Schema>>(
                     }
                 }
                 buffer.push(0u8);
+            } else if let Schema::Record {
+                ref name,
+                fields: ref schema_fields,
+                ..
+            } = *schema
+            {
+                // e.g. structs with flattened fields, which serde serializes as maps
+                let record_namespace = name.fully_qualified_name(enclosing_namespace).namespace;
+                for field in schema_fields {
+                    match items.get(&field.name) {
+                        Some(value) => {
+                            encode_internal(value, &field.schema, names, &record_namespace, buffer)?
+                        }
+                        None if field.is_nullable() => encode_internal(
+                            &Value::Null,
+                            &field.schema,
+                            names,
+                            &record_namespace,
+                            buffer,
+                        )?,
+                        None => return Err(Error::GetField(field.name.clone())),
+                    }
+                }
             } else {
                 error!("invalid schema type for Map: {:?}", schema);
                 return Err(Error::EncodeValueAsSchemaError {
                     value_kind: ValueKind::Map,
-                    supported_schema: vec![SchemaKind::Map],
+                    supported_schema: vec![SchemaKind::Map, SchemaKind::Record],
                 });
             }
         }
@@ -836,4 +934,99 @@ pub(crate) mod tests {
         assert!(encoded.is_ok());
         assert!(!buffer.is_empty());
     }
+
+    #[test]
+    fn test_encode_array_as_bytes() {
+        let bytes = Value::Array(vec![Value::Int(1), Value::Int(24)]);
+        let expected = encode_to_vec(&Value::Bytes(vec![1, 24]), &Schema::Bytes).unwrap();
+        assert_eq!(encode_to_vec(&bytes, &Schema::Bytes).unwrap(), expected);
+        let decimal = Schema::Decimal {
+            precision: 4,
+            scale: 2,
+            inner: Box::new(Schema::Bytes),
+        };
+        assert_eq!(encode_to_vec(&bytes, &decimal).unwrap(), expected);
+        assert_eq!(
+            encode_to_vec(&Value::Bytes(vec![1, 24]), &decimal).unwrap(),
+            expected
+        );
+
+        let not_bytes = Value::Array(vec![Value::Int(1), Value::Int(256)]);
+        assert!(encode_to_vec(&not_bytes, &Schema::Bytes).is_err());
+    }
+
+    #[test]
+    fn test_encode_map_as_record() {
+        let schema = Schema::parse_str(
+            r#"{
+                "type": "record",
+                "name": "test",
+                "fields": [
+                    {"name": "a", "type": "long"},
+                    {"name": "b", "type": ["null", "string"]},
+                    {"name": "c", "type": "string"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let record = Value::Record(vec![
+            ("a".into(), Value::Long(27)),
+            ("b".into(), Value::Union(0, Box::new(Value::Null))),
+            ("c".into(), Value::String("foo".into())),
+        ]);
+        let map = Value::Map(
+            vec![
+                ("c".to_owned(), Value::String("foo".into())),
+                ("a".to_owned(), Value::Long(27)),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(
+            encode_to_vec(&map, &schema).unwrap(),
+            encode_to_vec(&record, &schema).unwrap()
+        );
+
+        let incomplete = Value::Map(
+            vec![("a".to_owned(), Value::Long(27))]
+                .into_iter()
+                .collect(),
+        );
+        assert!(matches!(
+            encode_to_vec(&incomplete, &schema),
+            Err(Error::GetField(field)) if field == "c"
+        ));
+    }
+
+    #[test]
+    fn test_encode_array_as_record() {
+        let schema = Schema::parse_str(
+            r#"{
+                "type": "record",
+                "name": "Point",
+                "fields": [
+                    {"name": "field_0", "type": "int"},
+                    {"name": "field_1", "type": "string"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let record = Value::Record(vec![
+            ("field_0".into(), Value::Int(1)),
+            ("field_1".into(), Value::String("foo".into())),
+        ]);
+        let array = Value::Array(vec![Value::Int(1), Value::String("foo".into())]);
+        assert!(array.validate(&schema));
+        assert_eq!(
+            encode_to_vec(&array, &schema).unwrap(),
+            encode_to_vec(&record, &schema).unwrap()
+        );
+
+        let incomplete = Value::Array(vec![Value::Int(1)]);
+        assert!(!incomplete.validate(&schema));
+        assert!(matches!(
+            encode_to_vec(&incomplete, &schema),
+            Err(Error::CompareRecordFields { fields: 2, n: 1 })
+        ));
+    }
 }
@@ -0,0 +1,197 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Logic for the encryption of container file blocks.
+//!
+//! Blocks are encrypted with AES-256-GCM once compressed, each with its own random nonce which
+//! precedes the ciphertext. The sync marker of the file, the offset of the block in it and its
+//! number of values are authenticated along with it, so that blocks cannot be moved between or
+//! within files, nor have their count altered. Blocks stay delimited by sync markers, keeping
+//! encrypted files splittable.
+//!
+//! Only the blocks are authenticated: the header, including its `avro.encryption` metadata, is
+//! not, so it must be trusted by other means.
+use crate::{AvroResult, Error};
+use aes_gcm::{
+    aead::{AeadCore, AeadInPlace, KeyInit, OsRng},
+    Aes256Gcm, Key, Nonce,
+};
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// The metadata key under which the encryption algorithm is written.
+pub(crate) const ENCRYPTION_KEY: &str = "avro.encryption";
+/// The metadata key under which the id of the encryption key is written.
+pub(crate) const ENCRYPTION_KEY_ID_KEY: &str = "avro.encryption.key.id";
+/// The only supported encryption algorithm.
+pub(crate) const AES_256_GCM: &str = "aes-256-gcm";
+
+const NONCE_LEN: usize = 12;
+
+/// A 256 bits AES key encrypting blocks, along with the id under which readers look it up.
+#[derive(Clone)]
+pub struct EncryptionKey {
+    id: String,
+    cipher: Aes256Gcm,
+}
+
+impl EncryptionKey {
+    /// Creates an `EncryptionKey` from its id and the bytes of the key.
+    pub fn new(id: impl Into<String>, key: [u8; 32]) -> Self {
+        EncryptionKey {
+            id: id.into(),
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+        }
+    }
+
+    /// The id of the key, written in the header of the files it encrypts.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Encrypt in-place a compressed block, starting at `offset` in the file, prepending the
+    /// nonce to it.
+    pub(crate) fn encrypt_block(
+        &self,
+        marker: &[u8; 16],
+        offset: u64,
+        num_values: usize,
+        block: &mut Vec<u8>,
+    ) -> AvroResult<()> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        self.cipher
+            .encrypt_in_place(&nonce, &associated_data(marker, offset, num_values), block)
+            .map_err(|_| Error::EncryptBlock)?;
+        block.splice(0..0, nonce);
+        Ok(())
+    }
+
+    /// Decrypt in-place a block encrypted by `encrypt_block`, checking its authenticity.
+    pub(crate) fn decrypt_block(
+        &self,
+        marker: &[u8; 16],
+        offset: u64,
+        num_values: usize,
+        block: &mut Vec<u8>,
+    ) -> AvroResult<()> {
+        if block.len() < NONCE_LEN {
+            return Err(Error::BlockAuthentication);
+        }
+        let nonce = *Nonce::from_slice(&block[..NONCE_LEN]);
+        block.drain(..NONCE_LEN);
+        self.cipher
+            .decrypt_in_place(&nonce, &associated_data(marker, offset, num_values), block)
+            .map_err(|_| Error::BlockAuthentication)
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // never print the key itself
+        f.debug_struct("EncryptionKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+fn associated_data(marker: &[u8; 16], offset: u64, num_values: usize) -> [u8; 32] {
+    let mut data = [0; 32];
+    data[..16].copy_from_slice(marker);
+    data[16..24].copy_from_slice(&offset.to_le_bytes());
+    data[24..].copy_from_slice(&(num_values as u64).to_le_bytes());
+    data
+}
+
+/// A source of `EncryptionKey`s, keyed by their id, for the `Reader` and for the `Writer`
+/// appending to an encrypted file.
+pub trait KeyProvider {
+    /// Returns the key with the given id, if known.
+    fn encryption_key(&self, key_id: &str) -> Option<Arc<EncryptionKey>>;
+}
+
+impl KeyProvider for HashMap<String, Arc<EncryptionKey>> {
+    fn encryption_key(&self, key_id: &str) -> Option<Arc<EncryptionKey>> {
+        self.get(key_id).cloned()
+    }
+}
+
+impl<F> KeyProvider for F
+where
+    F: Fn(&str) -> Option<Arc<EncryptionKey>>,
+{
+    fn encryption_key(&self, key_id: &str) -> Option<Arc<EncryptionKey>> {
+        self(key_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::{assert_eq, assert_ne};
+
+    const MARKER: [u8; 16] = [7; 16];
+
+    #[test]
+    fn test_encrypt_decrypt_block() {
+        let key = EncryptionKey::new("k1", [1; 32]);
+        let mut block = b"some compressed block".to_vec();
+        key.encrypt_block(&MARKER, 100, 3, &mut block).unwrap();
+        assert_eq!(block.len(), 21 + NONCE_LEN + 16);
+        assert_ne!(&block[NONCE_LEN..NONCE_LEN + 21], b"some compressed block");
+
+        let mut other = b"some compressed block".to_vec();
+        key.encrypt_block(&MARKER, 100, 3, &mut other).unwrap();
+        assert_ne!(block, other, "nonces must not be reused");
+
+        key.decrypt_block(&MARKER, 100, 3, &mut block).unwrap();
+        assert_eq!(block, b"some compressed block");
+    }
+
+    #[test]
+    fn test_decrypt_block_authentication() {
+        let key = EncryptionKey::new("k1", [1; 32]);
+        let mut block = b"some compressed block".to_vec();
+        key.encrypt_block(&MARKER, 100, 3, &mut block).unwrap();
+
+        let mut tampered = block.clone();
+        tampered[NONCE_LEN + 2] ^= 1;
+        assert!(matches!(
+            key.decrypt_block(&MARKER, 100, 3, &mut tampered),
+            Err(Error::BlockAuthentication)
+        ));
+        assert!(matches!(
+            key.decrypt_block(&MARKER, 100, 4, &mut block.clone()),
+            Err(Error::BlockAuthentication)
+        ));
+        assert!(matches!(
+            key.decrypt_block(&MARKER, 200, 3, &mut block.clone()),
+            Err(Error::BlockAuthentication)
+        ));
+        assert!(matches!(
+            key.decrypt_block(&[8; 16], 100, 3, &mut block.clone()),
+            Err(Error::BlockAuthentication)
+        ));
+        assert!(matches!(
+            EncryptionKey::new("k2", [2; 32]).decrypt_block(&MARKER, 100, 3, &mut block),
+            Err(Error::BlockAuthentication)
+        ));
+        assert!(matches!(
+            key.decrypt_block(&MARKER, 100, 3, &mut vec![0; 5]),
+            Err(Error::BlockAuthentication)
+        ));
+        assert_eq!(format!("{key:?}"), r#"EncryptionKey { id: "k1", .. }"#);
+    }
+}
@@ -16,6 +16,7 @@
 // under the License.
 
 use crate::{
+    codec::Codec,
     schema::{Name, SchemaKind},
     types::ValueKind,
 };
@@ -26,6 +27,9 @@ pub enum Error {
     #[error("Bad Snappy CRC32; expected {expected:x} but got {actual:x}")]
     SnappyCrc32 { expected: u32, actual: u32 },
 
+    #[error("Snappy block of {0} bytes is too short to end with its CRC32")]
+    SnappyMissingCrc32(usize),
+
     #[error("Invalid u8 for bool: {0}")]
     BoolValue(u8),
 
@@ -181,6 +185,9 @@ pub enum Error {
     #[error("Fixed size mismatch, {size} expected, got {n}")]
     CompareFixedSizes { size: usize, n: usize },
 
+    #[error("Record fields mismatch, {fields} expected, got {n}")]
+    CompareRecordFields { fields: usize, n: usize },
+
     #[error("String expected for fixed, got {0:?}")]
     GetStringForFixed(ValueKind),
 
@@ -346,6 +353,65 @@ pub enum Error {
     #[error("Failed to decompress with zstd")]
     ZstdDecompress(#[source] std::io::Error),
 
+    #[error("Failed to train a zstd dictionary")]
+    ZstdTrainDictionary(#[source] std::io::Error),
+
+    #[error("Block compressed with zstd dictionary {0}, which has not been supplied")]
+    MissingZstdDictionary(u32),
+
+    #[error("Invalid zstd dictionary id in metadata")]
+    BadZstdDictionaryId,
+
+    #[error("Failed to encrypt block")]
+    EncryptBlock,
+
+    #[error(
+        "Block failed authentication: it is damaged, tampered with or encrypted with another key"
+    )]
+    BlockAuthentication,
+
+    #[error("Blocks encrypted with key {0:?}, which has not been supplied")]
+    MissingEncryptionKey(String),
+
+    #[error("Unsupported encryption algorithm: {0}")]
+    UnsupportedEncryption(String),
+
+    #[error("Invalid encryption metadata")]
+    BadEncryptionMetadata,
+
+    #[error("The file is encrypted but the `encryption` feature is not enabled")]
+    EncryptionNotEnabled,
+
+    #[error("Block checksum mismatch. Expected: {expected:#010x}. Actual: {actual:#010x}")]
+    BlockChecksumMismatch { expected: u32, actual: u32 },
+
+    #[error("No checksum recorded for the block")]
+    MissingBlockChecksum,
+
+    #[error("Block with a recorded checksum not found in the file")]
+    MissingBlock,
+
+    #[error("The block checksums belong to another file, whose sync marker is {0:?}")]
+    BlockChecksumsMarkerMismatch([u8; 16]),
+
+    #[error("Invalid block checksums index")]
+    BadBlockChecksums,
+
+    #[error("Failed to compress with bzip2")]
+    Bzip2Compress(#[source] std::io::Error),
+
+    #[error("Failed to decompress with bzip2")]
+    Bzip2Decompress(#[source] std::io::Error),
+
+    #[error("Failed to compress with xz")]
+    XzCompress(#[source] std::io::Error),
+
+    #[error("Failed to decompress with xz")]
+    XzDecompress(#[source] std::io::Error),
+
+    #[error("Invalid compression level {level} for codec {codec:?}")]
+    InvalidCompressionLevel { codec: Codec, level: i32 },
+
     #[error("Failed to read header")]
     ReadHeader(#[source] std::io::Error),
 
@@ -355,6 +421,45 @@ pub enum Error {
     #[error("Message Header mismatch. Expected: {0:?}. Actual: {1:?}")]
     SingleObjectHeaderMismatch([u8; 10], [u8; 10]),
 
+    #[error("Invalid datum stream message size: {0}")]
+    DatumStreamMessageSize(i64),
+
+    #[error("Datum stream message of {size} bytes exceeds the maximum of {max} bytes")]
+    DatumStreamMessageTooBig { size: usize, max: usize },
+
+    #[error("{0} bytes left in the datum stream message after decoding its datum")]
+    DatumStreamTrailingBytes(usize),
+
+    #[error("No schema with fingerprint {0:?} in the schema store")]
+    UnknownSchemaFingerprint([u8; 8]),
+
+    #[error("Wrong magic byte in Confluent wire format header: {0}")]
+    ConfluentMagic(u8),
+
+    #[error("No schema with id {0} in the schema store")]
+    UnknownSchemaId(u32),
+
+    #[error("Failed to read schema file {0:?}")]
+    ReadSchemaFile(std::path::PathBuf, #[source] std::io::Error),
+
+    #[error("Schema is incompatible with the latest version of subject {0}")]
+    SchemaRegistryIncompatible(String),
+
+    #[error("Schema registry request failed: {0}")]
+    SchemaRegistryRequest(String),
+
+    #[error("Schema registry answered with status {status}: {message}")]
+    SchemaRegistryStatus { status: u16, message: String },
+
+    #[error("Failed to parse the schema registry response")]
+    SchemaRegistryResponse(#[source] serde_json::Error),
+
+    #[error("Schema registry response has no valid field {0}")]
+    SchemaRegistryMissingField(String),
+
+    #[error("Schema registry response field {0} is out of range: {1}")]
+    SchemaRegistryFieldOutOfRange(String, u64),
+
     #[error("Failed to get JSON from avro.schema key in map")]
     GetAvroSchemaFromMap,
 
@@ -397,6 +502,30 @@ pub enum Error {
     #[error("Failed to write marker")]
     WriteMarker(#[source] std::io::Error),
 
+    #[error("A block compression worker terminated unexpectedly")]
+    CompressionWorkerTerminated,
+
+    #[error("Failed to create file {0:?}")]
+    CreateFile(std::path::PathBuf, #[source] std::io::Error),
+
+    #[error("Failed to read file {0:?}")]
+    ReadFile(std::path::PathBuf, #[source] std::io::Error),
+
+    #[error("Cannot generate Rust code: {0}")]
+    GenerateCode(String),
+
+    #[error("Failed to sync file to disk")]
+    SyncFile(#[source] std::io::Error),
+
+    #[error("Failed to seek")]
+    Seek(#[source] std::io::Error),
+
+    #[error("Cannot append values of schema {schema} to a file written with schema {file_schema}")]
+    AppendSchemaMismatch { file_schema: String, schema: String },
+
+    #[error("The position of the Writer in the file is unknown, it must be given with at_position when appending")]
+    UnknownWriterPosition,
+
     #[error("Failed to convert JSON to string")]
     ConvertJsonToString(#[source] serde_json::Error),
 
@@ -437,6 +566,16 @@ pub enum Error {
     #[error("Codec '{0}' is not supported/enabled")]
     CodecNotSupported(String),
 
+    #[error("Cannot register a custom codec named '{0}', like a built-in codec")]
+    CustomCodecName(String),
+
+    #[error("Custom codec '{codec}' failed")]
+    CustomCodec {
+        codec: &'static str,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
     #[error("Invalid Avro data! Cannot read codec type from value that is not Value::Bytes.")]
     BadCodecMetadata,
 }
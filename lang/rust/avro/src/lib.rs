@@ -720,33 +720,58 @@
 //! assert_eq!(false, SchemaCompatibility::can_read(&writers_schema, &readers_schema));
 //! ```
 
+mod checksum;
 mod codec;
+mod confluent;
+mod datum_stream;
 mod de;
 mod decimal;
 mod decode;
 mod duration;
 mod encode;
+#[cfg(feature = "encryption")]
+mod encryption;
 mod error;
 mod reader;
+mod rolling_writer;
+mod schema_registry;
+mod schema_store;
 mod ser;
 mod util;
 mod writer;
 
+pub mod codegen;
 pub mod rabin;
 pub mod schema;
 pub mod schema_compatibility;
+pub mod serde_timestamp_micros;
 pub mod types;
 
-pub use codec::Codec;
+pub use checksum::{BlockChecksum, BlockChecksums, BlockFailure, VerificationReport};
+pub use codec::{register_custom_codec, Codec, CodecOptions, CustomCodec};
+#[cfg(feature = "zstandard")]
+pub use codec::{ZstdDictionary, ZstdDictionaryProvider};
+pub use confluent::{
+    DirectorySchemaIdStore, GenericConfluentReader, GenericConfluentWriter, InMemorySchemaIdStore,
+    SchemaIdStore, SpecificConfluentReader, SpecificConfluentWriter, CONFLUENT_MAGIC_BYTE,
+};
+pub use datum_stream::{DatumStreamReader, DatumStreamWriter};
 pub use de::from_value;
 pub use decimal::Decimal;
 pub use duration::{Days, Duration, Millis, Months};
+#[cfg(feature = "encryption")]
+pub use encryption::{EncryptionKey, KeyProvider};
 pub use error::Error;
 pub use reader::{
     from_avro_datum, from_avro_datum_schemata, read_marker, GenericSingleObjectReader, Reader,
-    SpecificSingleObjectReader,
+    RecoveryStats, ResolvingSingleObjectReader, SkippedBlock, SpecificSingleObjectReader,
 };
+pub use rolling_writer::{CompletedFile, FileNamingStrategy, RollingWriter, SequentialFileNaming};
 pub use schema::{AvroSchema, Schema};
+#[cfg(feature = "registry")]
+pub use schema_registry::RestSchemaRegistry;
+pub use schema_registry::{MockSchemaRegistry, RegisteredSchema, SchemaRegistry};
+pub use schema_store::{InMemorySchemaStore, SchemaStore};
 pub use ser::to_value;
 pub use util::max_allocation_bytes;
 pub use writer::{
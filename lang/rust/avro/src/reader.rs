@@ -16,31 +16,123 @@
 // under the License.
 
 //! Logic handling reading from Avro format at user level.
+#[cfg(feature = "zstandard")]
+use crate::codec::{
+    ZstdDictionary, ZstdDictionaryProvider, ZSTD_DICTIONARY_ID_KEY, ZSTD_DICTIONARY_KEY,
+};
+#[cfg(feature = "encryption")]
+use crate::encryption::{
+    EncryptionKey, KeyProvider, AES_256_GCM, ENCRYPTION_KEY, ENCRYPTION_KEY_ID_KEY,
+};
 use crate::{
+    checksum::{block_crc32, BlockChecksums, BlockFailure, VerificationReport},
+    codec::{CodecOptions, Decompressor},
     decode::{decode, decode_internal},
     from_value,
     rabin::Rabin,
-    schema::{AvroSchema, Names, ResolvedOwnedSchema, ResolvedSchema, Schema},
+    schema::{AvroSchema, Names, ResolvedOwnedSchema, ResolvedSchema, Schema, SchemaFingerprint},
+    schema_store::SchemaStore,
     types::Value,
-    util, AvroResult, Codec, Error,
+    util,
+    writer::single_object_header,
+    AvroResult, Codec, Error,
 };
 use serde::de::DeserializeOwned;
 use serde_json::from_slice;
+#[cfg(any(feature = "encryption", feature = "zstandard"))]
+use std::sync::Arc;
 use std::{
-    collections::HashMap,
+    collections::{hash_map::Entry, HashMap},
     convert::TryFrom,
     io::{ErrorKind, Read},
     marker::PhantomData,
-    str::FromStr,
 };
 
-/// Internal Block reader.
+/// `Read` adapter keeping track of the position within the underlying reader and allowing
+/// already read bytes to be pushed back, so that they are read again.
 #[derive(Debug, Clone)]
+struct PositionedReader<R> {
+    inner: R,
+    position: u64,
+    pushed_back: Vec<u8>,
+}
+
+impl<R: Read> PositionedReader<R> {
+    fn new(inner: R) -> Self {
+        PositionedReader {
+            inner,
+            position: 0,
+            pushed_back: Vec::new(),
+        }
+    }
+
+    fn unread(&mut self, bytes: &[u8]) {
+        self.position -= bytes.len() as u64;
+        self.pushed_back.splice(0..0, bytes.iter().copied());
+    }
+}
+
+impl<R: Read> Read for PositionedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = if self.pushed_back.is_empty() {
+            self.inner.read(buf)?
+        } else {
+            let n = buf.len().min(self.pushed_back.len());
+            buf[..n].copy_from_slice(&self.pushed_back[..n]);
+            self.pushed_back.drain(..n);
+            n
+        };
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+/// A damaged region of a container file, skipped while reading in recovery mode.
+#[derive(Debug)]
+pub struct SkippedBlock {
+    /// Offset of the first skipped byte, i.e. the beginning of the damaged block.
+    pub start: u64,
+    /// Offset where reading resumed, right after the next sync marker (or the end of the input).
+    pub end: u64,
+    /// Number of records of the damaged block that could not be read, if known. It is not when
+    /// the block could not be delimited, or when its data ended before its count of records.
+    pub lost_records: Option<usize>,
+    /// The error which caused the block to be skipped.
+    pub error: Error,
+}
+
+/// Summary of the damage encountered by a [`Reader`](struct.Reader.html) in recovery mode.
+#[derive(Debug, Default)]
+pub struct RecoveryStats {
+    /// The skipped regions, in file order.
+    pub skipped_blocks: Vec<SkippedBlock>,
+}
+
+impl RecoveryStats {
+    /// Total number of bytes skipped.
+    pub fn skipped_bytes(&self) -> u64 {
+        self.skipped_blocks.iter().map(|b| b.end - b.start).sum()
+    }
+
+    /// Total number of records which could not be read, leaving out the blocks whose number of
+    /// lost records is unknown.
+    pub fn lost_records(&self) -> usize {
+        self.skipped_blocks
+            .iter()
+            .filter_map(|b| b.lost_records)
+            .sum()
+    }
+}
+
+/// Internal Block reader.
+#[derive(Debug)]
 struct Block<'r, R> {
-    reader: R,
-    /// Internal buffering to reduce allocation.
+    reader: PositionedReader<R>,
+    /// Internal buffering to reduce allocation, holding the decompressed block.
     buf: Vec<u8>,
     buf_idx: usize,
+    /// The block as read, before decompression. Like `buf`, it is reused from block to block.
+    compressed: Vec<u8>,
     /// Number of elements expected to exist within this block.
     message_count: usize,
     marker: [u8; 16],
@@ -48,20 +140,49 @@ struct Block<'r, R> {
     writer_schema: Schema,
     schemata: Vec<&'r Schema>,
     user_metadata: HashMap<String, Vec<u8>>,
+    /// Offset of the block currently being read.
+    block_start: u64,
+    /// Whether the sync marker of the block currently being read has been checked.
+    in_sync: bool,
+    /// Damage skipped so far, when reading in recovery mode.
+    recovery: Option<RecoveryStats>,
+    /// Options needed to decompress blocks, such as the `Zstandard` dictionary.
+    codec_options: CodecOptions,
+    /// Decompression state reused from block to block.
+    decompressor: Decompressor,
+    #[cfg(feature = "zstandard")]
+    zstd_dictionary_id: Option<u32>,
+    /// Id of the key the blocks are encrypted with, if they are.
+    #[cfg(feature = "encryption")]
+    encryption_key_id: Option<String>,
+    #[cfg(feature = "encryption")]
+    encryption_key: Option<Arc<EncryptionKey>>,
 }
 
 impl<'r, R: Read> Block<'r, R> {
     fn new(reader: R, schemata: Vec<&'r Schema>) -> AvroResult<Block<R>> {
         let mut block = Block {
-            reader,
+            reader: PositionedReader::new(reader),
             codec: Codec::Null,
             writer_schema: Schema::Null,
             schemata,
             buf: vec![],
             buf_idx: 0,
+            compressed: vec![],
             message_count: 0,
             marker: [0; 16],
             user_metadata: Default::default(),
+            block_start: 0,
+            in_sync: true,
+            recovery: None,
+            codec_options: CodecOptions::default(),
+            decompressor: Decompressor::default(),
+            #[cfg(feature = "zstandard")]
+            zstd_dictionary_id: None,
+            #[cfg(feature = "encryption")]
+            encryption_key_id: None,
+            #[cfg(feature = "encryption")]
+            encryption_key: None,
         };
 
         block.read_header()?;
@@ -89,6 +210,14 @@ impl<'r, R: Read> Block<'r, R> {
             for (key, value) in metadata {
                 if key == "avro.schema" || key == "avro.codec" {
                     // already processed
+                } else if cfg!(feature = "zstandard") && key.starts_with("avro.zstd.") {
+                    #[cfg(feature = "zstandard")]
+                    self.read_zstd_dictionary(&key, value)?;
+                } else if key.starts_with("avro.encryption") {
+                    #[cfg(feature = "encryption")]
+                    self.read_encryption(&key, value)?;
+                    #[cfg(not(feature = "encryption"))]
+                    return Err(Error::EncryptionNotEnabled);
                 } else if key.starts_with("avro.") {
                     warn!("Ignoring unknown metadata key: {}", key);
                 } else {
@@ -104,23 +233,76 @@ impl<'r, R: Read> Block<'r, R> {
             .map_err(Error::ReadMarker)
     }
 
+    #[cfg(feature = "zstandard")]
+    fn read_zstd_dictionary(&mut self, key: &str, value: Value) -> AvroResult<()> {
+        match (key, value) {
+            (ZSTD_DICTIONARY_ID_KEY, Value::Bytes(id)) => {
+                let id = std::str::from_utf8(&id)
+                    .ok()
+                    .and_then(|id| id.parse().ok())
+                    .ok_or(Error::BadZstdDictionaryId)?;
+                self.zstd_dictionary_id = Some(id);
+            }
+            (ZSTD_DICTIONARY_KEY, Value::Bytes(dictionary)) => {
+                self.codec_options.zstd_dictionary =
+                    Some(Arc::new(ZstdDictionary::new(dictionary)));
+            }
+            (key, _) => warn!("Ignoring unknown metadata key: {}", key),
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "encryption")]
+    fn read_encryption(&mut self, key: &str, value: Value) -> AvroResult<()> {
+        let value = match value {
+            Value::Bytes(bytes) => {
+                String::from_utf8(bytes).map_err(|_| Error::BadEncryptionMetadata)?
+            }
+            _ => return Err(Error::BadEncryptionMetadata),
+        };
+        match key {
+            ENCRYPTION_KEY if value != AES_256_GCM => {
+                return Err(Error::UnsupportedEncryption(value))
+            }
+            ENCRYPTION_KEY => {}
+            ENCRYPTION_KEY_ID_KEY => self.encryption_key_id = Some(value),
+            key => warn!("Ignoring unknown metadata key: {}", key),
+        }
+        Ok(())
+    }
+
+    /// Decrypt in-place the block just read, if the file is encrypted.
+    #[cfg(feature = "encryption")]
+    fn decrypt_block(&mut self) -> AvroResult<()> {
+        match (&self.encryption_key, &self.encryption_key_id) {
+            (Some(key), _) => key.decrypt_block(
+                &self.marker,
+                self.block_start,
+                self.message_count,
+                &mut self.compressed,
+            ),
+            (None, Some(key_id)) => Err(Error::MissingEncryptionKey(key_id.clone())),
+            (None, None) => Ok(()),
+        }
+    }
+
     fn fill_buf(&mut self, n: usize) -> AvroResult<()> {
         // The buffer needs to contain exactly `n` elements, otherwise codecs will potentially read
         // invalid bytes.
         //
-        // The are two cases to handle here:
-        //
-        // 1. `n > self.buf.len()`:
-        //    In this case we call `Vec::resize`, which guarantees that `self.buf.len() == n`.
-        // 2. `n < self.buf.len()`:
-        //    We need to resize to ensure that the buffer len is safe to read `n` elements.
-        //
-        // TODO: Figure out a way to avoid having to truncate for the second case.
-        self.buf.resize(util::safe_len(n)?, 0);
+        // The buffer keeps its allocation, so reading blocks of similar sizes does not allocate.
+        // It also keeps the bytes read when the input ends first, e.g. when the size of the
+        // block is damaged, for `resync` to look for the sync marker in them.
+        let n = util::safe_len(n)?;
+        self.compressed.clear();
         self.reader
-            .read_exact(&mut self.buf)
+            .by_ref()
+            .take(n as u64)
+            .read_to_end(&mut self.compressed)
             .map_err(Error::ReadIntoBuf)?;
-        self.buf_idx = 0;
+        if self.compressed.len() < n {
+            return Err(Error::ReadIntoBuf(ErrorKind::UnexpectedEof.into()));
+        }
         Ok(())
     }
 
@@ -128,6 +310,35 @@ impl<'r, R: Read> Block<'r, R> {
     /// the block. The objects are stored in an internal buffer to the `Reader`.
     fn read_block_next(&mut self) -> AvroResult<()> {
         assert!(self.is_empty(), "Expected self to be empty!");
+        if !self.read_block_bytes()? {
+            return Ok(());
+        }
+        #[cfg(feature = "encryption")]
+        self.decrypt_block()?;
+
+        // Both buffers are kept from block to block: uncompressed blocks are swapped
+        // into `buf`, the others are decompressed into it.
+        self.buf_idx = 0;
+        if self.codec == Codec::Null {
+            std::mem::swap(&mut self.buf, &mut self.compressed);
+            Ok(())
+        } else {
+            self.decompressor.decompress_into(
+                self.codec,
+                &self.compressed,
+                &mut self.buf,
+                &self.codec_options,
+            )
+        }
+    }
+
+    /// Read the next block as written, i.e. without decrypting or decompressing it, into
+    /// `compressed`, checking the sync marker which follows it.
+    ///
+    /// Return `false` if the input cleanly ended instead.
+    fn read_block_bytes(&mut self) -> AvroResult<bool> {
+        self.block_start = self.reader.position;
+        self.in_sync = false;
         match util::read_long(&mut self.reader) {
             Ok(block_len) => {
                 self.message_count = block_len as usize;
@@ -139,21 +350,18 @@ impl<'r, R: Read> Block<'r, R> {
                     .map_err(Error::ReadBlockMarker)?;
 
                 if marker != self.marker {
+                    // the marker might have been read as part of the block if its size is damaged
+                    self.compressed.extend_from_slice(&marker);
                     return Err(Error::GetBlockMarker);
                 }
-
-                // NOTE (JAB): This doesn't fit this Reader pattern very well.
-                // `self.buf` is a growable buffer that is reused as the reader is iterated.
-                // For non `Codec::Null` variants, `decompress` will allocate a new `Vec`
-                // and replace `buf` with the new one, instead of reusing the same buffer.
-                // We can address this by using some "limited read" type to decode directly
-                // into the buffer. But this is fine, for now.
-                self.codec.decompress(&mut self.buf)
+                self.in_sync = true;
+                Ok(true)
             }
             Err(Error::ReadVariableIntegerBytes(io_err)) => {
                 if let ErrorKind::UnexpectedEof = io_err.kind() {
                     // to not return any error in case we only finished to read cleanly from the stream
-                    Ok(())
+                    self.in_sync = true;
+                    Ok(false)
                 } else {
                     Err(Error::ReadVariableIntegerBytes(io_err))
                 }
@@ -162,6 +370,64 @@ impl<'r, R: Read> Block<'r, R> {
         }
     }
 
+    /// Check the remaining blocks against their recorded checksums, without decoding them. A
+    /// damaged block is reported, then skipped like in recovery mode.
+    fn verify(&mut self, checksums: &BlockChecksums) -> AvroResult<VerificationReport> {
+        if checksums.marker != self.marker {
+            return Err(Error::BlockChecksumsMarkerMismatch(checksums.marker));
+        }
+        // the blocks already read have been checked by decoding them
+        let start = self.reader.position;
+        let mut expected = checksums
+            .blocks
+            .iter()
+            .filter(|block| block.offset >= start)
+            .peekable();
+        let mut report = VerificationReport::default();
+        loop {
+            let result = self.read_block_bytes();
+            let offset = self.block_start;
+            // blocks expected before this one are missing, e.g. due to a damaged block size
+            while let Some(block) = expected.next_if(|block| block.offset < offset) {
+                report.failures.push(BlockFailure {
+                    offset: block.offset,
+                    error: Error::MissingBlock,
+                });
+            }
+            let error = match result {
+                Ok(false) => break,
+                Ok(true) => {
+                    let actual = block_crc32(self.message_count, &self.compressed);
+                    match expected.next_if(|block| block.offset == offset) {
+                        Some(block) if block.crc32 == actual => None,
+                        Some(block) => Some(Error::BlockChecksumMismatch {
+                            expected: block.crc32,
+                            actual,
+                        }),
+                        None => Some(Error::MissingBlockChecksum),
+                    }
+                }
+                Err(error) => {
+                    expected.next_if(|block| block.offset == offset);
+                    let window = self.damaged_block_bytes(&error);
+                    self.resync(window)?;
+                    self.in_sync = true;
+                    Some(error)
+                }
+            };
+            report.blocks += 1;
+            if let Some(error) = error {
+                report.failures.push(BlockFailure { offset, error });
+            }
+        }
+        self.message_count = 0;
+        report.failures.extend(expected.map(|block| BlockFailure {
+            offset: block.offset,
+            error: Error::MissingBlock,
+        }));
+        Ok(report)
+    }
+
     fn len(&self) -> usize {
         self.message_count
     }
@@ -171,6 +437,20 @@ impl<'r, R: Read> Block<'r, R> {
     }
 
     fn read_next(&mut self, read_schema: Option<&Schema>) -> AvroResult<Option<Value>> {
+        let item = loop {
+            match self.read_next_datum() {
+                Ok(Some(item)) => break item,
+                Ok(None) => return Ok(None),
+                Err(e) => self.skip_damaged_block(e)?,
+            }
+        };
+        match read_schema {
+            Some(schema) => item.resolve(schema).map(Some),
+            None => Ok(Some(item)),
+        }
+    }
+
+    fn read_next_datum(&mut self) -> AvroResult<Option<Value>> {
         if self.is_empty() {
             self.read_block_next()?;
             if self.is_empty() {
@@ -185,8 +465,7 @@ impl<'r, R: Read> Block<'r, R> {
         } else {
             self.schemata.clone()
         };
-        let item =
-            from_avro_datum_schemata(&self.writer_schema, schemata, &mut block_bytes, read_schema)?;
+        let item = from_avro_datum_schemata(&self.writer_schema, schemata, &mut block_bytes, None)?;
         if b_original == block_bytes.len() {
             // from_avro_datum did not consume any bytes, so return an error to avoid an infinite loop
             return Err(Error::ReadBlock);
@@ -196,6 +475,76 @@ impl<'r, R: Read> Block<'r, R> {
         Ok(Some(item))
     }
 
+    /// Skip the rest of the block in which `error` happened, when in recovery mode. If the block
+    /// could not be delimited, the input is scanned for the next sync marker.
+    ///
+    /// Return `error` itself when not in recovery mode.
+    fn skip_damaged_block(&mut self, error: Error) -> AvroResult<()> {
+        // a missing key is not damage, skipping would lose every block
+        if self.recovery.is_none() || matches!(error, Error::MissingEncryptionKey(_)) {
+            return Err(error);
+        }
+        // the count of records of a block is not trusted if its data ran out first
+        let data_ran_out = self.buf_idx > 0 && self.buf_idx >= self.buf.len();
+        let lost_records = if self.in_sync && !data_ran_out {
+            Some(self.message_count)
+        } else {
+            None
+        };
+        if !self.in_sync {
+            let window = self.damaged_block_bytes(&error);
+            self.resync(window)?;
+        }
+
+        let skipped = SkippedBlock {
+            start: self.block_start,
+            end: self.reader.position,
+            lost_records,
+            error,
+        };
+        warn!("Skipping damaged block: {:?}", skipped);
+        if let Some(recovery) = self.recovery.as_mut() {
+            recovery.skipped_blocks.push(skipped);
+        }
+        self.message_count = 0;
+        self.buf.clear();
+        self.buf_idx = 0;
+        self.in_sync = true;
+        Ok(())
+    }
+
+    /// The bytes already read of the block which could not be delimited because of `error`, in
+    /// which its sync marker might be, e.g. when its size is damaged.
+    fn damaged_block_bytes(&mut self, error: &Error) -> Vec<u8> {
+        match error {
+            Error::GetBlockMarker | Error::ReadIntoBuf(_) | Error::ReadBlockMarker(_) => {
+                std::mem::take(&mut self.compressed)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Look for the sync marker, first in `window` (bytes already read), then in the rest of the
+    /// input. The reader is left right after the marker, or at the end of the input if there is
+    /// none.
+    fn resync(&mut self, mut window: Vec<u8>) -> AvroResult<()> {
+        let mut chunk = [0u8; 4096];
+        loop {
+            if let Some(idx) = window.windows(16).position(|w| w == self.marker) {
+                self.reader.unread(&window[idx + 16..]);
+                return Ok(());
+            }
+            // keep what could be the beginning of a marker split across two chunks
+            window.drain(..window.len().saturating_sub(15));
+            match self.reader.read(&mut chunk) {
+                Ok(0) => return Ok(()),
+                Ok(n) => window.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(Error::ReadIntoBuf(e)),
+            }
+        }
+    }
+
     fn read_writer_schema(&mut self, metadata: &HashMap<String, Value>) -> AvroResult<()> {
         let json: serde_json::Value = metadata
             .get("avro.schema")
@@ -236,6 +585,38 @@ impl<'r, R: Read> Block<'r, R> {
     }
 }
 
+/// The content of a container file header.
+pub(crate) struct Header {
+    pub(crate) writer_schema: Schema,
+    pub(crate) codec: Codec,
+    pub(crate) marker: [u8; 16],
+    pub(crate) user_metadata: HashMap<String, Vec<u8>>,
+    #[cfg(feature = "zstandard")]
+    pub(crate) zstd_dictionary_id: Option<u32>,
+    /// The `Zstandard` dictionary embedded in the header, if any.
+    #[cfg(feature = "zstandard")]
+    pub(crate) zstd_dictionary: Option<Arc<ZstdDictionary>>,
+    #[cfg(feature = "encryption")]
+    pub(crate) encryption_key_id: Option<String>,
+}
+
+/// Read the header of a container file, leaving `reader` right after it.
+pub(crate) fn read_header<R: Read>(reader: R) -> AvroResult<Header> {
+    let block = Block::new(reader, vec![])?;
+    Ok(Header {
+        writer_schema: block.writer_schema,
+        codec: block.codec,
+        marker: block.marker,
+        user_metadata: block.user_metadata,
+        #[cfg(feature = "zstandard")]
+        zstd_dictionary_id: block.zstd_dictionary_id,
+        #[cfg(feature = "zstandard")]
+        zstd_dictionary: block.codec_options.zstd_dictionary,
+        #[cfg(feature = "encryption")]
+        encryption_key_id: block.encryption_key_id,
+    })
+}
+
 fn read_codec(metadata: &HashMap<String, Value>) -> AvroResult<Codec> {
     let result = metadata
         .get("avro.codec")
@@ -250,10 +631,7 @@ fn read_codec(metadata: &HashMap<String, Value>) -> AvroResult<Codec> {
             }
         })
         .map(|codec_res| match codec_res {
-            Ok(codec) => match Codec::from_str(codec) {
-                Ok(codec) => Ok(codec),
-                Err(_) => Err(Error::CodecNotSupported(codec.to_owned())),
-            },
+            Ok(codec) => Codec::from_name(codec),
             Err(err) => Err(err),
         });
 
@@ -357,6 +735,73 @@ impl<'a, R: Read> Reader<'a, R> {
         &self.block.user_metadata
     }
 
+    /// Turn on the recovery mode: instead of stopping at the first damaged block, the `Reader`
+    /// skips it (scanning forward to the next sync marker if needed) and carries on with the
+    /// following ones. What has been skipped is available through
+    /// [`recovery_stats`](struct.Reader.html#method.recovery_stats).
+    ///
+    /// Errors while resolving values against the reader `Schema` are still returned as usual.
+    pub fn with_recovery(mut self) -> Self {
+        self.block
+            .recovery
+            .get_or_insert_with(RecoveryStats::default);
+        self
+    }
+
+    /// Get the id of the `Zstandard` dictionary the blocks have been compressed with, if any.
+    #[cfg(feature = "zstandard")]
+    #[inline]
+    pub fn zstd_dictionary_id(&self) -> Option<u32> {
+        self.block.zstd_dictionary_id
+    }
+
+    /// Supply the `Zstandard` dictionary the blocks have been compressed with, looking it up by
+    /// the id written in the header. A dictionary embedded in the header takes precedence.
+    #[cfg(feature = "zstandard")]
+    pub fn with_zstd_dictionaries(mut self, provider: &impl ZstdDictionaryProvider) -> Self {
+        if self.block.codec_options.zstd_dictionary.is_none() {
+            self.block.codec_options.zstd_dictionary = self
+                .block
+                .zstd_dictionary_id
+                .and_then(|id| provider.zstd_dictionary(id));
+        }
+        self
+    }
+
+    /// Get the id of the key the blocks are encrypted with, if they are.
+    #[cfg(feature = "encryption")]
+    #[inline]
+    pub fn encryption_key_id(&self) -> Option<&str> {
+        self.block.encryption_key_id.as_deref()
+    }
+
+    /// Supply the key the blocks are encrypted with, looking it up by the id written in the
+    /// header. Reading blocks of an encrypted file fails until it is supplied.
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption_keys(mut self, provider: &impl KeyProvider) -> Self {
+        self.block.encryption_key = self
+            .block
+            .encryption_key_id
+            .as_deref()
+            .and_then(|key_id| provider.encryption_key(key_id));
+        self
+    }
+
+    /// Check every remaining block against the checksums recorded by the `Writer`, reporting the
+    /// offsets of the damaged or missing ones. Blocks are neither decrypted, decompressed nor
+    /// decoded, so this is much faster than reading the file and does not need its keys.
+    ///
+    /// It fails early only if the checksums belong to another file or the input cannot be read.
+    pub fn verify(mut self, checksums: &BlockChecksums) -> AvroResult<VerificationReport> {
+        self.block.verify(checksums)
+    }
+
+    /// Get a summary of the damaged blocks skipped so far, if the recovery mode is on.
+    #[inline]
+    pub fn recovery_stats(&self) -> Option<&RecoveryStats> {
+        self.block.recovery.as_ref()
+    }
+
     #[inline]
     fn read_next(&mut self) -> AvroResult<Option<Value>> {
         let read_schema = if self.should_resolve_schema {
@@ -435,21 +880,16 @@ pub struct GenericSingleObjectReader {
 impl GenericSingleObjectReader {
     pub fn new(schema: Schema) -> AvroResult<GenericSingleObjectReader> {
         let fingerprint = schema.fingerprint::<Rabin>();
-        let expected_header = [
-            0xC3,
-            0x01,
-            fingerprint.bytes[0],
-            fingerprint.bytes[1],
-            fingerprint.bytes[2],
-            fingerprint.bytes[3],
-            fingerprint.bytes[4],
-            fingerprint.bytes[5],
-            fingerprint.bytes[6],
-            fingerprint.bytes[7],
-        ];
+        Self::with_fingerprint(schema, &fingerprint)
+    }
+
+    fn with_fingerprint(
+        schema: Schema,
+        fingerprint: &SchemaFingerprint,
+    ) -> AvroResult<GenericSingleObjectReader> {
         Ok(GenericSingleObjectReader {
             write_schema: ResolvedOwnedSchema::try_from(schema)?,
-            expected_header,
+            expected_header: single_object_header(fingerprint),
         })
     }
 
@@ -488,9 +928,15 @@ impl<T> SpecificSingleObjectReader<T>
 where
     T: AvroSchema,
 {
+    /// The schema of `T` and its fingerprint come from
+    /// [`AvroSchema::get_schema_with_fingerprint`], which caches them for derived types.
     pub fn new() -> AvroResult<SpecificSingleObjectReader<T>> {
+        let schema = T::get_schema_with_fingerprint();
         Ok(SpecificSingleObjectReader {
-            inner: GenericSingleObjectReader::new(T::get_schema())?,
+            inner: GenericSingleObjectReader::with_fingerprint(
+                schema.schema().clone(),
+                schema.rabin_fingerprint(),
+            )?,
             _model: PhantomData,
         })
     }
@@ -514,6 +960,60 @@ where
     }
 }
 
+/// Reader of messages in the single object encoding, written with any of the schemas of a
+/// [`SchemaStore`](trait.SchemaStore.html).
+///
+/// The writer schema of each message is looked up by the fingerprint in its header and the
+/// decoded value is resolved to the reader schema. Writer schemas are cached once looked up.
+pub struct ResolvingSingleObjectReader<S: SchemaStore> {
+    store: S,
+    reader_schema: Schema,
+    writer_schemas: HashMap<[u8; 8], ResolvedOwnedSchema>,
+}
+
+impl<S: SchemaStore> ResolvingSingleObjectReader<S> {
+    pub fn new(store: S, reader_schema: Schema) -> ResolvingSingleObjectReader<S> {
+        ResolvingSingleObjectReader {
+            store,
+            reader_schema,
+            writer_schemas: HashMap::new(),
+        }
+    }
+
+    /// Get a reference to the reader `Schema`.
+    pub fn reader_schema(&self) -> &Schema {
+        &self.reader_schema
+    }
+
+    pub fn read_value<R: Read>(&mut self, reader: &mut R) -> AvroResult<Value> {
+        let mut header: [u8; 10] = [0; 10];
+        reader.read_exact(&mut header).map_err(Error::ReadHeader)?;
+        if header[..2] != [0xC3, 0x01] {
+            return Err(Error::HeaderMagic);
+        }
+        let mut fingerprint = [0; 8];
+        fingerprint.copy_from_slice(&header[2..]);
+
+        let writer_schema = match self.writer_schemas.entry(fingerprint) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let schema = self
+                    .store
+                    .lookup(&fingerprint)
+                    .ok_or(Error::UnknownSchemaFingerprint(fingerprint))?;
+                entry.insert(ResolvedOwnedSchema::try_from(schema)?)
+            }
+        };
+        decode_internal(
+            writer_schema.get_root_schema(),
+            writer_schema.get_names(),
+            &None,
+            reader,
+        )?
+        .resolve(&self.reader_schema)
+    }
+}
+
 /// Reads the marker bytes from Avro bytes generated earlier by a `Writer`
 pub fn read_marker(bytes: &[u8]) -> [u8; 16] {
     assert!(
@@ -528,7 +1028,10 @@ pub fn read_marker(bytes: &[u8]) -> [u8; 16] {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{encode::encode, from_value, types::Record, Reader};
+    use crate::{
+        encode::encode, from_value, types::Record, GenericSingleObjectWriter, InMemorySchemaStore,
+        Reader,
+    };
     use pretty_assertions::assert_eq;
     use serde::Deserialize;
     use std::io::Cursor;
@@ -720,6 +1223,422 @@ mod tests {
         }
     }
 
+    /// Write 4 blocks of 10 records each, returning the file and the offsets right after the
+    /// header and each of the blocks.
+    fn write_blocks(codec: Codec) -> (Vec<u8>, Vec<usize>) {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let mut writer = crate::Writer::with_codec(&schema, Vec::new(), codec);
+        let mut offsets = vec![];
+        let mut position = 0;
+        for block in 0..4 {
+            for i in 0..10 {
+                let mut record = Record::new(&schema).unwrap();
+                record.put("a", (block * 10 + i) as i64);
+                record.put("b", "foo");
+                position += writer.append(record).unwrap();
+                if offsets.is_empty() {
+                    offsets.push(position);
+                }
+            }
+            position += writer.flush().unwrap();
+            offsets.push(position);
+        }
+        (writer.into_inner().unwrap(), offsets)
+    }
+
+    #[test]
+    fn test_reader_reuses_block_buffers() {
+        for codec in [Codec::Null, Codec::Deflate] {
+            let (bytes, _) = write_blocks(codec);
+            let mut reader = Reader::new(&bytes[..]).unwrap();
+            let mut buffers = vec![];
+            while let Some(value) = reader.next() {
+                value.unwrap();
+                let block = &reader.block;
+                buffers.push((block.buf.as_ptr(), block.compressed.as_ptr()));
+            }
+            assert_eq!(buffers.len(), 40);
+            // once the first block has been read, the same two buffers are used over and over
+            let allocations: std::collections::HashSet<_> = buffers[10..]
+                .iter()
+                .flat_map(|(buf, compressed)| [*buf, *compressed])
+                .collect();
+            assert_eq!(allocations.len(), 2, "{codec:?}: {buffers:?}");
+        }
+    }
+
+    fn read_a_fields(reader: &mut Reader<&[u8]>) -> Vec<i64> {
+        reader
+            .map(|value| match value.unwrap() {
+                Value::Record(fields) => match fields[0].1 {
+                    Value::Long(a) => a,
+                    ref other => panic!("Unexpected value {other:?}"),
+                },
+                other => panic!("Unexpected value {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_reader_recovery_skips_undecompressable_block() {
+        let (mut bytes, offsets) = write_blocks(Codec::Deflate);
+        // block count and size both fit in a single byte here
+        bytes[offsets[1] + 2] = 0xFF;
+
+        let reader = Reader::new(&bytes[..]).unwrap();
+        assert!(reader.into_iter().any(|value| value.is_err()));
+
+        let mut reader = Reader::new(&bytes[..]).unwrap().with_recovery();
+        let values = read_a_fields(reader.by_ref());
+        assert_eq!(values, (0..10).chain(20..40).collect::<Vec<_>>());
+
+        let stats = reader.recovery_stats().unwrap();
+        assert_eq!(stats.skipped_blocks.len(), 1);
+        assert_eq!(stats.lost_records(), 10);
+        assert_eq!(stats.skipped_blocks[0].start, offsets[1] as u64);
+        assert_eq!(stats.skipped_blocks[0].end, offsets[2] as u64);
+        assert!(matches!(
+            stats.skipped_blocks[0].error,
+            Error::DeflateDecompress(_)
+        ));
+    }
+
+    #[test]
+    fn test_reader_recovery_resyncs_on_wrong_block_size() {
+        let (mut bytes, offsets) = write_blocks(Codec::Null);
+        // make the second block look bigger than it is, hiding its marker in its data
+        assert_eq!(bytes[offsets[1]], 20);
+        bytes[offsets[1] + 1] += 20;
+
+        let mut reader = Reader::new(&bytes[..]).unwrap().with_recovery();
+        let values = read_a_fields(reader.by_ref());
+        assert_eq!(values, (0..10).chain(20..40).collect::<Vec<_>>());
+
+        let stats = reader.recovery_stats().unwrap();
+        assert_eq!(stats.skipped_blocks.len(), 1);
+        assert_eq!(stats.skipped_blocks[0].start, offsets[1] as u64);
+        assert_eq!(stats.skipped_blocks[0].end, offsets[2] as u64);
+        assert!(matches!(
+            stats.skipped_blocks[0].error,
+            Error::GetBlockMarker
+        ));
+    }
+
+    #[test]
+    fn test_reader_recovery_resyncs_on_oversized_block_size() {
+        let (mut bytes, offsets) = write_blocks(Codec::Null);
+        // make the size of the second block go past the end of the input
+        bytes[offsets[1] + 1..offsets[1] + 4].copy_from_slice(&[0xFE, 0xFF, 0x0F]);
+
+        let mut reader = Reader::new(&bytes[..]).unwrap().with_recovery();
+        let values = read_a_fields(reader.by_ref());
+        assert_eq!(values, (0..10).chain(20..40).collect::<Vec<_>>());
+
+        let stats = reader.recovery_stats().unwrap();
+        assert_eq!(stats.skipped_blocks.len(), 1);
+        assert_eq!(stats.skipped_blocks[0].start, offsets[1] as u64);
+        assert_eq!(stats.skipped_blocks[0].end, offsets[2] as u64);
+        assert_eq!(stats.skipped_blocks[0].lost_records, None);
+        assert!(matches!(
+            stats.skipped_blocks[0].error,
+            Error::ReadIntoBuf(_)
+        ));
+    }
+
+    #[test]
+    fn test_reader_recovery_on_wrong_block_count() {
+        let (mut bytes, offsets) = write_blocks(Codec::Null);
+        // make the second block look like it has 20 records instead of 10
+        assert_eq!(bytes[offsets[1]], 20);
+        bytes[offsets[1]] = 40;
+
+        let mut reader = Reader::new(&bytes[..]).unwrap().with_recovery();
+        let values = read_a_fields(reader.by_ref());
+        assert_eq!(values, (0..40).collect::<Vec<_>>());
+
+        let stats = reader.recovery_stats().unwrap();
+        assert_eq!(stats.skipped_blocks.len(), 1);
+        assert_eq!(stats.skipped_blocks[0].start, offsets[1] as u64);
+        assert_eq!(stats.skipped_blocks[0].end, offsets[2] as u64);
+        // the count of the block cannot be trusted
+        assert_eq!(stats.skipped_blocks[0].lost_records, None);
+        assert_eq!(stats.lost_records(), 0);
+    }
+
+    #[test]
+    fn test_reader_recovery_on_truncated_input() {
+        let (bytes, offsets) = write_blocks(Codec::Null);
+        let truncated = &bytes[..offsets[3] + 10];
+
+        let mut reader = Reader::new(truncated).unwrap().with_recovery();
+        let values = read_a_fields(reader.by_ref());
+        assert_eq!(values, (0..30).collect::<Vec<_>>());
+
+        let stats = reader.recovery_stats().unwrap();
+        assert_eq!(stats.skipped_blocks.len(), 1);
+        assert_eq!(stats.skipped_bytes(), 10);
+        assert!(matches!(
+            stats.skipped_blocks[0].error,
+            Error::ReadIntoBuf(_)
+        ));
+    }
+
+    #[cfg(feature = "zstandard")]
+    #[test]
+    fn test_reader_with_zstd_dictionary() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let samples = (0..1000).map(|i| {
+            let mut record = Record::new(&schema).unwrap();
+            record.put("a", i as i64);
+            record.put("b", format!("foo-{}", i % 10));
+            record.into()
+        });
+        let dictionary =
+            Arc::new(ZstdDictionary::train_from_values(&schema, samples, 512).unwrap());
+
+        let write = |embed: bool| {
+            let options = CodecOptions::builder()
+                .zstd_dictionary(dictionary.clone())
+                .zstd_embed_dictionary(embed)
+                .build();
+            let mut writer = crate::Writer::builder()
+                .schema(&schema)
+                .writer(Vec::new())
+                .codec(Codec::Zstandard)
+                .codec_options(options)
+                .build();
+            for i in 0..10 {
+                let mut record = Record::new(&schema).unwrap();
+                record.put("a", i as i64);
+                record.put("b", "foo-3");
+                writer.append(record).unwrap();
+                writer.flush().unwrap();
+            }
+            writer.into_inner().unwrap()
+        };
+
+        let embedded = write(true);
+        let mut reader = Reader::new(&embedded[..]).unwrap();
+        assert_eq!(reader.zstd_dictionary_id(), Some(dictionary.id()));
+        assert_eq!(read_a_fields(&mut reader), (0..10).collect::<Vec<_>>());
+
+        let bytes = write(false);
+        assert!(bytes.len() < embedded.len());
+        let mut reader = Reader::new(&bytes[..]).unwrap();
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::MissingZstdDictionary(_)))
+        ));
+
+        let mut dictionaries = HashMap::new();
+        dictionaries.insert(dictionary.id(), dictionary.clone());
+        let mut reader = Reader::new(&bytes[..])
+            .unwrap()
+            .with_zstd_dictionaries(&dictionaries);
+        assert_eq!(read_a_fields(&mut reader), (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_reader_verify() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let mut writer = crate::Writer::builder()
+            .schema(&schema)
+            .writer(Vec::new())
+            .codec(Codec::Deflate)
+            .checksum_blocks(true)
+            .build();
+        for i in 0..40 {
+            let mut record = Record::new(&schema).unwrap();
+            record.put("a", i as i64);
+            record.put("b", "foo");
+            writer.append(record).unwrap();
+            if i % 10 == 9 {
+                writer.flush().unwrap();
+            }
+        }
+        let mut sidecar = Vec::new();
+        writer.block_checksums().write_to(&mut sidecar).unwrap();
+        let bytes = writer.into_inner().unwrap();
+        let checksums = BlockChecksums::read_from(&sidecar[..]).unwrap();
+        assert_eq!(checksums.blocks.len(), 4);
+
+        let report = Reader::new(&bytes[..]).unwrap().verify(&checksums).unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.blocks, 4);
+
+        // a flipped bit in the third block, which deflate would not notice
+        let mut damaged = bytes.clone();
+        let offset = checksums.blocks[2].offset;
+        damaged[offset as usize + 4] ^= 1;
+        let report = Reader::new(&damaged[..])
+            .unwrap()
+            .verify(&checksums)
+            .unwrap();
+        assert_eq!(report.blocks, 4);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].offset, offset);
+        assert!(matches!(
+            report.failures[0].error,
+            Error::BlockChecksumMismatch { .. }
+        ));
+
+        // the size of the second block going past the end of the input
+        let mut damaged = bytes.clone();
+        let offset = checksums.blocks[1].offset;
+        damaged[offset as usize + 1..offset as usize + 4].copy_from_slice(&[0xFE, 0xFF, 0x0F]);
+        let report = Reader::new(&damaged[..])
+            .unwrap()
+            .verify(&checksums)
+            .unwrap();
+        assert_eq!(report.blocks, 4);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].offset, offset);
+        assert!(matches!(report.failures[0].error, Error::ReadIntoBuf(_)));
+
+        // a truncated file
+        let offset = checksums.blocks[3].offset;
+        let report = Reader::new(&bytes[..bytes.len() - 20])
+            .unwrap()
+            .verify(&checksums)
+            .unwrap();
+        assert_eq!(report.blocks, 4);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].offset, offset);
+
+        // blocks after the ones recorded, and checksums of another file
+        let report = Reader::new(&bytes[..])
+            .unwrap()
+            .verify(&BlockChecksums {
+                marker: checksums.marker,
+                blocks: checksums.blocks[..3].to_vec(),
+            })
+            .unwrap();
+        assert_eq!(report.failures.len(), 1);
+        assert!(matches!(
+            report.failures[0].error,
+            Error::MissingBlockChecksum
+        ));
+        let other = BlockChecksums {
+            marker: [0; 16],
+            blocks: vec![],
+        };
+        assert!(matches!(
+            Reader::new(&bytes[..]).unwrap().verify(&other),
+            Err(Error::BlockChecksumsMarkerMismatch(_))
+        ));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_reader_with_encryption() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let key = Arc::new(EncryptionKey::new("key-1", [42; 32]));
+        let mut writer = crate::Writer::builder()
+            .schema(&schema)
+            .writer(Vec::new())
+            .encryption_key(key.clone())
+            .build();
+        for i in 0..10 {
+            let mut record = Record::new(&schema).unwrap();
+            record.put("a", i as i64);
+            record.put("b", "plaintext");
+            writer.append(record).unwrap();
+            writer.flush().unwrap();
+        }
+        let bytes = writer.into_inner().unwrap();
+        assert!(!bytes.windows(9).any(|w| w == b"plaintext"));
+
+        let mut reader = Reader::new(&bytes[..]).unwrap();
+        assert_eq!(reader.encryption_key_id(), Some("key-1"));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::MissingEncryptionKey(id))) if id == "key-1"
+        ));
+        // a missing key is not skipped as damage
+        let mut reader = Reader::new(&bytes[..]).unwrap().with_recovery();
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::MissingEncryptionKey(_)))
+        ));
+
+        let mut keys = HashMap::new();
+        keys.insert("key-1".to_string(), key);
+        let mut reader = Reader::new(&bytes[..]).unwrap().with_encryption_keys(&keys);
+        assert_eq!(read_a_fields(&mut reader), (0..10).collect::<Vec<_>>());
+
+        let wrong_key = |_: &str| Some(Arc::new(EncryptionKey::new("key-1", [0; 32])));
+        let mut reader = Reader::new(&bytes[..])
+            .unwrap()
+            .with_encryption_keys(&wrong_key);
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::BlockAuthentication))
+        ));
+
+        // tamper with the last byte of the first block, right before its sync marker
+        let mut tampered = bytes.clone();
+        let marker = read_marker(&bytes);
+        let mut markers = bytes.windows(16).enumerate().filter(|(_, w)| *w == marker);
+        // the first marker ends the header
+        let first_block_end = markers.nth(1).unwrap().0;
+        tampered[first_block_end - 1] ^= 1;
+        let mut reader = Reader::new(&tampered[..])
+            .unwrap()
+            .with_encryption_keys(&keys)
+            .with_recovery();
+        assert_eq!(read_a_fields(&mut reader), (1..10).collect::<Vec<_>>());
+        let skipped = &reader.recovery_stats().unwrap().skipped_blocks;
+        assert_eq!(skipped.len(), 1);
+        assert!(matches!(skipped[0].error, Error::BlockAuthentication));
+
+        // swap the second and third blocks, of the same size
+        let ends: Vec<_> = bytes
+            .windows(16)
+            .enumerate()
+            .filter(|(_, w)| *w == marker)
+            .map(|(i, _)| i + 16)
+            .collect();
+        let (second, third) = (ends[1]..ends[2], ends[2]..ends[3]);
+        assert_eq!(second.len(), third.len());
+        let mut swapped = bytes.clone();
+        swapped[second.clone()].copy_from_slice(&bytes[third.clone()]);
+        swapped[third].copy_from_slice(&bytes[second]);
+        let mut reader = Reader::new(&swapped[..])
+            .unwrap()
+            .with_encryption_keys(&keys);
+        assert!(matches!(reader.next(), Some(Ok(_))));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(Error::BlockAuthentication))
+        ));
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    #[test]
+    fn test_reader_encrypted_file_without_encryption_feature() {
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "avro.schema".to_string(),
+            Value::Bytes(br#""long""#.to_vec()),
+        );
+        metadata.insert(
+            "avro.encryption".to_string(),
+            Value::Bytes(b"aes-256-gcm".to_vec()),
+        );
+        let mut bytes = b"Obj\x01".to_vec();
+        encode(
+            &Value::Map(metadata),
+            &Schema::Map(Box::new(Schema::Bytes)),
+            &mut bytes,
+        )
+        .unwrap();
+        bytes.extend_from_slice(&[0; 16]);
+        assert!(matches!(
+            Reader::new(&bytes[..]),
+            Err(Error::EncryptionNotEnabled)
+        ));
+    }
+
     #[test]
     fn test_avro_3405_read_user_metadata_success() {
         use crate::writer::Writer;
@@ -938,6 +1857,68 @@ mod tests {
         assert_eq!(val, expected_value)
     }
 
+    #[test]
+    fn test_resolving_single_object_reader() {
+        let v1 = Schema::parse_str(
+            r#"{"type": "record", "name": "Event", "fields": [{"name": "a", "type": "int"}]}"#,
+        )
+        .unwrap();
+        let v2 = Schema::parse_str(
+            r#"{"type": "record", "name": "Event", "fields": [
+                {"name": "a", "type": "long"},
+                {"name": "b", "type": "string", "default": "none"}
+            ]}"#,
+        )
+        .unwrap();
+        let unknown = Schema::parse_str(r#""string""#).unwrap();
+
+        let mut store = InMemorySchemaStore::new();
+        store.add(v1.clone());
+        store.add(v2.clone());
+
+        let mut bytes = Vec::new();
+        let mut record = Record::new(&v1).unwrap();
+        record.put("a", 1);
+        GenericSingleObjectWriter::new_with_capacity(&v1, 32)
+            .unwrap()
+            .write_value(record.into(), &mut bytes)
+            .unwrap();
+        let mut record = Record::new(&v2).unwrap();
+        record.put("a", 2_i64);
+        record.put("b", "two");
+        GenericSingleObjectWriter::new_with_capacity(&v2, 32)
+            .unwrap()
+            .write_value(record.into(), &mut bytes)
+            .unwrap();
+        GenericSingleObjectWriter::new_with_capacity(&unknown, 32)
+            .unwrap()
+            .write_value(Value::String("?".to_string()), &mut bytes)
+            .unwrap();
+
+        let mut reader = ResolvingSingleObjectReader::new(&store, v2);
+        let mut input = &bytes[..];
+        assert_eq!(
+            reader.read_value(&mut input).unwrap(),
+            Value::Record(vec![
+                ("a".to_string(), Value::Long(1)),
+                ("b".to_string(), Value::String("none".to_string())),
+            ])
+        );
+        assert_eq!(
+            reader.read_value(&mut input).unwrap(),
+            Value::Record(vec![
+                ("a".to_string(), Value::Long(2)),
+                ("b".to_string(), Value::String("two".to_string())),
+            ])
+        );
+        match reader.read_value(&mut input) {
+            Err(Error::UnknownSchemaFingerprint(fingerprint)) => {
+                assert_eq!(fingerprint.to_vec(), unknown.fingerprint::<Rabin>().bytes)
+            }
+            other => panic!("Expected an unknown fingerprint error, got {other:?}"),
+        }
+    }
+
     #[cfg(not(feature = "snappy"))]
     #[test]
     fn test_avro_3549_read_not_enabled_codec() {
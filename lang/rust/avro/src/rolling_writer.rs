@@ -0,0 +1,495 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Logic handling writing a stream of values to a sequence of Avro container files.
+use crate::{
+    schema::Schema, types::Value, writer::DEFAULT_BLOCK_SIZE, AvroResult, Codec, CodecOptions,
+    Error, Writer,
+};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufWriter, ErrorKind, Write},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+/// Decides where the successive files of a [`RollingWriter`](struct.RollingWriter.html) are
+/// created.
+///
+/// It is implemented for any `FnMut(u64) -> PathBuf` closure.
+pub trait FileNamingStrategy {
+    /// Return the path of the file with the given `index`, starting from 0.
+    fn file_path(&mut self, index: u64) -> PathBuf;
+}
+
+impl<F> FileNamingStrategy for F
+where
+    F: FnMut(u64) -> PathBuf,
+{
+    fn file_path(&mut self, index: u64) -> PathBuf {
+        self(index)
+    }
+}
+
+/// Names files `<prefix>-<index>.avro` within a directory, the index being zero-padded so that
+/// the files sort in the order they have been written.
+#[derive(Debug, Clone)]
+pub struct SequentialFileNaming {
+    directory: PathBuf,
+    prefix: String,
+}
+
+impl SequentialFileNaming {
+    pub fn new<D: Into<PathBuf>, P: Into<String>>(directory: D, prefix: P) -> Self {
+        SequentialFileNaming {
+            directory: directory.into(),
+            prefix: prefix.into(),
+        }
+    }
+}
+
+impl FileNamingStrategy for SequentialFileNaming {
+    fn file_path(&mut self, index: u64) -> PathBuf {
+        self.directory
+            .join(format!("{}-{:010}.avro", self.prefix, index))
+    }
+}
+
+/// Description of a file a [`RollingWriter`](struct.RollingWriter.html) is done with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletedFile {
+    pub path: PathBuf,
+    pub index: u64,
+    pub num_values: u64,
+    pub num_bytes: u64,
+}
+
+type FileCompletedCallback<'a> = Box<dyn FnMut(&CompletedFile) + 'a>;
+
+struct OpenFile<'a> {
+    writer: Writer<'a, BufWriter<File>>,
+    path: PathBuf,
+    index: u64,
+    num_values: u64,
+    num_bytes: u64,
+    opened_at: Instant,
+}
+
+/// Writes values to a sequence of Avro container files, each one with its own header, moving on
+/// to a new file once the current one is big enough, holds enough values or is old enough.
+///
+/// Files are created lazily, when the first value going into them is appended, so no empty file
+/// is ever created. The size limit is checked against the bytes actually written, so files can
+/// exceed it by up to one block.
+///
+/// Existing files are never overwritten: the indices whose file already exists, e.g. written
+/// before a restart, are skipped. Creating a file fails if the naming strategy gives the path of
+/// an existing file again for the next index.
+///
+/// **NOTE** Call [`close`](struct.RollingWriter.html#method.close) once done, otherwise the
+/// values still buffered for the last file are lost.
+#[derive(typed_builder::TypedBuilder)]
+pub struct RollingWriter<'a> {
+    schema: &'a Schema,
+    #[builder(setter(transform = |naming: impl FileNamingStrategy + 'a| Box::new(naming) as Box<dyn FileNamingStrategy + 'a>))]
+    file_naming: Box<dyn FileNamingStrategy + 'a>,
+    #[builder(default = Codec::Null)]
+    codec: Codec,
+    #[builder(default)]
+    codec_options: CodecOptions,
+    #[builder(default = DEFAULT_BLOCK_SIZE)]
+    block_size: usize,
+    #[builder(default)]
+    user_metadata: HashMap<String, Value>,
+    /// Number of bytes after which a file is completed.
+    #[builder(default, setter(strip_option))]
+    max_file_size: Option<u64>,
+    /// Number of values after which a file is completed.
+    #[builder(default, setter(strip_option))]
+    max_file_values: Option<u64>,
+    /// Amount of time, since its creation, after which a file is completed. It is checked
+    /// whenever a value is appended, see also
+    /// [`roll_if_expired`](struct.RollingWriter.html#method.roll_if_expired).
+    #[builder(default, setter(strip_option))]
+    max_file_age: Option<Duration>,
+    /// Called every time a file is completed.
+    #[builder(default, setter(transform = |callback: impl FnMut(&CompletedFile) + 'a| Some(Box::new(callback) as FileCompletedCallback<'a>)))]
+    on_file_completed: Option<FileCompletedCallback<'a>>,
+    #[builder(default, setter(skip))]
+    current: Option<OpenFile<'a>>,
+    #[builder(default = 0, setter(skip))]
+    next_index: u64,
+}
+
+impl<'a> RollingWriter<'a> {
+    /// Get a reference to the `Schema` of the written files.
+    pub fn schema(&self) -> &'a Schema {
+        self.schema
+    }
+
+    /// Append a compatible value to the current file, also performing schema validation.
+    ///
+    /// Return the number of bytes written to the current file (it might be 0, since the values
+    /// are buffered as with [`Writer`](struct.Writer.html)).
+    pub fn append<T: Into<Value>>(&mut self, value: T) -> AvroResult<usize> {
+        let value = value.into();
+        self.append_value_ref(&value)
+    }
+
+    /// Append a compatible value to the current file, also performing schema validation.
+    ///
+    /// If the file is then completed but this fails, the value is appended all the same and the
+    /// file stays open, to be completed by the next call to `roll`.
+    pub fn append_value_ref(&mut self, value: &Value) -> AvroResult<usize> {
+        let file = match self.current.take() {
+            Some(file) => file,
+            None => self.open_file()?,
+        };
+        let file = self.current.insert(file);
+        let n = file.writer.append_value_ref(value)?;
+        file.num_values += 1;
+        file.num_bytes += n as u64;
+
+        if self.is_file_full() || self.is_file_expired() {
+            self.roll()?;
+        }
+        Ok(n)
+    }
+
+    /// Append anything implementing the `Serialize` trait to the current file, also performing
+    /// schema validation.
+    pub fn append_ser<S: Serialize>(&mut self, value: S) -> AvroResult<usize> {
+        let value = crate::to_value(value)?;
+        self.append_value_ref(&value)
+    }
+
+    /// Complete the current file if it is older than the configured `max_file_age`. Writers
+    /// receiving values sporadically can call this periodically to bound the lifetime of files.
+    pub fn roll_if_expired(&mut self) -> AvroResult<Option<CompletedFile>> {
+        if self.is_file_expired() {
+            self.roll()
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Complete the current file, if any: the next value appended goes to a new file. If this
+    /// fails, the file stays open with the values buffered for it, so that it can be retried.
+    pub fn roll(&mut self) -> AvroResult<Option<CompletedFile>> {
+        let file = match self.current.as_mut() {
+            Some(file) => file,
+            None => return Ok(None),
+        };
+        let completed = finish_file(file)?;
+        self.current = None;
+        if let Some(callback) = self.on_file_completed.as_mut() {
+            callback(&completed);
+        }
+        Ok(Some(completed))
+    }
+
+    /// Complete the current file, consuming the `RollingWriter`.
+    pub fn close(mut self) -> AvroResult<Option<CompletedFile>> {
+        self.roll()
+    }
+
+    fn open_file(&mut self) -> AvroResult<OpenFile<'a>> {
+        let mut existing = None;
+        let (file, path, index) = loop {
+            let index = self.next_index;
+            let path = self.file_naming.file_path(index);
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => {
+                    self.next_index += 1;
+                    break (file, path, index);
+                }
+                // the naming strategy does not depend on the index
+                Err(e) if existing.as_ref() == Some(&path) => {
+                    return Err(Error::CreateFile(path, e))
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    self.next_index += 1;
+                    existing = Some(path);
+                }
+                Err(e) => return Err(Error::CreateFile(path, e)),
+            }
+        };
+
+        let writer = Writer::builder()
+            .schema(self.schema)
+            .writer(BufWriter::new(file))
+            .codec(self.codec)
+            .codec_options(self.codec_options.clone())
+            .block_size(self.block_size)
+            .user_metadata(self.user_metadata.clone())
+            .build();
+        Ok(OpenFile {
+            writer,
+            path,
+            index,
+            num_values: 0,
+            num_bytes: 0,
+            opened_at: Instant::now(),
+        })
+    }
+
+    fn is_file_full(&self) -> bool {
+        match self.current {
+            Some(ref file) => {
+                self.max_file_size
+                    .map_or(false, |max_size| file.num_bytes >= max_size)
+                    || self
+                        .max_file_values
+                        .map_or(false, |max_values| file.num_values >= max_values)
+            }
+            None => false,
+        }
+    }
+
+    fn is_file_expired(&self) -> bool {
+        match (self.current.as_ref(), self.max_file_age) {
+            (Some(file), Some(max_age)) => file.opened_at.elapsed() >= max_age,
+            _ => false,
+        }
+    }
+}
+
+fn finish_file(file: &mut OpenFile) -> AvroResult<CompletedFile> {
+    file.num_bytes += file.writer.flush()? as u64;
+    let file_writer = file.writer.inner_mut();
+    file_writer.flush().map_err(Error::WriteBytes)?;
+    file_writer.get_ref().sync_all().map_err(Error::SyncFile)?;
+
+    Ok(CompletedFile {
+        path: file.path.clone(),
+        index: file.index,
+        num_values: file.num_values,
+        num_bytes: file.num_bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{types::Record, Reader};
+    use pretty_assertions::assert_eq;
+    use std::{cell::RefCell, fs, path::Path};
+
+    const SCHEMA: &str = r#"
+    {
+      "type": "record",
+      "name": "test",
+      "fields": [
+        {"name": "a", "type": "long"},
+        {"name": "b", "type": "string"}
+      ]
+    }
+    "#;
+
+    fn temp_directory() -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("avro-rolling-{:016x}", rand::random::<u64>()));
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn read_a_fields(path: &Path) -> Vec<i64> {
+        let file = File::open(path).unwrap();
+        Reader::new(file)
+            .unwrap()
+            .map(|value| match value.unwrap() {
+                Value::Record(fields) => match fields[0].1 {
+                    Value::Long(a) => a,
+                    ref other => panic!("Unexpected value {other:?}"),
+                },
+                other => panic!("Unexpected value {other:?}"),
+            })
+            .collect()
+    }
+
+    fn make_record(schema: &Schema, a: i64) -> Record<'_> {
+        let mut record = Record::new(schema).unwrap();
+        record.put("a", a);
+        record.put("b", "foo");
+        record
+    }
+
+    #[test]
+    fn test_rolling_writer_by_values() -> AvroResult<()> {
+        let schema = Schema::parse_str(SCHEMA)?;
+        let directory = temp_directory();
+        let completed = RefCell::new(vec![]);
+
+        let mut writer = RollingWriter::builder()
+            .schema(&schema)
+            .file_naming(SequentialFileNaming::new(&directory, "events"))
+            .max_file_values(10)
+            .on_file_completed(|file: &CompletedFile| completed.borrow_mut().push(file.clone()))
+            .build();
+        for i in 0..25 {
+            writer.append(make_record(&schema, i))?;
+        }
+        let last = writer.close()?.unwrap();
+
+        let completed = completed.into_inner();
+        assert_eq!(completed.len(), 3);
+        assert_eq!(completed[2], last);
+        for (index, file) in completed.iter().enumerate() {
+            assert_eq!(file.index, index as u64);
+            assert_eq!(
+                file.path,
+                directory.join(format!("events-{index:010}.avro"))
+            );
+            assert_eq!(file.num_bytes, fs::metadata(&file.path).unwrap().len());
+            let start = index as i64 * 10;
+            let end = (start + 10).min(25);
+            assert_eq!(read_a_fields(&file.path), (start..end).collect::<Vec<_>>());
+        }
+
+        fs::remove_dir_all(directory).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn test_rolling_writer_by_size() -> AvroResult<()> {
+        let schema = Schema::parse_str(SCHEMA)?;
+        let directory = temp_directory();
+        let paths = RefCell::new(vec![]);
+
+        let mut writer = RollingWriter::builder()
+            .schema(&schema)
+            .file_naming(|index| directory.join(format!("{index}.avro")))
+            .block_size(50)
+            .max_file_size(300)
+            .on_file_completed(|file: &CompletedFile| paths.borrow_mut().push(file.path.clone()))
+            .build();
+        for i in 0..100 {
+            writer.append(make_record(&schema, i))?;
+        }
+        writer.close()?;
+
+        let paths = paths.into_inner();
+        assert!(paths.len() > 1);
+        let values: Vec<i64> = paths.iter().flat_map(|path| read_a_fields(path)).collect();
+        assert_eq!(values, (0..100).collect::<Vec<_>>());
+
+        fs::remove_dir_all(directory).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn test_rolling_writer_by_age() -> AvroResult<()> {
+        let schema = Schema::parse_str(SCHEMA)?;
+        let directory = temp_directory();
+
+        let mut writer = RollingWriter::builder()
+            .schema(&schema)
+            .file_naming(SequentialFileNaming::new(&directory, "events"))
+            .max_file_age(Duration::from_millis(20))
+            .build();
+        assert_eq!(writer.roll_if_expired()?, None);
+        writer.append(make_record(&schema, 1))?;
+        assert_eq!(writer.roll_if_expired()?, None);
+        std::thread::sleep(Duration::from_millis(30));
+        let completed = writer.roll_if_expired()?.unwrap();
+        assert_eq!(completed.num_values, 1);
+        assert_eq!(writer.close()?, None);
+
+        assert_eq!(read_a_fields(&completed.path), vec![1]);
+
+        fs::remove_dir_all(directory).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn test_rolling_writer_skips_existing_files() -> AvroResult<()> {
+        let schema = Schema::parse_str(SCHEMA)?;
+        let directory = temp_directory();
+        let naming = SequentialFileNaming::new(&directory, "events");
+        let existing = naming.clone().file_path(0);
+        fs::write(&existing, b"written before a restart").unwrap();
+
+        let mut writer = RollingWriter::builder()
+            .schema(&schema)
+            .file_naming(naming)
+            .build();
+        writer.append(make_record(&schema, 1))?;
+        let completed = writer.close()?.unwrap();
+        assert_eq!(completed.index, 1);
+        assert_eq!(read_a_fields(&completed.path), vec![1]);
+        assert_eq!(fs::read(&existing).unwrap(), b"written before a restart");
+
+        // a naming strategy ignoring the index cannot go past an existing file
+        let mut writer = RollingWriter::builder()
+            .schema(&schema)
+            .file_naming(|_| existing.clone())
+            .build();
+        assert!(matches!(
+            writer.append(make_record(&schema, 1)),
+            Err(Error::CreateFile(path, _)) if path == existing
+        ));
+        assert_eq!(fs::read(&existing).unwrap(), b"written before a restart");
+
+        fs::remove_dir_all(directory).unwrap();
+        Ok(())
+    }
+
+    /// Fails to compress the first block.
+    struct FailingOnceCodec(std::sync::atomic::AtomicBool);
+
+    impl crate::CustomCodec for FailingOnceCodec {
+        fn name(&self) -> &'static str {
+            "failing-once-rolling"
+        }
+
+        fn compress(&self, _stream: &mut Vec<u8>) -> AvroResult<()> {
+            if self.0.swap(false, std::sync::atomic::Ordering::SeqCst) {
+                return Err(Error::BadCodecMetadata);
+            }
+            Ok(())
+        }
+
+        fn decompress(&self, _stream: &mut Vec<u8>) -> AvroResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_rolling_writer_keeps_the_file_when_rolling_fails() -> AvroResult<()> {
+        let codec = crate::register_custom_codec(FailingOnceCodec(true.into()))?;
+        let schema = Schema::parse_str(SCHEMA)?;
+        let directory = temp_directory();
+
+        let mut writer = RollingWriter::builder()
+            .schema(&schema)
+            .file_naming(SequentialFileNaming::new(&directory, "events"))
+            .codec(codec)
+            .max_file_values(2)
+            .build();
+        writer.append(make_record(&schema, 1))?;
+        assert!(writer.append(make_record(&schema, 2)).is_err());
+        let completed = writer.roll()?.unwrap();
+        assert_eq!(completed.index, 0);
+        assert_eq!(completed.num_values, 2);
+        assert_eq!(read_a_fields(&completed.path), vec![1, 2]);
+
+        fs::remove_dir_all(directory).unwrap();
+        Ok(())
+    }
+}
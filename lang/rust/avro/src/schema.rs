@@ -16,7 +16,7 @@
 // under the License.
 
 //! Logic for parsing and interacting with schemas in Avro format.
-use crate::{error::Error, types, util::MapHelper, AvroResult};
+use crate::{error::Error, rabin::Rabin, types, util::MapHelper, AvroResult};
 use digest::Digest;
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -26,12 +26,14 @@ use serde::{
 };
 use serde_json::{Map, Value};
 use std::{
+    any::TypeId,
     borrow::Cow,
     collections::{BTreeMap, HashMap, HashSet},
     convert::{TryFrom, TryInto},
     fmt,
     hash::Hash,
     str::FromStr,
+    sync::Mutex,
 };
 use strum_macros::{EnumDiscriminants, EnumString};
 
@@ -41,11 +43,15 @@ lazy_static! {
     // An optional namespace (with optional dots) followed by a name without any dots in it.
     static ref SCHEMA_NAME_R: Regex =
         Regex::new(r"^((?P<namespace>[A-Za-z_][A-Za-z0-9_\.]*)*\.)?(?P<name>[A-Za-z_][A-Za-z0-9_]*)$").unwrap();
+
+    // The schemas cached by `AvroSchema::get_cached_schema`, by type.
+    static ref CACHED_SCHEMAS: Mutex<HashMap<TypeId, &'static CachedSchema>> = Mutex::default();
 }
 
 /// Represents an Avro schema fingerprint
 /// More information about Avro schema fingerprints can be found in the
 /// [Avro Schema Fingerprint documentation](https://avro.apache.org/docs/current/spec.html#schema_fingerprints)
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SchemaFingerprint {
     pub bytes: Vec<u8>,
 }
@@ -723,6 +729,45 @@ impl UnionSchema {
             })
         }
     }
+
+    /// Like `find_schema`, but looking up the named types the variants refer to in the given
+    /// schemata.
+    pub(crate) fn find_schema_with_known_schemata<S: std::borrow::Borrow<Schema>>(
+        &self,
+        value: &types::Value,
+        known_schemata: &HashMap<Name, S>,
+        enclosing_namespace: &Namespace,
+    ) -> Option<(usize, &Schema)> {
+        let schema_kind = SchemaKind::from(value);
+        if let Some(&i) = self.variant_index.get(&schema_kind) {
+            // fast path
+            Some((i, &self.schemas[i]))
+        } else {
+            // slow path (required for matching logical or named types)
+            self.schemas.iter().enumerate().find(|(_, schema)| {
+                value
+                    .validate_internal(schema, known_schemata, enclosing_namespace)
+                    .is_none()
+            })
+        }
+    }
+
+    /// Whether the variant at `index` is an empty record, which is how the unit variants of a
+    /// Rust enum with data are represented.
+    pub(crate) fn is_unit_variant<S: std::borrow::Borrow<Schema>>(
+        &self,
+        index: u32,
+        known_schemata: &HashMap<Name, S>,
+        enclosing_namespace: &Namespace,
+    ) -> bool {
+        let mut schema = self.schemas.get(index as usize);
+        if let Some(Schema::Ref { name }) = schema {
+            schema = known_schemata
+                .get(&name.fully_qualified_name(enclosing_namespace))
+                .map(|schema| schema.borrow());
+        }
+        matches!(schema, Some(Schema::Record { fields, .. }) if fields.is_empty())
+    }
 }
 
 // No need to compare variant_index, it is derivative of schemas.
@@ -785,11 +830,7 @@ impl Schema {
     /// [fingerprint]:
     /// https://avro.apache.org/docs/current/spec.html#schema_fingerprints
     pub fn fingerprint<D: Digest>(&self) -> SchemaFingerprint {
-        let mut d = D::new();
-        d.update(self.canonical_form());
-        SchemaFingerprint {
-            bytes: d.finalize().to_vec(),
-        }
+        fingerprint_of::<D>(&self.canonical_form())
     }
 
     /// Create a `Schema` from a string representing a JSON Avro schema.
@@ -1833,17 +1874,118 @@ fn field_ordering_position(field: &str) -> Option<usize> {
         .map(|pos| pos + 1)
 }
 
+fn fingerprint_of<D: Digest>(canonical_form: &str) -> SchemaFingerprint {
+    let mut d = D::new();
+    d.update(canonical_form);
+    SchemaFingerprint {
+        bytes: d.finalize().to_vec(),
+    }
+}
+
+/// A schema along with its [Parsing Canonical Form] and its Rabin fingerprint, which are computed
+/// once, as returned by [`AvroSchema::get_cached_schema`].
+///
+/// [Parsing Canonical Form]:
+/// https://avro.apache.org/docs/1.8.2/spec.html#Parsing+Canonical+Form+for+Schemas
+#[derive(Clone, Debug)]
+pub struct CachedSchema {
+    schema: Schema,
+    canonical_form: String,
+    rabin_fingerprint: SchemaFingerprint,
+}
+
+impl CachedSchema {
+    pub fn new(schema: Schema) -> Self {
+        let canonical_form = schema.canonical_form();
+        let rabin_fingerprint = fingerprint_of::<Rabin>(&canonical_form);
+        CachedSchema {
+            schema,
+            canonical_form,
+            rabin_fingerprint,
+        }
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// The Parsing Canonical Form of the schema.
+    pub fn canonical_form(&self) -> &str {
+        &self.canonical_form
+    }
+
+    /// The Rabin fingerprint of the schema, as used by the single object encoding.
+    pub fn rabin_fingerprint(&self) -> &SchemaFingerprint {
+        &self.rabin_fingerprint
+    }
+
+    /// Generate the fingerprint of the schema from its cached Parsing Canonical Form.
+    pub fn fingerprint<D: Digest>(&self) -> SchemaFingerprint {
+        fingerprint_of::<D>(&self.canonical_form)
+    }
+}
+
 /// Trait for types that serve as an Avro data model. Derive implementation available
 /// through `derive` feature. Do not implement directly!
 /// Implement `apache_avro::schema::derive::AvroSchemaComponent` to get this trait
 /// through a blanket implementation.
 pub trait AvroSchema {
     fn get_schema() -> Schema;
+
+    /// The schema of the type along with its Parsing Canonical Form and Rabin fingerprint, which
+    /// are only built on the first call.
+    fn get_cached_schema() -> &'static CachedSchema
+    where
+        Self: 'static,
+    {
+        cached_schema::<Self>(Self::get_schema)
+    }
+
+    /// The Parsing Canonical Form of the schema of the type, see `get_cached_schema`.
+    fn get_canonical_form() -> &'static str
+    where
+        Self: 'static,
+    {
+        Self::get_cached_schema().canonical_form()
+    }
+
+    /// The Rabin fingerprint of the schema of the type, see `get_cached_schema`.
+    fn get_rabin_fingerprint() -> &'static SchemaFingerprint
+    where
+        Self: 'static,
+    {
+        Self::get_cached_schema().rabin_fingerprint()
+    }
+
+    /// The schema of the type along with its Rabin fingerprint, e.g. for the single object
+    /// encoding. They are computed on each call, as the type may not be `'static`, unless the
+    /// implementation returns those of `get_cached_schema`, as the derived ones of types without
+    /// generics do.
+    fn get_schema_with_fingerprint() -> Cow<'static, CachedSchema> {
+        Cow::Owned(CachedSchema::new(Self::get_schema()))
+    }
+}
+
+/// Returns the schema of `T` from the cache shared by all the types, building it if needed.
+fn cached_schema<T: ?Sized + 'static>(get_schema: fn() -> Schema) -> &'static CachedSchema {
+    let type_id = TypeId::of::<T>();
+    if let Some(cached) = CACHED_SCHEMAS.lock().unwrap().get(&type_id) {
+        return cached;
+    }
+    // built without holding the lock, as building it may need the cached schemas of other types;
+    // should another thread cache it meanwhile, this one is leaked once
+    let cached = Box::leak(Box::new(CachedSchema::new(get_schema())));
+    CACHED_SCHEMAS
+        .lock()
+        .unwrap()
+        .entry(type_id)
+        .or_insert(cached)
 }
 
 #[cfg(feature = "derive")]
 pub mod derive {
     use super::*;
+    use crate::schema_compatibility::SchemaCompatibility;
 
     /// Trait for types that serve as fully defined components inside an Avro data model. Derive
     /// implementation available through `derive` feature. This is what is implemented by
@@ -1896,6 +2038,24 @@ pub mod derive {
     pub trait AvroSchemaComponent {
         fn get_schema_in_ctxt(named_schemas: &mut Names, enclosing_namespace: &Namespace)
             -> Schema;
+
+        /// Backs `AvroSchema::get_cached_schema`. The derived implementations of non generic
+        /// types keep the schema in a static of their own, the others in a cache shared by all
+        /// the types.
+        fn get_static_schema() -> &'static CachedSchema
+        where
+            Self: 'static,
+        {
+            cached_schema::<Self>(|| Self::get_schema_in_ctxt(&mut HashMap::default(), &None))
+        }
+
+        /// Backs `AvroSchema::get_schema_with_fingerprint`.
+        fn get_fingerprinted_schema() -> Cow<'static, CachedSchema> {
+            Cow::Owned(CachedSchema::new(Self::get_schema_in_ctxt(
+                &mut HashMap::default(),
+                &None,
+            )))
+        }
     }
 
     impl<T> AvroSchema for T
@@ -1905,10 +2065,217 @@ pub mod derive {
         fn get_schema() -> Schema {
             T::get_schema_in_ctxt(&mut HashMap::default(), &None)
         }
+
+        fn get_cached_schema() -> &'static CachedSchema
+        where
+            Self: 'static,
+        {
+            T::get_static_schema()
+        }
+
+        fn get_schema_with_fingerprint() -> Cow<'static, CachedSchema> {
+            T::get_fingerprinted_schema()
+        }
+    }
+
+    /// For the statics of derived implementations.
+    #[doc(hidden)]
+    pub use lazy_static::lazy_static;
+
+    /// Returns the fields of the record of `T`, for the `#[serde(flatten)]` fields of derived
+    /// records: their fields are part of the enclosing record, but the record of `T` is not.
+    ///
+    /// # Panics
+    ///
+    /// If the schema of `T` is not a record.
+    pub fn flattened_fields<T>(
+        named_schemas: &mut Names,
+        enclosing_namespace: &Namespace,
+    ) -> Vec<RecordField>
+    where
+        T: AvroSchemaComponent,
+    {
+        // the record has to be defined, even if it already is somewhere else
+        let mut names = named_schemas.clone();
+        let mut schema = T::get_schema_in_ctxt(&mut names, enclosing_namespace);
+        if let Schema::Ref { name } = &schema {
+            names.remove(name);
+            schema = T::get_schema_in_ctxt(&mut names, enclosing_namespace);
+        }
+        match schema {
+            Schema::Record { name, fields, .. } => {
+                let name = name.fully_qualified_name(enclosing_namespace);
+                if !named_schemas.contains_key(&name) {
+                    names.remove(&name);
+                }
+                // the named types defined by the fields now are by the enclosing record
+                named_schemas.extend(names);
+                fields
+            }
+            schema => panic!("Only records can be flattened, got {schema:?}"),
+        }
+    }
+
+    /// Checks that the default values of the fields of a derived record match their schemas, the
+    /// way schema resolution uses them: a default has to resolve to the schema of its field, or to
+    /// the first branch of it if it is a union.
+    ///
+    /// The named types still being defined are skipped, as their schemas are not known yet.
+    ///
+    /// # Panics
+    ///
+    /// If a default value does not match the schema of its field.
+    pub fn validate_defaults(
+        fields: &[RecordField],
+        named_schemas: &Names,
+        enclosing_namespace: &Namespace,
+    ) {
+        let mut names: Option<NamesRef> = None;
+        for field in fields {
+            let default = match field.default {
+                Some(ref default) => default,
+                None => continue,
+            };
+            let names = names.get_or_insert_with(|| {
+                named_schemas
+                    .iter()
+                    .filter(|(name, schema)| !is_placeholder(name, schema))
+                    .map(|(name, schema)| (name.clone(), schema))
+                    .collect()
+            });
+            let schema = match field.schema {
+                Schema::Union(ref union_schema) => &union_schema.variants()[0],
+                ref schema => schema,
+            };
+            match types::Value::from(default.clone()).resolve_internal(
+                schema,
+                names,
+                enclosing_namespace,
+            ) {
+                Ok(_) => {}
+                Err(Error::SchemaResolutionError(ref name))
+                    if named_schemas
+                        .get(name)
+                        .map_or(false, |schema| is_placeholder(name, schema)) => {}
+                Err(e) => panic!(
+                    "Invalid default value {default} for field {:?} of schema {schema:?}: {e}",
+                    field.name
+                ),
+            }
+        }
+    }
+
+    /// Checks that the record of a variant of a derived enum with data has the same fields as the
+    /// schema already registered under its name, i.e. that it is the record of the same variant.
+    ///
+    /// # Panics
+    ///
+    /// If the registered schema is not a record with fields of the same names, e.g. if two enums
+    /// rename variants alike.
+    pub fn check_variant_record(name: &Name, registered: &Schema, fields: &[RecordField]) {
+        let same_fields = match registered {
+            Schema::Record {
+                fields: registered_fields,
+                ..
+            } => registered_fields
+                .iter()
+                .map(|field| &field.name)
+                .eq(fields.iter().map(|field| &field.name)),
+            _ => false,
+        };
+        if !same_fields {
+            panic!(
+                "Unable to name the record of an enum variant {}, which is already the name of another schema: {}",
+                name.fullname(None),
+                registered.canonical_form()
+            );
+        }
+    }
+
+    /// Returns the name of the schema of `T` as it appears in the names of derived generic
+    /// records, e.g. `int`, `User`, `array_User` or `union_null_User`.
+    pub fn schema_type_name<T>(named_schemas: &Names, enclosing_namespace: &Namespace) -> String
+    where
+        T: AvroSchemaComponent,
+    {
+        // the named types of `T` are defined where it is used, not in the name
+        let mut names = named_schemas.clone();
+        type_name(&T::get_schema_in_ctxt(&mut names, enclosing_namespace))
+    }
+
+    fn type_name(schema: &Schema) -> String {
+        match schema {
+            Schema::Record { name, .. }
+            | Schema::Enum { name, .. }
+            | Schema::Fixed { name, .. }
+            | Schema::Ref { name } => name.name.clone(),
+            Schema::Array(items) => format!("array_{}", type_name(items)),
+            Schema::Map(values) => format!("map_{}", type_name(values)),
+            Schema::Union(union_schema) => std::iter::once("union".to_owned())
+                .chain(union_schema.variants().iter().map(type_name))
+                .collect::<Vec<_>>()
+                .join("_"),
+            Schema::Decimal {
+                precision, scale, ..
+            } => format!("decimal_{precision}_{scale}"),
+            Schema::Null => "null".to_owned(),
+            Schema::Boolean => "boolean".to_owned(),
+            Schema::Int => "int".to_owned(),
+            Schema::Long => "long".to_owned(),
+            Schema::Float => "float".to_owned(),
+            Schema::Double => "double".to_owned(),
+            Schema::Bytes => "bytes".to_owned(),
+            Schema::String => "string".to_owned(),
+            Schema::Uuid => "uuid".to_owned(),
+            Schema::Date => "date".to_owned(),
+            Schema::TimeMillis => "time_millis".to_owned(),
+            Schema::TimeMicros => "time_micros".to_owned(),
+            Schema::TimestampMillis => "timestamp_millis".to_owned(),
+            Schema::TimestampMicros => "timestamp_micros".to_owned(),
+            Schema::Duration => "duration".to_owned(),
+        }
+    }
+
+    /// Checks the schema of `T` against the one of an `.avsc` file, for the tests generated by
+    /// `#[avro(schema_file = "...")]`: they must have the same Parsing Canonical Form if
+    /// `identical`, and be mutually compatible otherwise.
+    ///
+    /// # Panics
+    ///
+    /// If the file does not hold a valid schema, or if the schemas do not match.
+    pub fn verify_schema_file<T>(path: &str, contents: &str, identical: bool)
+    where
+        T: AvroSchema,
+    {
+        let file_schema =
+            Schema::parse_str(contents).unwrap_or_else(|e| panic!("Invalid schema in {path}: {e}"));
+        let schema = T::get_schema();
+        if identical {
+            let (canonical_form, file_canonical_form) =
+                (schema.canonical_form(), file_schema.canonical_form());
+            assert!(
+                canonical_form == file_canonical_form,
+                "The schema is not the one of {path}:\n  derived: {canonical_form}\n  file:    {file_canonical_form}"
+            );
+        } else {
+            assert!(
+                SchemaCompatibility::mutual_read(&schema, &file_schema),
+                "The schema is not compatible with the one of {path}:\n  derived: {}\n  file:    {}",
+                schema.canonical_form(),
+                file_schema.canonical_form()
+            );
+        }
+    }
+
+    /// Whether the schema registered under a name is the `Ref` to itself standing for a named
+    /// type while it is being defined
+    fn is_placeholder(name: &Name, schema: &Schema) -> bool {
+        matches!(schema, Schema::Ref { name: ref_name } if ref_name == name)
     }
 
     macro_rules! impl_schema(
-        ($type:ty, $variant_constructor:expr) => (
+        ($(#[$attr:meta])* $type:ty, $variant_constructor:expr) => (
+            $(#[$attr])*
             impl AvroSchemaComponent for $type {
                 fn get_schema_in_ctxt(_: &mut Names, _: &Namespace) -> Schema {
                     $variant_constructor
@@ -1930,6 +2297,57 @@ pub mod derive {
     impl_schema!(String, Schema::String);
     impl_schema!(uuid::Uuid, Schema::Uuid);
     impl_schema!(core::time::Duration, Schema::Duration);
+    /// `SystemTime`s are serialized by serde as records of their seconds and nanoseconds since the
+    /// UNIX epoch. They can be written as `timestamp-micros` instead with
+    /// [`serde_timestamp_micros`](crate::serde_timestamp_micros).
+    impl AvroSchemaComponent for std::time::SystemTime {
+        fn get_schema_in_ctxt(
+            named_schemas: &mut Names,
+            enclosing_namespace: &Namespace,
+        ) -> Schema {
+            let name = Name::new("SystemTime")
+                .expect("Unable to parse schema name")
+                .fully_qualified_name(enclosing_namespace);
+            if named_schemas.contains_key(&name) {
+                return Schema::Ref { name };
+            }
+            let fields: Vec<RecordField> = ["secs_since_epoch", "nanos_since_epoch"]
+                .iter()
+                .enumerate()
+                .map(|(position, field)| RecordField {
+                    name: field.to_string(),
+                    doc: None,
+                    aliases: None,
+                    default: None,
+                    schema: Schema::Long,
+                    order: RecordFieldOrder::Ascending,
+                    position,
+                    custom_attributes: Default::default(),
+                })
+                .collect();
+            let lookup = fields
+                .iter()
+                .map(|field| (field.name.clone(), field.position))
+                .collect();
+            let schema = Schema::Record {
+                name: name.clone(),
+                aliases: None,
+                doc: None,
+                fields,
+                lookup,
+                attributes: Default::default(),
+            };
+            named_schemas.insert(name, schema.clone());
+            schema
+        }
+    }
+    impl_schema!(std::net::IpAddr, Schema::String);
+    impl_schema!(std::net::Ipv4Addr, Schema::String);
+    impl_schema!(std::net::Ipv6Addr, Schema::String);
+    #[cfg(feature = "serde_bytes")]
+    impl_schema!(serde_bytes::ByteBuf, Schema::Bytes);
+    #[cfg(feature = "url")]
+    impl_schema!(url::Url, Schema::String);
 
     impl<T> AvroSchemaComponent for Vec<T>
     where
@@ -1946,6 +2364,81 @@ pub mod derive {
         }
     }
 
+    impl<T> AvroSchemaComponent for std::collections::VecDeque<T>
+    where
+        T: AvroSchemaComponent,
+    {
+        fn get_schema_in_ctxt(
+            named_schemas: &mut Names,
+            enclosing_namespace: &Namespace,
+        ) -> Schema {
+            Schema::Array(Box::new(T::get_schema_in_ctxt(
+                named_schemas,
+                enclosing_namespace,
+            )))
+        }
+    }
+
+    impl<T> AvroSchemaComponent for HashSet<T>
+    where
+        T: AvroSchemaComponent,
+    {
+        fn get_schema_in_ctxt(
+            named_schemas: &mut Names,
+            enclosing_namespace: &Namespace,
+        ) -> Schema {
+            Schema::Array(Box::new(T::get_schema_in_ctxt(
+                named_schemas,
+                enclosing_namespace,
+            )))
+        }
+    }
+
+    impl<T> AvroSchemaComponent for std::collections::BTreeSet<T>
+    where
+        T: AvroSchemaComponent,
+    {
+        fn get_schema_in_ctxt(
+            named_schemas: &mut Names,
+            enclosing_namespace: &Namespace,
+        ) -> Schema {
+            Schema::Array(Box::new(T::get_schema_in_ctxt(
+                named_schemas,
+                enclosing_namespace,
+            )))
+        }
+    }
+
+    /// Byte arrays are `fixed`, named after their size (e.g. `fixed_16`) in the enclosing namespace.
+    impl<const N: usize> AvroSchemaComponent for [u8; N] {
+        fn get_schema_in_ctxt(
+            named_schemas: &mut Names,
+            enclosing_namespace: &Namespace,
+        ) -> Schema {
+            let name = Name::new(&format!("fixed_{N}")).expect("Unable to parse fixed name");
+            let fully_qualified_name = name.fully_qualified_name(enclosing_namespace);
+            if named_schemas.contains_key(&fully_qualified_name) {
+                Schema::Ref {
+                    name: fully_qualified_name,
+                }
+            } else {
+                named_schemas.insert(
+                    fully_qualified_name.clone(),
+                    Schema::Ref {
+                        name: fully_qualified_name,
+                    },
+                );
+                Schema::Fixed {
+                    name,
+                    aliases: None,
+                    doc: None,
+                    size: N,
+                    attributes: Default::default(),
+                }
+            }
+        }
+    }
+
     impl<T> AvroSchemaComponent for Option<T>
     where
         T: AvroSchemaComponent,
@@ -1996,6 +2489,37 @@ pub mod derive {
         }
     }
 
+    impl<T> AvroSchemaComponent for BTreeMap<String, T>
+    where
+        T: AvroSchemaComponent,
+    {
+        fn get_schema_in_ctxt(
+            named_schemas: &mut Names,
+            enclosing_namespace: &Namespace,
+        ) -> Schema {
+            Schema::Map(Box::new(T::get_schema_in_ctxt(
+                named_schemas,
+                enclosing_namespace,
+            )))
+        }
+    }
+
+    #[cfg(feature = "indexmap")]
+    impl<T> AvroSchemaComponent for indexmap::IndexMap<String, T>
+    where
+        T: AvroSchemaComponent,
+    {
+        fn get_schema_in_ctxt(
+            named_schemas: &mut Names,
+            enclosing_namespace: &Namespace,
+        ) -> Schema {
+            Schema::Map(Box::new(T::get_schema_in_ctxt(
+                named_schemas,
+                enclosing_namespace,
+            )))
+        }
+    }
+
     impl<T> AvroSchemaComponent for Box<T>
     where
         T: AvroSchemaComponent,
@@ -2008,6 +2532,30 @@ pub mod derive {
         }
     }
 
+    impl<T> AvroSchemaComponent for std::sync::Arc<T>
+    where
+        T: AvroSchemaComponent,
+    {
+        fn get_schema_in_ctxt(
+            named_schemas: &mut Names,
+            enclosing_namespace: &Namespace,
+        ) -> Schema {
+            T::get_schema_in_ctxt(named_schemas, enclosing_namespace)
+        }
+    }
+
+    impl<T> AvroSchemaComponent for std::rc::Rc<T>
+    where
+        T: AvroSchemaComponent,
+    {
+        fn get_schema_in_ctxt(
+            named_schemas: &mut Names,
+            enclosing_namespace: &Namespace,
+        ) -> Schema {
+            T::get_schema_in_ctxt(named_schemas, enclosing_namespace)
+        }
+    }
+
     impl<T> AvroSchemaComponent for std::sync::Mutex<T>
     where
         T: AvroSchemaComponent,
@@ -3000,6 +3548,39 @@ mod tests {
         )
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)] // Sha256 uses an inline assembly instructions which is not supported by miri
+    fn test_cached_schema() {
+        use sha2::Sha256;
+
+        struct Cached;
+
+        impl AvroSchema for Cached {
+            fn get_schema() -> Schema {
+                Schema::parse_str(
+                    r#"{"type": "record", "name": "Cached", "fields": [{"name": "a", "type": "long"}]}"#,
+                )
+                .unwrap()
+            }
+        }
+
+        let schema = Cached::get_schema();
+        let cached = Cached::get_cached_schema();
+        assert_eq!(cached.schema(), &schema);
+        assert_eq!(cached.canonical_form(), schema.canonical_form());
+        assert_eq!(cached.rabin_fingerprint(), &schema.fingerprint::<Rabin>());
+        assert_eq!(
+            cached.fingerprint::<Sha256>(),
+            schema.fingerprint::<Sha256>()
+        );
+        assert!(std::ptr::eq(cached, Cached::get_cached_schema()));
+        assert_eq!(Cached::get_canonical_form(), schema.canonical_form());
+        assert_eq!(
+            Cached::get_rabin_fingerprint(),
+            &schema.fingerprint::<Rabin>()
+        );
+    }
+
     #[test]
     fn test_logical_types() {
         let schema = Schema::parse_str(r#"{"type": "int", "logicalType": "date"}"#).unwrap();
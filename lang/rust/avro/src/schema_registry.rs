@@ -0,0 +1,406 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Logic for talking to a Confluent-compatible schema registry.
+use crate::{
+    confluent::SchemaIdStore, schema::Schema, schema_compatibility::SchemaCompatibility,
+    AvroResult, Error,
+};
+use std::{collections::HashMap, sync::Mutex};
+
+/// A schema registered under a subject.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisteredSchema {
+    pub subject: String,
+    pub id: u32,
+    pub version: u32,
+    pub schema: Schema,
+}
+
+/// The operations of a Confluent-compatible schema registry.
+///
+/// Compatibility is checked in the registry's `BACKWARD` mode: a new version of a subject must be
+/// able to read data written with its latest version.
+pub trait SchemaRegistry {
+    /// Registers `schema` as a new version of `subject`, returning its id. Registering a schema
+    /// already known under the subject returns its existing id.
+    fn register(&self, subject: &str, schema: &Schema) -> AvroResult<u32>;
+
+    /// Returns the schema with the given id, or `None` if there is no such schema.
+    fn schema_by_id(&self, id: u32) -> AvroResult<Option<Schema>>;
+
+    /// Returns the latest version registered under `subject`, or `None` if there is no such
+    /// subject.
+    fn latest_version(&self, subject: &str) -> AvroResult<Option<RegisteredSchema>>;
+
+    /// Checks whether `schema` could be registered as a new version of `subject`.
+    fn is_compatible(&self, subject: &str, schema: &Schema) -> AvroResult<bool>;
+}
+
+#[derive(Default)]
+struct MockState {
+    /// The schema with id `N` is at index `N - 1`.
+    schemas: Vec<Schema>,
+    subjects: HashMap<String, Vec<u32>>,
+}
+
+/// An in-process `SchemaRegistry` keeping everything in memory, for tests and offline use.
+#[derive(Default)]
+pub struct MockSchemaRegistry {
+    state: Mutex<MockState>,
+}
+
+impl MockSchemaRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SchemaRegistry for MockSchemaRegistry {
+    fn register(&self, subject: &str, schema: &Schema) -> AvroResult<u32> {
+        let mut state = self.state.lock().unwrap();
+        let canonical_form = schema.canonical_form();
+        let versions = state.subjects.get(subject).cloned().unwrap_or_default();
+        if let Some(id) = versions
+            .iter()
+            .find(|id| state.schemas[**id as usize - 1].canonical_form() == canonical_form)
+        {
+            return Ok(*id);
+        }
+        if let Some(latest) = versions.last() {
+            if !SchemaCompatibility::can_read(&state.schemas[*latest as usize - 1], schema) {
+                return Err(Error::SchemaRegistryIncompatible(subject.to_string()));
+            }
+        }
+
+        let id = match state
+            .schemas
+            .iter()
+            .position(|known| known.canonical_form() == canonical_form)
+        {
+            Some(index) => index as u32 + 1,
+            None => {
+                state.schemas.push(schema.clone());
+                state.schemas.len() as u32
+            }
+        };
+        state
+            .subjects
+            .entry(subject.to_string())
+            .or_default()
+            .push(id);
+        Ok(id)
+    }
+
+    fn schema_by_id(&self, id: u32) -> AvroResult<Option<Schema>> {
+        let state = self.state.lock().unwrap();
+        Ok((id as usize)
+            .checked_sub(1)
+            .and_then(|index| state.schemas.get(index))
+            .cloned())
+    }
+
+    fn latest_version(&self, subject: &str) -> AvroResult<Option<RegisteredSchema>> {
+        let state = self.state.lock().unwrap();
+        Ok(state.subjects.get(subject).and_then(|versions| {
+            versions.last().map(|id| RegisteredSchema {
+                subject: subject.to_string(),
+                id: *id,
+                version: versions.len() as u32,
+                schema: state.schemas[*id as usize - 1].clone(),
+            })
+        }))
+    }
+
+    fn is_compatible(&self, subject: &str, schema: &Schema) -> AvroResult<bool> {
+        Ok(match self.latest_version(subject)? {
+            Some(latest) => SchemaCompatibility::can_read(&latest.schema, schema),
+            None => true,
+        })
+    }
+}
+
+impl SchemaIdStore for MockSchemaRegistry {
+    fn lookup(&self, id: u32) -> AvroResult<Option<Schema>> {
+        self.schema_by_id(id)
+    }
+}
+
+#[cfg(feature = "registry")]
+pub use rest::RestSchemaRegistry;
+
+#[cfg(feature = "registry")]
+mod rest {
+    use super::{RegisteredSchema, SchemaRegistry};
+    use crate::{confluent::SchemaIdStore, schema::Schema, AvroResult, Error};
+    use serde_json::{json, Value as JsonValue};
+    use std::{collections::HashMap, sync::Mutex};
+
+    const CONTENT_TYPE: &str = "application/vnd.schemaregistry.v1+json";
+
+    #[derive(Default)]
+    struct Cache {
+        schemas: HashMap<u32, Schema>,
+        /// Ids by subject and Parsing Canonical Form.
+        ids: HashMap<(String, String), u32>,
+    }
+
+    /// A `SchemaRegistry` talking to a Confluent-compatible schema registry over its REST API.
+    ///
+    /// Schemas fetched by id and the ids of registered schemas are cached, as they never change.
+    pub struct RestSchemaRegistry {
+        base_url: String,
+        agent: ureq::Agent,
+        cache: Mutex<Cache>,
+    }
+
+    impl RestSchemaRegistry {
+        /// Creates a client for the registry at `base_url`, e.g. `http://localhost:8081`.
+        pub fn new(base_url: impl Into<String>) -> Self {
+            Self {
+                base_url: base_url.into().trim_end_matches('/').to_string(),
+                agent: ureq::Agent::new(),
+                cache: Mutex::new(Cache::default()),
+            }
+        }
+
+        /// Sends a request, returning `None` when the registry answers `404 Not Found`.
+        fn send(
+            &self,
+            method: &str,
+            path: &str,
+            body: Option<JsonValue>,
+        ) -> AvroResult<Option<JsonValue>> {
+            let request = self
+                .agent
+                .request(method, &format!("{}{}", self.base_url, path))
+                .set("Accept", CONTENT_TYPE);
+            let result = match body {
+                Some(body) => request
+                    .set("Content-Type", CONTENT_TYPE)
+                    .send_string(&body.to_string()),
+                None => request.call(),
+            };
+            let response = match result {
+                Ok(response) => response,
+                Err(ureq::Error::Status(404, _)) => return Ok(None),
+                Err(ureq::Error::Status(status, response)) => {
+                    return Err(Error::SchemaRegistryStatus {
+                        status,
+                        message: response.into_string().unwrap_or_default(),
+                    })
+                }
+                Err(e) => return Err(Error::SchemaRegistryRequest(e.to_string())),
+            };
+            let body = response
+                .into_string()
+                .map_err(|e| Error::SchemaRegistryRequest(e.to_string()))?;
+            serde_json::from_str(&body)
+                .map(Some)
+                .map_err(Error::SchemaRegistryResponse)
+        }
+
+        fn schema_body(schema: &Schema) -> AvroResult<JsonValue> {
+            let schema = serde_json::to_string(schema).map_err(Error::ConvertJsonToString)?;
+            Ok(json!({ "schema": schema }))
+        }
+    }
+
+    fn field<'a>(response: &'a JsonValue, name: &str) -> AvroResult<&'a JsonValue> {
+        response
+            .get(name)
+            .ok_or_else(|| Error::SchemaRegistryMissingField(name.to_string()))
+    }
+
+    pub(super) fn u32_field(response: &JsonValue, name: &str) -> AvroResult<u32> {
+        let n = field(response, name)?
+            .as_u64()
+            .ok_or_else(|| Error::SchemaRegistryMissingField(name.to_string()))?;
+        u32::try_from(n).map_err(|_| Error::SchemaRegistryFieldOutOfRange(name.to_string(), n))
+    }
+
+    /// Percent-encodes a path segment, e.g. a subject, keeping only the unreserved characters of
+    /// RFC 3986.
+    pub(super) fn encode_path_segment(segment: &str) -> String {
+        let mut encoded = String::with_capacity(segment.len());
+        for byte in segment.bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                    encoded.push(byte as char)
+                }
+                _ => encoded.push_str(&format!("%{byte:02X}")),
+            }
+        }
+        encoded
+    }
+
+    fn schema_field(response: &JsonValue) -> AvroResult<Schema> {
+        match field(response, "schema")?.as_str() {
+            Some(schema) => Schema::parse_str(schema),
+            None => Err(Error::SchemaRegistryMissingField("schema".to_string())),
+        }
+    }
+
+    impl SchemaRegistry for RestSchemaRegistry {
+        fn register(&self, subject: &str, schema: &Schema) -> AvroResult<u32> {
+            let key = (subject.to_string(), schema.canonical_form());
+            if let Some(id) = self.cache.lock().unwrap().ids.get(&key) {
+                return Ok(*id);
+            }
+            let path = format!("/subjects/{}/versions", encode_path_segment(subject));
+            let response = match self.send("POST", &path, Some(Self::schema_body(schema)?)) {
+                Err(Error::SchemaRegistryStatus { status: 409, .. }) => {
+                    return Err(Error::SchemaRegistryIncompatible(subject.to_string()))
+                }
+                result => result?
+                    .ok_or_else(|| Error::SchemaRegistryRequest(format!("{path} not found")))?,
+            };
+            let id = u32_field(&response, "id")?;
+
+            let mut cache = self.cache.lock().unwrap();
+            cache.ids.insert(key, id);
+            cache.schemas.insert(id, schema.clone());
+            Ok(id)
+        }
+
+        fn schema_by_id(&self, id: u32) -> AvroResult<Option<Schema>> {
+            if let Some(schema) = self.cache.lock().unwrap().schemas.get(&id) {
+                return Ok(Some(schema.clone()));
+            }
+            match self.send("GET", &format!("/schemas/ids/{id}"), None)? {
+                Some(response) => {
+                    let schema = schema_field(&response)?;
+                    self.cache
+                        .lock()
+                        .unwrap()
+                        .schemas
+                        .insert(id, schema.clone());
+                    Ok(Some(schema))
+                }
+                None => Ok(None),
+            }
+        }
+
+        fn latest_version(&self, subject: &str) -> AvroResult<Option<RegisteredSchema>> {
+            match self.send(
+                "GET",
+                &format!("/subjects/{}/versions/latest", encode_path_segment(subject)),
+                None,
+            )? {
+                Some(response) => Ok(Some(RegisteredSchema {
+                    subject: subject.to_string(),
+                    id: u32_field(&response, "id")?,
+                    version: u32_field(&response, "version")?,
+                    schema: schema_field(&response)?,
+                })),
+                None => Ok(None),
+            }
+        }
+
+        fn is_compatible(&self, subject: &str, schema: &Schema) -> AvroResult<bool> {
+            let path = format!(
+                "/compatibility/subjects/{}/versions/latest",
+                encode_path_segment(subject)
+            );
+            match self.send("POST", &path, Some(Self::schema_body(schema)?))? {
+                Some(response) => field(&response, "is_compatible")?
+                    .as_bool()
+                    .ok_or_else(|| Error::SchemaRegistryMissingField("is_compatible".to_string())),
+                // a subject without any version accepts any schema
+                None => Ok(true),
+            }
+        }
+    }
+
+    impl SchemaIdStore for RestSchemaRegistry {
+        fn lookup(&self, id: u32) -> AvroResult<Option<Schema>> {
+            self.schema_by_id(id)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const V1: &str = r#"{"type": "record", "name": "Event", "fields": [
+        {"name": "a", "type": "long"}
+    ]}"#;
+    const V2: &str = r#"{"type": "record", "name": "Event", "fields": [
+        {"name": "a", "type": "long"},
+        {"name": "b", "type": "string", "default": "none"}
+    ]}"#;
+    const INCOMPATIBLE: &str = r#"{"type": "record", "name": "Event", "fields": [
+        {"name": "c", "type": "long"}
+    ]}"#;
+
+    #[test]
+    fn test_mock_schema_registry() {
+        let v1 = Schema::parse_str(V1).unwrap();
+        let v2 = Schema::parse_str(V2).unwrap();
+        let incompatible = Schema::parse_str(INCOMPATIBLE).unwrap();
+
+        let registry = MockSchemaRegistry::new();
+        assert_eq!(registry.latest_version("events").unwrap(), None);
+        assert!(registry.is_compatible("events", &incompatible).unwrap());
+
+        assert_eq!(registry.register("events", &v1).unwrap(), 1);
+        assert_eq!(registry.register("events", &v1).unwrap(), 1);
+        assert!(registry.is_compatible("events", &v2).unwrap());
+        assert!(!registry.is_compatible("events", &incompatible).unwrap());
+        assert_eq!(registry.register("events", &v2).unwrap(), 2);
+        assert_eq!(registry.register("other", &v2).unwrap(), 2);
+        match registry.register("events", &incompatible) {
+            Err(Error::SchemaRegistryIncompatible(subject)) => assert_eq!(subject, "events"),
+            other => panic!("Expected an incompatible schema error, got {other:?}"),
+        }
+
+        assert_eq!(
+            registry.latest_version("events").unwrap(),
+            Some(RegisteredSchema {
+                subject: "events".to_string(),
+                id: 2,
+                version: 2,
+                schema: v2.clone(),
+            })
+        );
+        assert_eq!(registry.schema_by_id(1).unwrap(), Some(v1));
+        assert_eq!(registry.lookup(2).unwrap(), Some(v2));
+        assert_eq!(registry.schema_by_id(0).unwrap(), None);
+        assert_eq!(registry.schema_by_id(3).unwrap(), None);
+    }
+
+    #[cfg(feature = "registry")]
+    #[test]
+    fn test_rest_schema_registry_helpers() {
+        use super::rest::{encode_path_segment, u32_field};
+
+        assert_eq!(encode_path_segment("events-value"), "events-value");
+        assert_eq!(
+            encode_path_segment("a/b?c%d e.é"),
+            "a%2Fb%3Fc%25d%20e.%C3%A9"
+        );
+
+        let response = serde_json::json!({"id": 7, "version": 4294967296u64});
+        assert_eq!(u32_field(&response, "id").unwrap(), 7);
+        assert!(matches!(
+            u32_field(&response, "version"),
+            Err(Error::SchemaRegistryFieldOutOfRange(name, 4294967296)) if name == "version"
+        ));
+    }
+}
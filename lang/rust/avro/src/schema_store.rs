@@ -0,0 +1,103 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Logic for looking up writer schemas by their fingerprint.
+use crate::{rabin::Rabin, schema::Schema};
+use std::collections::HashMap;
+
+/// A source of writer schemas, keyed by the 8-byte Rabin fingerprint of their
+/// [Parsing Canonical Form], as used by the [single object encoding].
+///
+/// [Parsing Canonical Form]:
+/// https://avro.apache.org/docs/1.8.2/spec.html#Parsing+Canonical+Form+for+Schemas
+/// [single object encoding]:
+/// https://avro.apache.org/docs/current/spec.html#single_object_encoding
+pub trait SchemaStore {
+    /// Returns the schema with the given fingerprint, if known.
+    fn lookup(&self, fingerprint: &[u8; 8]) -> Option<Schema>;
+}
+
+/// A `SchemaStore` keeping all of its schemas in memory.
+#[derive(Debug, Default, Clone)]
+pub struct InMemorySchemaStore {
+    schemas: HashMap<[u8; 8], Schema>,
+}
+
+impl InMemorySchemaStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a schema to the store, returning its fingerprint.
+    pub fn add(&mut self, schema: Schema) -> [u8; 8] {
+        let fingerprint = rabin_fingerprint(&schema);
+        self.schemas.insert(fingerprint, schema);
+        fingerprint
+    }
+
+    /// Returns the number of schemas in the store.
+    pub fn len(&self) -> usize {
+        self.schemas.len()
+    }
+
+    /// Returns `true` if the store has no schemas.
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+    }
+}
+
+impl SchemaStore for InMemorySchemaStore {
+    fn lookup(&self, fingerprint: &[u8; 8]) -> Option<Schema> {
+        self.schemas.get(fingerprint).cloned()
+    }
+}
+
+impl<S: SchemaStore + ?Sized> SchemaStore for &S {
+    fn lookup(&self, fingerprint: &[u8; 8]) -> Option<Schema> {
+        (**self).lookup(fingerprint)
+    }
+}
+
+/// Returns the 8-byte Rabin fingerprint of the schema's Parsing Canonical Form.
+pub(crate) fn rabin_fingerprint(schema: &Schema) -> [u8; 8] {
+    let mut fingerprint = [0; 8];
+    fingerprint.copy_from_slice(&schema.fingerprint::<Rabin>().bytes);
+    fingerprint
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_schema_store() {
+        let long = Schema::parse_str(r#""long""#).unwrap();
+        let string = Schema::parse_str(r#""string""#).unwrap();
+
+        let mut store = InMemorySchemaStore::new();
+        assert!(store.is_empty());
+        let long_fingerprint = store.add(long.clone());
+        let string_fingerprint = store.add(string.clone());
+        assert_eq!(store.len(), 2);
+
+        assert_eq!(long_fingerprint.to_vec(), long.fingerprint::<Rabin>().bytes);
+        assert_eq!(store.lookup(&long_fingerprint), Some(long));
+        assert_eq!(store.lookup(&string_fingerprint), Some(string));
+        assert_eq!(store.lookup(&[0; 8]), None);
+    }
+}
@@ -27,9 +27,8 @@ pub struct SeqSerializer {
     items: Vec<Value>,
 }
 
-pub struct SeqVariantSerializer<'a> {
+pub struct SeqVariantSerializer {
     index: u32,
-    variant: &'a str,
     items: Vec<Value>,
 }
 
@@ -42,9 +41,8 @@ pub struct StructSerializer {
     fields: Vec<(String, Value)>,
 }
 
-pub struct StructVariantSerializer<'a> {
+pub struct StructVariantSerializer {
     index: u32,
-    variant: &'a str,
     fields: Vec<(String, Value)>,
 }
 
@@ -59,17 +57,13 @@ impl SeqSerializer {
     }
 }
 
-impl<'a> SeqVariantSerializer<'a> {
-    pub fn new(index: u32, variant: &'a str, len: Option<usize>) -> SeqVariantSerializer {
+impl SeqVariantSerializer {
+    pub fn new(index: u32, len: Option<usize>) -> SeqVariantSerializer {
         let items = match len {
             Some(len) => Vec::with_capacity(len),
             None => Vec::new(),
         };
-        SeqVariantSerializer {
-            index,
-            variant,
-            items,
-        }
+        SeqVariantSerializer { index, items }
     }
 }
 
@@ -92,11 +86,10 @@ impl StructSerializer {
     }
 }
 
-impl<'a> StructVariantSerializer<'a> {
-    pub fn new(index: u32, variant: &'a str, len: usize) -> StructVariantSerializer {
+impl StructVariantSerializer {
+    pub fn new(index: u32, len: usize) -> StructVariantSerializer {
         StructVariantSerializer {
             index,
-            variant,
             fields: Vec::with_capacity(len),
         }
     }
@@ -108,10 +101,10 @@ impl<'b> ser::Serializer for &'b mut Serializer {
     type SerializeSeq = SeqSerializer;
     type SerializeTuple = SeqSerializer;
     type SerializeTupleStruct = SeqSerializer;
-    type SerializeTupleVariant = SeqVariantSerializer<'b>;
+    type SerializeTupleVariant = SeqVariantSerializer;
     type SerializeMap = MapSerializer;
     type SerializeStruct = StructSerializer;
-    type SerializeStructVariant = StructVariantSerializer<'b>;
+    type SerializeStructVariant = StructVariantSerializer;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         Ok(Value::Boolean(v))
@@ -221,19 +214,13 @@ impl<'b> ser::Serializer for &'b mut Serializer {
         self,
         _: &'static str,
         index: u32,
-        variant: &'static str,
+        _: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: Serialize,
     {
-        Ok(Value::Record(vec![
-            ("type".to_owned(), Value::Enum(index, variant.to_owned())),
-            (
-                "value".to_owned(),
-                Value::Union(index, Box::new(value.serialize(self)?)),
-            ),
-        ]))
+        Ok(Value::Union(index, Box::new(value.serialize(self)?)))
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
@@ -256,10 +243,10 @@ impl<'b> ser::Serializer for &'b mut Serializer {
         self,
         _: &'static str,
         index: u32,
-        variant: &'static str,
+        _: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        Ok(SeqVariantSerializer::new(index, variant, Some(len)))
+        Ok(SeqVariantSerializer::new(index, Some(len)))
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
@@ -278,10 +265,10 @@ impl<'b> ser::Serializer for &'b mut Serializer {
         self,
         _: &'static str,
         index: u32,
-        variant: &'static str,
+        _: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        Ok(StructVariantSerializer::new(index, variant, len))
+        Ok(StructVariantSerializer::new(index, len))
     }
 }
 
@@ -335,7 +322,7 @@ impl ser::SerializeTupleStruct for SeqSerializer {
     }
 }
 
-impl<'a> ser::SerializeSeq for SeqVariantSerializer<'a> {
+impl ser::SerializeSeq for SeqVariantSerializer {
     type Ok = Value;
     type Error = Error;
 
@@ -343,25 +330,23 @@ impl<'a> ser::SerializeSeq for SeqVariantSerializer<'a> {
     where
         T: Serialize,
     {
-        self.items.push(Value::Union(
-            self.index,
-            Box::new(value.serialize(&mut Serializer::default())?),
-        ));
+        self.items
+            .push(value.serialize(&mut Serializer::default())?);
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(Value::Record(vec![
-            (
-                "type".to_owned(),
-                Value::Enum(self.index, self.variant.to_owned()),
-            ),
-            ("value".to_owned(), Value::Array(self.items)),
-        ]))
+        let fields = self
+            .items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| (positional_field_name(i), item))
+            .collect();
+        Ok(Value::Union(self.index, Box::new(Value::Record(fields))))
     }
 }
 
-impl<'a> ser::SerializeTupleVariant for SeqVariantSerializer<'a> {
+impl ser::SerializeTupleVariant for SeqVariantSerializer {
     type Ok = Value;
     type Error = Error;
 
@@ -440,7 +425,7 @@ impl ser::SerializeStruct for StructSerializer {
     }
 }
 
-impl<'a> ser::SerializeStructVariant for StructVariantSerializer<'a> {
+impl ser::SerializeStructVariant for StructVariantSerializer {
     type Ok = Value;
     type Error = Error;
 
@@ -460,19 +445,18 @@ impl<'a> ser::SerializeStructVariant for StructVariantSerializer<'a> {
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(Value::Record(vec![
-            (
-                "type".to_owned(),
-                Value::Enum(self.index, self.variant.to_owned()),
-            ),
-            (
-                "value".to_owned(),
-                Value::Union(self.index, Box::new(Value::Record(self.fields))),
-            ),
-        ]))
+        Ok(Value::Union(
+            self.index,
+            Box::new(Value::Record(self.fields)),
+        ))
     }
 }
 
+/// Name of the record field holding the `index`th field of a tuple struct or variant.
+pub(crate) fn positional_field_name(index: usize) -> String {
+    format!("field_{index}")
+}
+
 /// Interpret a serializeable instance as a `Value`.
 ///
 /// This conversion can fail if the value is not valid as per the Avro specification.
@@ -485,6 +469,7 @@ pub fn to_value<S: Serialize>(value: S) -> Result<Value, Error> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{to_avro_datum, Schema};
     use pretty_assertions::assert_eq;
     use serde::{Deserialize, Serialize};
 
@@ -771,13 +756,7 @@ mod tests {
 
         let expected = Value::Record(vec![(
             "a".to_owned(),
-            Value::Record(vec![
-                ("type".to_owned(), Value::Enum(0, "Double".to_owned())),
-                (
-                    "value".to_owned(),
-                    Value::Union(0, Box::new(Value::Double(64.0))),
-                ),
-            ]),
+            Value::Union(0, Box::new(Value::Double(64.0))),
         )]);
 
         assert_eq!(
@@ -831,19 +810,13 @@ mod tests {
         };
         let expected = Value::Record(vec![(
             "a".to_owned(),
-            Value::Record(vec![
-                ("type".to_owned(), Value::Enum(0, "Val1".to_owned())),
-                (
-                    "value".to_owned(),
-                    Value::Union(
-                        0,
-                        Box::new(Value::Record(vec![
-                            ("x".to_owned(), Value::Float(1.0)),
-                            ("y".to_owned(), Value::Float(2.0)),
-                        ])),
-                    ),
-                ),
-            ]),
+            Value::Union(
+                0,
+                Box::new(Value::Record(vec![
+                    ("x".to_owned(), Value::Float(1.0)),
+                    ("y".to_owned(), Value::Float(2.0)),
+                ])),
+            ),
         )]);
 
         assert_eq!(
@@ -935,6 +908,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_to_value_tuple_struct() {
+        #[derive(Serialize)]
+        struct Point(i32, i32);
+
+        let expected = Value::Array(vec![Value::Int(1), Value::Int(2)]);
+
+        assert_eq!(
+            to_value(Point(1, 2)).unwrap(),
+            expected,
+            "error serializing tuple struct"
+        );
+    }
+
     #[test]
     fn test_to_value_tuple_enum() {
         let test = TestTupleExternalEnum {
@@ -943,17 +930,14 @@ mod tests {
 
         let expected = Value::Record(vec![(
             "a".to_owned(),
-            Value::Record(vec![
-                ("type".to_owned(), Value::Enum(1, "Val2".to_owned())),
-                (
-                    "value".to_owned(),
-                    Value::Array(vec![
-                        Value::Union(1, Box::new(Value::Float(1.0))),
-                        Value::Union(1, Box::new(Value::Float(2.0))),
-                        Value::Union(1, Box::new(Value::Float(3.0))),
-                    ]),
-                ),
-            ]),
+            Value::Union(
+                1,
+                Box::new(Value::Record(vec![
+                    ("field_0".to_owned(), Value::Float(1.0)),
+                    ("field_1".to_owned(), Value::Float(2.0)),
+                    ("field_2".to_owned(), Value::Float(3.0)),
+                ])),
+            ),
         )]);
 
         assert_eq!(
@@ -998,4 +982,112 @@ mod tests {
             "error serializing tuple untagged enum"
         );
     }
+
+    #[test]
+    fn test_to_value_enums_with_legacy_schemas() {
+        // the schemas written for the former representation of enums with data, records of their
+        // variant and of its data, keep working
+        let schema = Schema::parse_str(
+            r#"{"type": "record", "name": "TestSingleValueExternalEnum", "fields": [
+                {"name": "a", "type": {"type": "record", "name": "SingleValueExternalEnum", "fields": [
+                    {"name": "type", "type": {"type": "enum", "name": "Type", "symbols": ["Double", "String"]}},
+                    {"name": "value", "type": ["double", "string"]}
+                ]}}
+            ]}"#,
+        )
+        .unwrap();
+        let value = to_value(TestSingleValueExternalEnum {
+            a: SingleValueExternalEnum::Double(64.0),
+        })
+        .unwrap();
+        let legacy = Value::Record(vec![(
+            "a".to_owned(),
+            Value::Record(vec![
+                ("type".to_owned(), Value::Enum(0, "Double".to_owned())),
+                (
+                    "value".to_owned(),
+                    Value::Union(0, Box::new(Value::Double(64.0))),
+                ),
+            ]),
+        )]);
+        assert_eq!(
+            to_avro_datum(&schema, value).unwrap(),
+            to_avro_datum(&schema, legacy).unwrap()
+        );
+
+        let schema = Schema::parse_str(
+            r#"{"type": "record", "name": "TestStructExternalEnum", "fields": [
+                {"name": "a", "type": {"type": "record", "name": "StructExternalEnum", "fields": [
+                    {"name": "type", "type": {"type": "enum", "name": "Type", "symbols": ["Val1", "Val2"]}},
+                    {"name": "value", "type": [
+                        {"type": "record", "name": "Val1", "fields": [
+                            {"name": "x", "type": "float"},
+                            {"name": "y", "type": "float"}
+                        ]},
+                        {"type": "record", "name": "Val2", "fields": [
+                            {"name": "x", "type": "float"},
+                            {"name": "y", "type": "float"}
+                        ]}
+                    ]}
+                ]}}
+            ]}"#,
+        )
+        .unwrap();
+        let value = to_value(TestStructExternalEnum {
+            a: StructExternalEnum::Val1 { x: 1.0, y: 2.0 },
+        })
+        .unwrap();
+        let legacy = Value::Record(vec![(
+            "a".to_owned(),
+            Value::Record(vec![
+                ("type".to_owned(), Value::Enum(0, "Val1".to_owned())),
+                (
+                    "value".to_owned(),
+                    Value::Union(
+                        0,
+                        Box::new(Value::Record(vec![
+                            ("x".to_owned(), Value::Float(1.0)),
+                            ("y".to_owned(), Value::Float(2.0)),
+                        ])),
+                    ),
+                ),
+            ]),
+        )]);
+        assert_eq!(
+            to_avro_datum(&schema, value).unwrap(),
+            to_avro_datum(&schema, legacy).unwrap()
+        );
+
+        let schema = Schema::parse_str(
+            r#"{"type": "record", "name": "TestTupleExternalEnum", "fields": [
+                {"name": "a", "type": {"type": "record", "name": "TupleExternalEnum", "fields": [
+                    {"name": "type", "type": {"type": "enum", "name": "Type", "symbols": ["Val1", "Val2"]}},
+                    {"name": "value", "type": {"type": "array", "items": ["string", "float"]}}
+                ]}}
+            ]}"#,
+        )
+        .unwrap();
+        let value = to_value(TestTupleExternalEnum {
+            a: TupleExternalEnum::Val2(1.0, 2.0, 3.0),
+        })
+        .unwrap();
+        let legacy = Value::Record(vec![(
+            "a".to_owned(),
+            Value::Record(vec![
+                ("type".to_owned(), Value::Enum(1, "Val2".to_owned())),
+                (
+                    "value".to_owned(),
+                    Value::Array(vec![
+                        Value::Union(1, Box::new(Value::Float(1.0))),
+                        Value::Union(1, Box::new(Value::Float(2.0))),
+                        Value::Union(1, Box::new(Value::Float(3.0))),
+                    ]),
+                ),
+            ]),
+        )]);
+        assert_eq!(
+            to_avro_datum(&schema, value).unwrap(),
+            to_avro_datum(&schema, legacy).unwrap()
+        );
+    }
 }
@@ -0,0 +1,179 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Serde helpers writing `std::time::SystemTime`s as `timestamp-micros`, i.e. as the number of
+//! microseconds since the UNIX epoch, rather than as serde's record of seconds and nanoseconds.
+//!
+//! The nanoseconds within the microsecond are truncated: times are read back as the microsecond
+//! at or before them. When deriving `AvroSchema`, the field also needs
+//! `#[avro(logical_type = "timestamp-micros")]`.
+//!
+//! ```
+//! use apache_avro::{from_value, to_value, types::Value};
+//! use serde::{Deserialize, Serialize};
+//! use std::time::{Duration, SystemTime, UNIX_EPOCH};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Event {
+//!     #[serde(with = "apache_avro::serde_timestamp_micros")]
+//!     time: SystemTime,
+//!     #[serde(with = "apache_avro::serde_timestamp_micros::option")]
+//!     end: Option<SystemTime>,
+//! }
+//!
+//! let event = Event {
+//!     time: UNIX_EPOCH + Duration::from_micros(1_681_000_000_123_456),
+//!     end: None,
+//! };
+//! let value = to_value(&event).unwrap();
+//! assert_eq!(
+//!     value,
+//!     Value::Record(vec![
+//!         ("time".to_string(), Value::Long(1_681_000_000_123_456)),
+//!         ("end".to_string(), Value::Union(0, Box::new(Value::Null))),
+//!     ])
+//! );
+//! assert_eq!(from_value::<Event>(&value).unwrap(), event);
+//! ```
+use serde::{de, ser, Deserialize, Deserializer, Serializer};
+use std::{
+    convert::TryFrom,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Serializes a `SystemTime` as its microseconds since the UNIX epoch.
+pub fn serialize<S>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_i64(to_micros(time).map_err(ser::Error::custom)?)
+}
+
+/// Deserializes a `SystemTime` from its microseconds since the UNIX epoch.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<SystemTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    from_micros(i64::deserialize(deserializer)?).map_err(de::Error::custom)
+}
+
+/// The same helpers for `Option<SystemTime>`s.
+pub mod option {
+    use super::*;
+
+    /// Serializes an `Option<SystemTime>` as the microseconds since the UNIX epoch, if any.
+    pub fn serialize<S>(time: &Option<SystemTime>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match time {
+            Some(time) => serializer.serialize_some(&to_micros(time).map_err(ser::Error::custom)?),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserializes an `Option<SystemTime>` from the microseconds since the UNIX epoch, if any.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<SystemTime>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<i64>::deserialize(deserializer)?
+            .map(from_micros)
+            .transpose()
+            .map_err(de::Error::custom)
+    }
+}
+
+const OUT_OF_RANGE: &str = "SystemTime is out of the range of a timestamp-micros";
+
+fn to_micros(time: &SystemTime) -> Result<i64, &'static str> {
+    let micros = match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => i64::try_from(after.as_micros()).ok(),
+        Err(before) => {
+            // truncated towards the past too
+            let before = before.duration();
+            let micros = before.as_micros() + u128::from(before.subsec_nanos() % 1_000 != 0);
+            i64::try_from(micros).ok().map(|micros| -micros)
+        }
+    };
+    micros.ok_or(OUT_OF_RANGE)
+}
+
+fn from_micros(micros: i64) -> Result<SystemTime, &'static str> {
+    let duration = Duration::from_micros(micros.unsigned_abs());
+    if micros >= 0 {
+        UNIX_EPOCH.checked_add(duration)
+    } else {
+        UNIX_EPOCH.checked_sub(duration)
+    }
+    .ok_or(OUT_OF_RANGE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{from_value, to_value, types::Value};
+    use pretty_assertions::assert_eq;
+    use serde::Serialize;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Event {
+        #[serde(with = "crate::serde_timestamp_micros")]
+        time: SystemTime,
+        #[serde(with = "crate::serde_timestamp_micros::option")]
+        end: Option<SystemTime>,
+    }
+
+    #[test]
+    fn test_timestamp_micros() {
+        let event = Event {
+            time: UNIX_EPOCH + Duration::new(1_681_000_000, 123_456_789),
+            end: Some(UNIX_EPOCH - Duration::new(1, 500)),
+        };
+        let value = to_value(&event).unwrap();
+        assert_eq!(
+            value,
+            Value::Record(vec![
+                ("time".to_owned(), Value::Long(1_681_000_000_123_456)),
+                (
+                    "end".to_owned(),
+                    Value::Union(1, Box::new(Value::Long(-1_000_001)))
+                ),
+            ])
+        );
+        // the nanoseconds within the microsecond are truncated
+        assert_eq!(
+            from_value::<Event>(&value).unwrap(),
+            Event {
+                time: UNIX_EPOCH + Duration::from_micros(1_681_000_000_123_456),
+                end: Some(UNIX_EPOCH - Duration::from_micros(1_000_001)),
+            }
+        );
+
+        let value = Value::Record(vec![
+            ("time".to_owned(), Value::TimestampMicros(-1)),
+            ("end".to_owned(), Value::Union(0, Box::new(Value::Null))),
+        ]);
+        assert_eq!(
+            from_value::<Event>(&value).unwrap(),
+            Event {
+                time: UNIX_EPOCH - Duration::from_micros(1),
+                end: None,
+            }
+        );
+    }
+}
@@ -435,6 +435,31 @@ impl Value {
                     None
                 }
             }
+            // e.g. byte arrays, which serde serializes as tuples
+            (Value::Array(items), &Schema::Fixed { size, .. }) => {
+                if items.len() != size {
+                    Some(format!(
+                        "The array's length ({}) is different than the schema's size ({})",
+                        items.len(),
+                        size
+                    ))
+                } else if !Value::is_bytes_array(items) {
+                    Some("The array's items are not all bytes".to_string())
+                } else {
+                    None
+                }
+            }
+            // e.g. byte vectors, which serde serializes as sequences
+            (Value::Array(items), &Schema::Bytes) => {
+                if Value::is_bytes_array(items) {
+                    None
+                } else {
+                    Some("The array's items are not all bytes".to_string())
+                }
+            }
+            (Value::Array(_), Schema::Decimal { inner, .. }) => {
+                self.validate_internal(inner, names, enclosing_namespace)
+            }
             (&Value::Fixed(n, _), &Schema::Duration) => {
                 if n != 12 {
                     Some(format!(
@@ -469,10 +494,24 @@ impl Value {
                 .get(i as usize)
                 .map(|schema| value.validate_internal(schema, names, enclosing_namespace))
                 .unwrap_or_else(|| Some(format!("No schema in the union at position '{i}'"))),
-            (v, Schema::Union(inner)) => match inner.find_schema(v) {
-                Some(_) => None,
-                None => Some("Could not find matching type in union".to_string()),
-            },
+            // the unit variants of enums with data are serialized as `Enum`s
+            (Value::Union(..), Schema::Record { .. }) => {
+                match self.as_legacy_variant(schema, names, enclosing_namespace) {
+                    Some(legacy) => legacy.validate_internal(schema, names, enclosing_namespace),
+                    None => Some("Unsupported value-schema combination".to_string()),
+                }
+            }
+            (&Value::Enum(i, _), Schema::Union(inner))
+                if inner.is_unit_variant(i, names, enclosing_namespace) =>
+            {
+                None
+            }
+            (v, Schema::Union(inner)) => {
+                match inner.find_schema_with_known_schemata(v, names, enclosing_namespace) {
+                    Some(_) => None,
+                    None => Some("Could not find matching type in union".to_string()),
+                }
+            }
             (Value::Array(items), Schema::Array(inner)) => items.iter().fold(None, |acc, item| {
                 Value::accumulate(
                     acc,
@@ -545,10 +584,88 @@ impl Value {
                     }
                 })
             }
+            // e.g. tuple structs, which serde serializes as sequences of their fields
+            (Value::Array(items), Schema::Record { fields, .. }) => {
+                if items.len() != fields.len() {
+                    return Some(format!(
+                        "The array's length ({}) is different than the schema's number of fields ({})",
+                        items.len(),
+                        fields.len()
+                    ));
+                }
+                items.iter().zip(fields).fold(None, |acc, (item, field)| {
+                    Value::accumulate(
+                        acc,
+                        item.validate_internal(&field.schema, names, enclosing_namespace),
+                    )
+                })
+            }
             (_v, _s) => Some("Unsupported value-schema combination".to_string()),
         }
     }
 
+    /// Convert a variant of an enum with data, serialized as a union of the variants, to the
+    /// representation such variants had before: a record holding the variant as an enum named
+    /// `type`, and its data as `value`. Return `None` if `self` is not a variant, or if `schema`
+    /// is not the record of this representation.
+    ///
+    /// It keeps the values of variants valid against the schemas written for that representation.
+    pub(crate) fn as_legacy_variant<S: std::borrow::Borrow<Schema>>(
+        &self,
+        schema: &Schema,
+        names: &HashMap<Name, S>,
+        enclosing_namespace: &Namespace,
+    ) -> Option<Value> {
+        let (index, data) = match self {
+            Value::Union(index, data) => (*index, data.as_ref()),
+            _ => return None,
+        };
+        let (record_namespace, type_field, value_field) = match schema {
+            Schema::Record { name, fields, .. } => match &fields[..] {
+                [type_field, value_field]
+                    if type_field.name == "type" && value_field.name == "value" =>
+                {
+                    (
+                        name.fully_qualified_name(enclosing_namespace).namespace,
+                        type_field,
+                        value_field,
+                    )
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let symbols = match &type_field.schema {
+            Schema::Enum { symbols, .. } => symbols,
+            Schema::Ref { name } => match names
+                .get(&name.fully_qualified_name(&record_namespace))
+                .map(|schema| schema.borrow())
+            {
+                Some(Schema::Enum { symbols, .. }) => symbols,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let value = match (&value_field.schema, data) {
+            // the fields of tuple variants were an array of unions
+            (Schema::Array(_), Value::Record(fields)) => Value::Array(
+                fields
+                    .iter()
+                    .map(|(_, field)| Value::Union(index, Box::new(field.clone())))
+                    .collect(),
+            ),
+            (Schema::Array(_), _) => return None,
+            (_, data) => Value::Union(index, Box::new(data.clone())),
+        };
+        Some(Value::Record(vec![
+            (
+                "type".to_owned(),
+                Value::Enum(index, symbols.get(index as usize)?.clone()),
+            ),
+            ("value".to_owned(), value),
+        ]))
+    }
+
     /// Attempt to perform schema resolution on the value, with the given
     /// [Schema](../schema/enum.Schema.html).
     ///
@@ -561,7 +678,7 @@ impl Value {
         self.resolve_internal(schema, rs.get_names(), &enclosing_namespace)
     }
 
-    fn resolve_internal(
+    pub(crate) fn resolve_internal(
         mut self,
         schema: &Schema,
         names: &NamesRef,
@@ -815,6 +932,20 @@ impl Value {
                 }
             }
             Value::String(s) => Ok(Value::Fixed(s.len(), s.into_bytes())),
+            Value::Array(items) => {
+                if items.len() == size {
+                    let bytes = items
+                        .into_iter()
+                        .map(Value::try_u8)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok(Value::Fixed(size, bytes))
+                } else {
+                    Err(Error::CompareFixedSizes {
+                        size,
+                        n: items.len(),
+                    })
+                }
+            }
             other => Err(Error::GetStringForFixed(other.into())),
         }
     }
@@ -995,7 +1126,14 @@ impl Value {
         Ok(Value::Record(new_fields))
     }
 
-    fn try_u8(self) -> AvroResult<u8> {
+    /// Whether all the items of an array are bytes, i.e. `Int`s between 0 and 255.
+    pub(crate) fn is_bytes_array(items: &[Value]) -> bool {
+        items
+            .iter()
+            .all(|item| matches!(item, Value::Int(n) if u8::try_from(*n).is_ok()))
+    }
+
+    pub(crate) fn try_u8(self) -> AvroResult<u8> {
         let int = self.resolve(&Schema::Int)?;
         if let Value::Int(n) = int {
             if n >= 0 && n <= i32::from(u8::MAX) {
@@ -1199,6 +1337,31 @@ mod tests {
             )
             .as_str(),
         );
+
+        let bytes = |items: &[i32]| Value::Array(items.iter().map(|&i| Value::Int(i)).collect());
+        assert!(bytes(&[0, 1, 2, 255]).validate(&schema));
+        let value = bytes(&[0, 1, 2]);
+        assert!(!value.validate(&schema));
+        assert_logged(
+            format!(
+                "Invalid value: {:?} for schema: {:?}. Reason: {}",
+                value, schema, "The array's length (3) is different than the schema's size (4)"
+            )
+            .as_str(),
+        );
+        let value = bytes(&[0, 1, 2, 256]);
+        assert!(!value.validate(&schema));
+        assert_logged(
+            format!(
+                "Invalid value: {:?} for schema: {:?}. Reason: {}",
+                value, schema, "The array's items are not all bytes"
+            )
+            .as_str(),
+        );
+        assert_eq!(
+            bytes(&[0, 1, 2, 255]).resolve(&schema).unwrap(),
+            Value::Fixed(4, vec![0, 1, 2, 255])
+        );
     }
 
     #[test]
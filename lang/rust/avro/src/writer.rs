@@ -16,18 +16,34 @@
 // under the License.
 
 //! Logic handling writing in Avro format at user level.
+#[cfg(feature = "zstandard")]
+use crate::codec::{ZstdDictionaryProvider, ZSTD_DICTIONARY_ID_KEY, ZSTD_DICTIONARY_KEY};
+#[cfg(feature = "encryption")]
+use crate::encryption::{
+    EncryptionKey, KeyProvider, AES_256_GCM, ENCRYPTION_KEY, ENCRYPTION_KEY_ID_KEY,
+};
 use crate::{
+    checksum::{block_crc32, BlockChecksum, BlockChecksums},
     encode::{encode, encode_internal, encode_to_vec},
     rabin::Rabin,
-    schema::{AvroSchema, ResolvedOwnedSchema, ResolvedSchema, Schema},
+    reader::read_header,
+    schema::{AvroSchema, ResolvedOwnedSchema, ResolvedSchema, Schema, SchemaFingerprint},
     ser::Serializer,
     types::Value,
-    AvroResult, Codec, Error,
+    AvroResult, Codec, CodecOptions, Error,
 };
 use serde::Serialize;
-use std::{collections::HashMap, convert::TryFrom, io::Write, marker::PhantomData};
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::TryFrom,
+    io::{Read, Seek, SeekFrom, Write},
+    marker::PhantomData,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
 
-const DEFAULT_BLOCK_SIZE: usize = 16000;
+pub(crate) const DEFAULT_BLOCK_SIZE: usize = 16000;
 const AVRO_OBJECT_HEADER: &[u8] = b"Obj\x01";
 
 /// Main interface for writing Avro formatted values.
@@ -39,10 +55,16 @@ pub struct Writer<'a, W> {
     resolved_schema: Option<ResolvedSchema<'a>>,
     #[builder(default = Codec::Null)]
     codec: Codec,
+    /// Options tuning the compression of the `codec`, such as its level.
+    #[builder(default)]
+    codec_options: CodecOptions,
     #[builder(default = DEFAULT_BLOCK_SIZE)]
     block_size: usize,
     #[builder(default = Vec::with_capacity(block_size), setter(skip))]
     buffer: Vec<u8>,
+    /// The block being compressed, apart from `buffer` so that it is kept if compression fails.
+    #[builder(default, setter(skip))]
+    compressed: Vec<u8>,
     #[builder(default, setter(skip))]
     serializer: Serializer,
     #[builder(default = 0, setter(skip))]
@@ -53,6 +75,57 @@ pub struct Writer<'a, W> {
     has_header: bool,
     #[builder(default)]
     user_metadata: HashMap<String, Value>,
+    /// Number of worker threads used to compress blocks. With `0` (the default) blocks are
+    /// compressed synchronously on the calling thread.
+    #[builder(default = 0)]
+    compression_workers: usize,
+    /// Maximum number of blocks handed to the compression workers that have not been written
+    /// yet. Appending blocks while at this limit waits for the oldest one to be written.
+    #[builder(default = 2 * compression_workers)]
+    max_in_flight_blocks: usize,
+    #[builder(default, setter(skip))]
+    pipeline: Option<CompressionPipeline>,
+    /// Maximum number of values in a block.
+    #[builder(default, setter(strip_option))]
+    max_block_values: Option<usize>,
+    /// Size a block should have once compressed. It is estimated from the compression ratio of
+    /// the previous blocks, so actual blocks might be a bit bigger or smaller.
+    #[builder(default, setter(strip_option))]
+    target_compressed_block_size: Option<usize>,
+    /// Maximum amount of time a block stays open. It is checked whenever a value is appended,
+    /// see also [`flush_if_expired`](struct.Writer.html#method.flush_if_expired).
+    #[builder(default, setter(strip_option))]
+    flush_interval: Option<Duration>,
+    #[builder(default, setter(skip))]
+    block_opened_at: Option<Instant>,
+    #[builder(default = 1.0, setter(skip))]
+    compression_ratio: f64,
+    #[builder(default = 0, setter(skip))]
+    position: u64,
+    /// Whether `position` is relative to the start of the file, which it is not when appending
+    /// with the `append_to*` functions until `at_position` is called.
+    #[builder(default = true, setter(skip))]
+    position_known: bool,
+    /// Records a checksum of each block written, available through
+    /// [`block_checksums`](struct.Writer.html#method.block_checksums).
+    #[builder(default)]
+    checksum_blocks: bool,
+    #[builder(default, setter(skip))]
+    checksums: Vec<BlockChecksum>,
+    /// Id of the `Zstandard` dictionary the blocks of the file being appended to are compressed
+    /// with.
+    #[cfg(feature = "zstandard")]
+    #[builder(default, setter(skip))]
+    zstd_dictionary_id: Option<u32>,
+    /// Key encrypting the blocks once compressed. Its id is written in the file header, under
+    /// the `avro.encryption.key.id` key, so that readers can find it.
+    #[cfg(feature = "encryption")]
+    #[builder(default, setter(strip_option))]
+    encryption_key: Option<Arc<EncryptionKey>>,
+    /// Id of the key the blocks of the file being appended to are encrypted with.
+    #[cfg(feature = "encryption")]
+    #[builder(default, setter(skip))]
+    encryption_key_id: Option<String>,
 }
 
 impl<'a, W: Write> Writer<'a, W> {
@@ -97,6 +170,9 @@ impl<'a, W: Write> Writer<'a, W> {
     /// Creates a `Writer` that will append values to already populated
     /// `std::io::Write` using the provided `marker`
     /// No compression `Codec` will be used.
+    ///
+    /// The length of what is already written must be given with `at_position` to `sync` or to
+    /// record block checksums.
     pub fn append_to(schema: &'a Schema, writer: W, marker: [u8; 16]) -> Self {
         Writer::append_to_with_codec(schema, writer, Codec::Null, marker)
     }
@@ -116,6 +192,7 @@ impl<'a, W: Write> Writer<'a, W> {
             .marker(marker)
             .build();
         w.has_header = true;
+        w.position_known = false;
         w.resolved_schema = ResolvedSchema::try_from(schema).ok();
         w
     }
@@ -136,6 +213,7 @@ impl<'a, W: Write> Writer<'a, W> {
             .marker(marker)
             .build();
         w.has_header = true;
+        w.position_known = false;
         w.resolved_schema = ResolvedSchema::try_from(schemata).ok();
         w
     }
@@ -175,9 +253,12 @@ impl<'a, W: Write> Writer<'a, W> {
             Some(ref rs) => {
                 write_value_ref_resolved(self.schema, rs, value, &mut self.buffer)?;
                 self.num_values += 1;
+                if self.num_values == 1 && self.flush_interval.is_some() {
+                    self.block_opened_at = Some(Instant::now());
+                }
 
-                if self.buffer.len() >= self.block_size {
-                    return self.flush().map(|b| b + n);
+                if self.is_block_full() || self.is_block_expired() {
+                    return self.end_block().map(|b| b + n);
                 }
 
                 Ok(n)
@@ -185,7 +266,7 @@ impl<'a, W: Write> Writer<'a, W> {
             None => {
                 let rs = ResolvedSchema::try_from(self.schema)?;
                 self.resolved_schema = Some(rs);
-                self.append_value_ref(value)
+                self.append_value_ref(value).map(|m| m + n)
             }
         }
     }
@@ -293,31 +374,245 @@ impl<'a, W: Write> Writer<'a, W> {
     /// Flush the content appended to a `Writer`. Call this function to make sure all the content
     /// has been written before releasing the `Writer`.
     ///
+    /// When compression workers are used, this also waits for all the blocks still being
+    /// compressed and writes them.
+    ///
     /// Return the number of bytes written.
     pub fn flush(&mut self) -> AvroResult<usize> {
+        let num_bytes = self.end_block()?;
+        Ok(num_bytes + self.write_compressed_blocks(0)?)
+    }
+
+    /// Flush the block currently being buffered if it has been open for longer than the
+    /// configured `flush_interval`. Long-running writers receiving values sporadically can call
+    /// this periodically to bound the time values stay buffered.
+    ///
+    /// Return the number of bytes written.
+    pub fn flush_if_expired(&mut self) -> AvroResult<usize> {
+        if self.is_block_expired() {
+            self.flush()
+        } else {
+            Ok(0)
+        }
+    }
+
+    /// Terminate the current block and emit a sync marker, writing the header first if needed.
+    ///
+    /// Return the position in the file, in bytes, right after the marker. When appending with the
+    /// `append_to*` functions, it requires the starting position to be given with `at_position`.
+    pub fn sync(&mut self) -> AvroResult<u64> {
+        if !self.position_known {
+            return Err(Error::UnknownWriterPosition);
+        }
+        self.maybe_write_header()?;
+        self.flush()?;
+        Ok(self.position)
+    }
+
+    /// Whether the block being buffered has reached one of the configured size limits.
+    fn is_block_full(&self) -> bool {
+        if self.buffer.len() >= self.block_size {
+            return true;
+        }
+        if let Some(max_block_values) = self.max_block_values {
+            if self.num_values >= max_block_values {
+                return true;
+            }
+        }
+        if let Some(target) = self.target_compressed_block_size {
+            if self.buffer.len() as f64 * self.compression_ratio >= target as f64 {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Whether the block being buffered has been open for longer than the `flush_interval`.
+    fn is_block_expired(&self) -> bool {
+        match (self.flush_interval, self.block_opened_at) {
+            (Some(interval), Some(opened_at)) => opened_at.elapsed() >= interval,
+            _ => false,
+        }
+    }
+
+    /// Terminate the block currently being buffered. It is either compressed and written
+    /// straight away or, when compression workers are configured, handed over to them.
+    ///
+    /// Return the number of bytes written.
+    fn end_block(&mut self) -> AvroResult<usize> {
         if self.num_values == 0 {
             return Ok(0);
         }
+        #[cfg(feature = "zstandard")]
+        self.check_zstd_dictionary()?;
+        let num_values = self.num_values;
 
-        self.codec.compress(&mut self.buffer)?;
+        if self.compression_workers == 0 {
+            let mut data = std::mem::take(&mut self.compressed);
+            data.clear();
+            data.extend_from_slice(&self.buffer);
+            let compressed = self
+                .codec
+                .compress_with_options(&mut data, &self.codec_options);
+            if let Err(e) = compressed {
+                // the block is still buffered, so that flushing can be retried
+                self.compressed = data;
+                return Err(e);
+            }
+            let uncompressed_len = self.buffer.len();
+            self.buffer.clear();
+            self.num_values = 0;
+            self.block_opened_at = None;
+            let num_bytes = self.write_block(num_values, uncompressed_len, &mut data);
+            self.compressed = data;
+            return num_bytes;
+        }
 
-        let num_values = self.num_values;
-        let stream_len = self.buffer.len();
+        self.num_values = 0;
+        self.block_opened_at = None;
+
+        let data = std::mem::replace(&mut self.buffer, Vec::with_capacity(self.block_size));
+        let workers = self.compression_workers;
+        self.pipeline
+            .get_or_insert_with(|| CompressionPipeline::new(workers))
+            .submit(self.codec, self.codec_options.clone(), num_values, data)?;
+
+        self.write_compressed_blocks(self.max_in_flight_blocks.max(1))
+    }
+
+    /// Write the blocks coming out of the compression workers, in submission order, until at
+    /// most `max_in_flight` of them are left in the pipeline.
+    ///
+    /// Return the number of bytes written.
+    fn write_compressed_blocks(&mut self, max_in_flight: usize) -> AvroResult<usize> {
+        let mut num_bytes = 0;
+        while let Some(pipeline) = self.pipeline.as_mut() {
+            if pipeline.in_flight.len() <= max_in_flight {
+                break;
+            }
+            if let Some(block) = pipeline.next_block() {
+                let (num_values, uncompressed_len, mut data) = block?;
+                num_bytes += self.write_block(num_values, uncompressed_len, &mut data)?;
+            }
+        }
+        Ok(num_bytes)
+    }
+
+    /// Write a block of already compressed data, followed by the sync marker. The data is
+    /// encrypted first if the `Writer` has an encryption key.
+    fn write_block(
+        &mut self,
+        num_values: usize,
+        uncompressed_len: usize,
+        data: &mut Vec<u8>,
+    ) -> AvroResult<usize> {
+        if uncompressed_len > 0 {
+            self.compression_ratio = data.len() as f64 / uncompressed_len as f64;
+        }
+        #[cfg(feature = "encryption")]
+        self.encrypt_block(num_values, data)?;
+        if self.checksum_blocks {
+            self.checksums.push(BlockChecksum {
+                offset: self.position,
+                crc32: block_crc32(num_values, data),
+            });
+        }
 
         let num_bytes = self.append_raw(&num_values.into(), &Schema::Long)?
-            + self.append_raw(&stream_len.into(), &Schema::Long)?
-            + self
-                .writer
-                .write(self.buffer.as_ref())
-                .map_err(Error::WriteBytes)?
+            + self.append_raw(&data.len().into(), &Schema::Long)?
+            + self.writer.write(data).map_err(Error::WriteBytes)?
             + self.append_marker()?;
 
-        self.buffer.clear();
-        self.num_values = 0;
-
+        self.position += num_bytes as u64;
         Ok(num_bytes)
     }
 
+    /// Check that blocks are compressed with the dictionary of the file being appended to.
+    #[cfg(feature = "zstandard")]
+    fn check_zstd_dictionary(&self) -> AvroResult<()> {
+        match (&self.codec_options.zstd_dictionary, self.zstd_dictionary_id) {
+            (Some(dictionary), Some(id)) if dictionary.id() != id => {
+                Err(Error::MissingZstdDictionary(id))
+            }
+            (None, Some(id)) => Err(Error::MissingZstdDictionary(id)),
+            _ => Ok(()),
+        }
+    }
+
+    #[cfg(feature = "encryption")]
+    fn encrypt_block(&self, num_values: usize, data: &mut Vec<u8>) -> AvroResult<()> {
+        match (&self.encryption_key, &self.encryption_key_id) {
+            // the block is authenticated along with its offset, like its checksum
+            (Some(_), _) if !self.position_known => Err(Error::UnknownWriterPosition),
+            (Some(key), _) => key.encrypt_block(&self.marker, self.position, num_values, data),
+            (None, Some(key_id)) => Err(Error::MissingEncryptionKey(key_id.clone())),
+            (None, None) => Ok(()),
+        }
+    }
+
+    /// Supply the `Zstandard` dictionary the blocks of the file being appended to are compressed
+    /// with, looking it up by the id written in its header. It does nothing if the file has no
+    /// dictionary or embeds it.
+    #[cfg(feature = "zstandard")]
+    pub fn with_zstd_dictionaries(
+        mut self,
+        provider: &impl ZstdDictionaryProvider,
+    ) -> AvroResult<Self> {
+        if let (None, Some(id)) = (&self.codec_options.zstd_dictionary, self.zstd_dictionary_id) {
+            self.codec_options.zstd_dictionary = Some(
+                provider
+                    .zstd_dictionary(id)
+                    .ok_or(Error::MissingZstdDictionary(id))?,
+            );
+        }
+        Ok(self)
+    }
+
+    /// Supply the key the blocks of the file being appended to are encrypted with, looking it
+    /// up by the id written in its header. It does nothing if the file is not encrypted.
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption_keys(mut self, provider: &impl KeyProvider) -> AvroResult<Self> {
+        if let Some(key_id) = &self.encryption_key_id {
+            self.encryption_key = Some(
+                provider
+                    .encryption_key(key_id)
+                    .ok_or_else(|| Error::MissingEncryptionKey(key_id.clone()))?,
+            );
+        }
+        Ok(self)
+    }
+
+    /// Set the position in the file the `Writer` starts writing at, i.e. the length of what is
+    /// already written, for the `Writer`s created with the `append_to*` functions. `sync` and the
+    /// block checksums count positions from the start of the file.
+    pub fn at_position(mut self, position: u64) -> Self {
+        self.position = position;
+        self.position_known = true;
+        self
+    }
+
+    /// Record the checksums of the blocks written from now on, as `checksum_blocks` does for the
+    /// `Writer`s built with the builder, e.g. when appending to a file whose blocks have
+    /// checksums. The checksums record the positions of the blocks, so the `Writer`s created with
+    /// the `append_to*` functions must be given theirs with `at_position` first.
+    pub fn with_block_checksums(mut self) -> AvroResult<Self> {
+        if !self.position_known {
+            return Err(Error::UnknownWriterPosition);
+        }
+        self.checksum_blocks = true;
+        Ok(self)
+    }
+
+    /// Get the checksums of the blocks written so far, if the `Writer` has been built with
+    /// `checksum_blocks`. They are meant to be stored next to the file, for instance in a sidecar
+    /// file, to later [`verify`](struct.Reader.html#method.verify) it.
+    pub fn block_checksums(&self) -> BlockChecksums {
+        BlockChecksums {
+            marker: self.marker,
+            blocks: self.checksums.clone(),
+        }
+    }
+
     /// Return what the `Writer` is writing to, consuming the `Writer` itself.
     ///
     /// **NOTE** This function forces the written data to be flushed (an implicit
@@ -327,6 +622,11 @@ impl<'a, W: Write> Writer<'a, W> {
         Ok(self.writer)
     }
 
+    /// Get a mutable reference to what the `Writer` is writing to.
+    pub(crate) fn inner_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
     /// Generate and append synchronization marker to the payload.
     fn append_marker(&mut self) -> AvroResult<usize> {
         // using .writer.write directly to avoid mutable borrow of self
@@ -369,6 +669,32 @@ impl<'a, W: Write> Writer<'a, W> {
         metadata.insert("avro.schema", Value::Bytes(schema_bytes));
         metadata.insert("avro.codec", self.codec.into());
 
+        #[cfg(feature = "zstandard")]
+        if let (Codec::Zstandard, Some(dictionary)) =
+            (self.codec, &self.codec_options.zstd_dictionary)
+        {
+            let id = dictionary.id().to_string().into_bytes();
+            metadata.insert(ZSTD_DICTIONARY_ID_KEY, Value::Bytes(id));
+            if self.codec_options.zstd_embed_dictionary {
+                metadata.insert(
+                    ZSTD_DICTIONARY_KEY,
+                    Value::Bytes(dictionary.bytes().to_vec()),
+                );
+            }
+        }
+
+        #[cfg(feature = "encryption")]
+        if let Some(key) = &self.encryption_key {
+            metadata.insert(
+                ENCRYPTION_KEY,
+                Value::Bytes(AES_256_GCM.as_bytes().to_vec()),
+            );
+            metadata.insert(
+                ENCRYPTION_KEY_ID_KEY,
+                Value::Bytes(key.id().as_bytes().to_vec()),
+            );
+        }
+
         for (k, v) in &self.user_metadata {
             metadata.insert(k.as_str(), v.clone());
         }
@@ -389,6 +715,7 @@ impl<'a, W: Write> Writer<'a, W> {
         if !self.has_header {
             let header = self.header()?;
             let n = self.append_bytes(header.as_ref())?;
+            self.position += n as u64;
             self.has_header = true;
             Ok(n)
         } else {
@@ -397,6 +724,173 @@ impl<'a, W: Write> Writer<'a, W> {
     }
 }
 
+type CompressedBlock = AvroResult<Vec<u8>>;
+
+struct CompressionJob {
+    codec: Codec,
+    options: CodecOptions,
+    data: Vec<u8>,
+    result: mpsc::SyncSender<CompressedBlock>,
+}
+
+struct InFlightBlock {
+    num_values: usize,
+    uncompressed_len: usize,
+    result: mpsc::Receiver<CompressedBlock>,
+}
+
+/// Pool of threads compressing blocks on behalf of a `Writer`.
+///
+/// Blocks are handed out to whichever worker is free, but are returned in the order they have
+/// been submitted so that the container file keeps its original block order.
+struct CompressionPipeline {
+    jobs: Option<mpsc::Sender<CompressionJob>>,
+    workers: Vec<thread::JoinHandle<()>>,
+    in_flight: VecDeque<InFlightBlock>,
+}
+
+impl CompressionPipeline {
+    fn new(num_workers: usize) -> Self {
+        let (jobs, receiver) = mpsc::channel::<CompressionJob>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..num_workers)
+            .map(|_| {
+                let receiver = Arc::clone(&receiver);
+                thread::spawn(move || loop {
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+                    match job {
+                        Ok(CompressionJob {
+                            codec,
+                            options,
+                            mut data,
+                            result,
+                        }) => {
+                            let compressed = codec
+                                .compress_with_options(&mut data, &options)
+                                .map(|_| data);
+                            // the writer may have been dropped in the meantime
+                            let _ = result.send(compressed);
+                        }
+                        Err(_) => return,
+                    }
+                })
+            })
+            .collect();
+
+        CompressionPipeline {
+            jobs: Some(jobs),
+            workers,
+            in_flight: VecDeque::new(),
+        }
+    }
+
+    fn submit(
+        &mut self,
+        codec: Codec,
+        options: CodecOptions,
+        num_values: usize,
+        data: Vec<u8>,
+    ) -> AvroResult<()> {
+        let uncompressed_len = data.len();
+        let (sender, result) = mpsc::sync_channel(1);
+        self.jobs
+            .as_ref()
+            .ok_or(Error::CompressionWorkerTerminated)?
+            .send(CompressionJob {
+                codec,
+                options,
+                data,
+                result: sender,
+            })
+            .map_err(|_| Error::CompressionWorkerTerminated)?;
+        self.in_flight.push_back(InFlightBlock {
+            num_values,
+            uncompressed_len,
+            result,
+        });
+        Ok(())
+    }
+
+    /// Wait for the oldest in-flight block to be compressed, if any. Return its number of values,
+    /// its uncompressed length and its compressed data.
+    fn next_block(&mut self) -> Option<AvroResult<(usize, usize, Vec<u8>)>> {
+        self.in_flight.pop_front().map(|block| {
+            block
+                .result
+                .recv()
+                .map_err(|_| Error::CompressionWorkerTerminated)?
+                .map(|data| (block.num_values, block.uncompressed_len, data))
+        })
+    }
+}
+
+impl Drop for CompressionPipeline {
+    fn drop(&mut self) {
+        // closing the jobs channel makes the workers exit their loop
+        self.jobs.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl<'a, W: Read + Write + Seek> Writer<'a, W> {
+    /// Creates a `Writer` that will append values to an existing container file.
+    ///
+    /// The header of the file is read to recover its `Codec`, sync marker and user metadata, then
+    /// the `Writer` moves to the end of the file. The given `schema` must be the same as the one
+    /// of the file, as defined by their [Parsing Canonical Form].
+    ///
+    /// The `Zstandard` dictionary of a file is restored when it is embedded in the header,
+    /// otherwise it must be supplied with `with_zstd_dictionaries`. Likewise, with the
+    /// `encryption` feature, the key of an encrypted file must be supplied with
+    /// `with_encryption_keys`. Writing blocks fails without them.
+    ///
+    /// [Parsing Canonical Form]:
+    /// https://avro.apache.org/docs/1.8.2/spec.html#Parsing+Canonical+Form+for+Schemas
+    pub fn append_to_file(schema: &'a Schema, mut file: W) -> AvroResult<Self> {
+        file.seek(SeekFrom::Start(0)).map_err(Error::Seek)?;
+        let header = read_header(&mut file)?;
+        if &header.writer_schema != schema {
+            return Err(Error::AppendSchemaMismatch {
+                file_schema: header.writer_schema.canonical_form(),
+                schema: schema.canonical_form(),
+            });
+        }
+        let position = file.seek(SeekFrom::End(0)).map_err(Error::Seek)?;
+
+        let user_metadata = header
+            .user_metadata
+            .into_iter()
+            .map(|(key, value)| (key, Value::Bytes(value)))
+            .collect();
+        let mut w = Self::builder()
+            .schema(schema)
+            .writer(file)
+            .codec(header.codec)
+            .marker(header.marker)
+            .user_metadata(user_metadata)
+            .build();
+        w.has_header = true;
+        w.position = position;
+        #[cfg(feature = "zstandard")]
+        {
+            w.zstd_dictionary_id = header.zstd_dictionary_id;
+            w.codec_options.zstd_embed_dictionary = header.zstd_dictionary.is_some();
+            w.codec_options.zstd_dictionary = header.zstd_dictionary;
+        }
+        #[cfg(feature = "encryption")]
+        {
+            w.encryption_key_id = header.encryption_key_id;
+        }
+        w.resolved_schema = ResolvedSchema::try_from(schema).ok();
+        Ok(w)
+    }
+}
+
 /// Encode a compatible value (implementing the `ToAvro` trait) into Avro format, also performing
 /// schema validation.
 ///
@@ -431,6 +925,14 @@ fn write_avro_datum_schemata<T: Into<Value>>(
     encode_internal(&avro, schema, names, &enclosing_namespace, buffer)
 }
 
+/// The header of the single object encoding: a two bytes marker followed by the 8-byte
+/// little-endian Rabin fingerprint of the schema.
+pub(crate) fn single_object_header(fingerprint: &SchemaFingerprint) -> [u8; 10] {
+    let mut header = [0xC3, 0x01, 0, 0, 0, 0, 0, 0, 0, 0];
+    header[2..].copy_from_slice(&fingerprint.bytes);
+    header
+}
+
 /// Writer that encodes messages according to the single object encoding v1 spec
 /// Uses an API similar to the current File Writer
 /// Writes all object bytes at once, and drains internal buffer
@@ -444,21 +946,16 @@ impl GenericSingleObjectWriter {
         schema: &Schema,
         initial_buffer_cap: usize,
     ) -> AvroResult<GenericSingleObjectWriter> {
-        let fingerprint = schema.fingerprint::<Rabin>();
+        Self::with_fingerprint(schema, &schema.fingerprint::<Rabin>(), initial_buffer_cap)
+    }
+
+    fn with_fingerprint(
+        schema: &Schema,
+        fingerprint: &SchemaFingerprint,
+        initial_buffer_cap: usize,
+    ) -> AvroResult<GenericSingleObjectWriter> {
         let mut buffer = Vec::with_capacity(initial_buffer_cap);
-        let header = [
-            0xC3,
-            0x01,
-            fingerprint.bytes[0],
-            fingerprint.bytes[1],
-            fingerprint.bytes[2],
-            fingerprint.bytes[3],
-            fingerprint.bytes[4],
-            fingerprint.bytes[5],
-            fingerprint.bytes[6],
-            fingerprint.bytes[7],
-        ];
-        buffer.extend_from_slice(&header);
+        buffer.extend_from_slice(&single_object_header(fingerprint));
 
         Ok(GenericSingleObjectWriter {
             buffer,
@@ -498,10 +995,16 @@ impl<T> SpecificSingleObjectWriter<T>
 where
     T: AvroSchema,
 {
+    /// The schema of `T` and its fingerprint come from
+    /// [`AvroSchema::get_schema_with_fingerprint`], which caches them for derived types.
     pub fn with_capacity(buffer_cap: usize) -> AvroResult<SpecificSingleObjectWriter<T>> {
-        let schema = T::get_schema();
+        let schema = T::get_schema_with_fingerprint();
         Ok(SpecificSingleObjectWriter {
-            inner: GenericSingleObjectWriter::new_with_capacity(&schema, buffer_cap)?,
+            inner: GenericSingleObjectWriter::with_fingerprint(
+                schema.schema(),
+                schema.rabin_fingerprint(),
+                buffer_cap,
+            )?,
             _model: PhantomData,
         })
     }
@@ -538,7 +1041,7 @@ where
     }
 }
 
-fn write_value_ref_resolved(
+pub(crate) fn write_value_ref_resolved(
     schema: &Schema,
     resolved_schema: &ResolvedSchema,
     value: &Value,
@@ -556,7 +1059,7 @@ fn write_value_ref_resolved(
     }
 }
 
-fn write_value_ref_owned_resolved(
+pub(crate) fn write_value_ref_owned_resolved(
     resolved_schema: &ResolvedOwnedSchema,
     value: &Value,
     buffer: &mut Vec<u8>,
@@ -1027,6 +1530,37 @@ mod tests {
         check_writer(writer, &schema);
     }
 
+    #[test]
+    fn test_writer_with_codec_options() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let options = CodecOptions::builder().level(0).build();
+        let mut writer = Writer::builder()
+            .schema(&schema)
+            .writer(Vec::new())
+            .codec(Codec::Deflate)
+            .codec_options(options.clone())
+            .build();
+        writer.append(make_record(&schema, 27)).unwrap();
+        let result = writer.into_inner().unwrap();
+
+        let mut data = Vec::new();
+        zig_i64(27, &mut data);
+        zig_i64(3, &mut data);
+        data.extend(b"foo");
+        Codec::Deflate
+            .compress_with_options(&mut data, &options)
+            .unwrap();
+        let last_data_byte = result.len() - 16;
+        assert_eq!(
+            &result[last_data_byte - data.len()..last_data_byte],
+            data.as_slice()
+        );
+
+        // the options do not change the codec name in the header
+        assert!(result.windows(7).any(|w| w == b"deflate"));
+        assert_eq!(crate::Reader::new(&result[..]).unwrap().count(), 1);
+    }
+
     #[test]
     fn test_writer_with_builder() {
         let schema = Schema::parse_str(SCHEMA).unwrap();
@@ -1034,6 +1568,240 @@ mod tests {
         check_writer(writer, &schema);
     }
 
+    #[test]
+    fn test_writer_with_compression_workers() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let marker = generate_sync_marker();
+        let records = (0..1000).map(|i| {
+            let mut record = Record::new(&schema).unwrap();
+            record.put("a", i as i64);
+            record.put("b", format!("foo-{i}"));
+            record
+        });
+
+        let mut sequential = Writer::builder()
+            .schema(&schema)
+            .writer(Vec::new())
+            .codec(Codec::Deflate)
+            .block_size(64)
+            .marker(marker)
+            .build();
+        sequential.extend(records.clone()).unwrap();
+        let expected = sequential.into_inner().unwrap();
+
+        let mut pipelined = Writer::builder()
+            .schema(&schema)
+            .writer(Vec::new())
+            .codec(Codec::Deflate)
+            .block_size(64)
+            .marker(marker)
+            .compression_workers(4)
+            .max_in_flight_blocks(3)
+            .build();
+        let mut num_bytes = 0;
+        for record in records {
+            num_bytes += pipelined.append(record).unwrap();
+            let in_flight = pipelined.pipeline.as_ref().map_or(0, |p| p.in_flight.len());
+            assert!(in_flight <= 3);
+        }
+        num_bytes += pipelined.flush().unwrap();
+        let result = pipelined.into_inner().unwrap();
+
+        assert_eq!(num_bytes, result.len());
+        // the header metadata is not written in a stable order, so only compare the blocks
+        let blocks = |bytes: &[u8]| {
+            let header_len = bytes.windows(16).position(|w| w == marker).unwrap() + 16;
+            bytes[header_len..].to_vec()
+        };
+        assert_eq!(blocks(&result), blocks(&expected));
+        assert_eq!(crate::Reader::new(&result[..]).unwrap().count(), 1000);
+    }
+
+    /// Fails to compress the first block, after having clobbered it.
+    struct FailingOnceCodec(std::sync::atomic::AtomicBool);
+
+    impl crate::codec::CustomCodec for FailingOnceCodec {
+        fn name(&self) -> &'static str {
+            "failing-once"
+        }
+
+        fn compress(&self, stream: &mut Vec<u8>) -> AvroResult<()> {
+            if self.0.swap(false, std::sync::atomic::Ordering::SeqCst) {
+                stream.clear();
+                return Err(Error::BadCodecMetadata);
+            }
+            Ok(())
+        }
+
+        fn decompress(&self, _stream: &mut Vec<u8>) -> AvroResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_writer_retries_failed_compression() -> TestResult<()> {
+        let codec = crate::codec::register_custom_codec(FailingOnceCodec(true.into()))?;
+        let schema = Schema::parse_str(SCHEMA)?;
+        let mut writer = Writer::with_codec(&schema, Vec::new(), codec);
+        writer.append(make_record(&schema, 1))?;
+        writer.append(make_record(&schema, 2))?;
+        assert!(matches!(writer.flush(), Err(Error::CustomCodec { .. })));
+
+        // the values are still buffered
+        writer.flush()?;
+        let result = writer.into_inner()?;
+        let values: Vec<_> = crate::Reader::new(&result[..])?.collect::<Result<_, _>>()?;
+        assert_eq!(values.len(), 2);
+        Ok(())
+    }
+
+    fn count_blocks(bytes: &[u8], marker: [u8; 16]) -> usize {
+        // the header also ends with the marker
+        bytes.windows(16).filter(|w| *w == marker).count() - 1
+    }
+
+    fn make_record(schema: &Schema, a: i64) -> Record<'_> {
+        let mut record = Record::new(schema).unwrap();
+        record.put("a", a);
+        record.put("b", "foo");
+        record
+    }
+
+    #[test]
+    fn test_writer_max_block_values() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let marker = generate_sync_marker();
+        let mut writer = Writer::builder()
+            .schema(&schema)
+            .writer(Vec::new())
+            .marker(marker)
+            .max_block_values(10)
+            .build();
+        writer
+            .extend((0..25).map(|i| make_record(&schema, i)))
+            .unwrap();
+        let result = writer.into_inner().unwrap();
+
+        assert_eq!(count_blocks(&result, marker), 3);
+        assert_eq!(crate::Reader::new(&result[..]).unwrap().count(), 25);
+    }
+
+    #[test]
+    fn test_writer_target_compressed_block_size() {
+        let schema = Schema::parse_str(SCHEMA).unwrap();
+        let marker = generate_sync_marker();
+        let mut writer = Writer::builder()
+            .schema(&schema)
+            .writer(Vec::new())
+            .marker(marker)
+            .target_compressed_block_size(50)
+            .build();
+        // each record takes 5 bytes
+        writer
+            .extend((0..30).map(|i| make_record(&schema, i)))
+            .unwrap();
+        let result = writer.into_inner().unwrap();
+
+        assert_eq!(count_blocks(&result, marker), 3);
+    }
+
+    #[test]
+    fn test_writer_flush_interval() -> TestResult<()> {
+        let schema = Schema::parse_str(SCHEMA)?;
+        let marker = generate_sync_marker();
+        let mut writer = Writer::builder()
+            .schema(&schema)
+            .writer(Vec::new())
+            .marker(marker)
+            .flush_interval(std::time::Duration::from_millis(20))
+            .build();
+
+        assert_eq!(writer.flush_if_expired()?, 0);
+        writer.append(make_record(&schema, 1))?;
+        writer.append(make_record(&schema, 2))?;
+        assert_eq!(writer.flush_if_expired()?, 0);
+        std::thread::sleep(std::time::Duration::from_millis(30));
+        assert!(writer.flush_if_expired()? > 0);
+        assert_eq!(writer.flush_if_expired()?, 0);
+
+        writer.append(make_record(&schema, 3))?;
+        std::thread::sleep(std::time::Duration::from_millis(30));
+        // the expired block is closed along with the value being appended
+        assert!(writer.append(make_record(&schema, 4))? > 0);
+        let result = writer.into_inner()?;
+
+        assert_eq!(count_blocks(&result, marker), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_writer_sync() -> TestResult<()> {
+        let schema = Schema::parse_str(SCHEMA)?;
+        let marker = generate_sync_marker();
+        let mut writer = Writer::builder()
+            .schema(&schema)
+            .writer(Vec::new())
+            .marker(marker)
+            .build();
+
+        let header_end = writer.sync()?;
+        writer.append(make_record(&schema, 1))?;
+        let first_block_end = writer.sync()?;
+        // nothing to flush, the position does not change
+        assert_eq!(writer.sync()?, first_block_end);
+        writer.append(make_record(&schema, 2))?;
+        let result = writer.into_inner()?;
+
+        for position in [header_end, first_block_end] {
+            let position = position as usize;
+            assert_eq!(&result[position - 16..position], &marker);
+        }
+        assert!(header_end < first_block_end);
+        assert!((first_block_end as usize) < result.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_writer_append_to_positions() -> TestResult<()> {
+        let schema = Schema::parse_str(SCHEMA)?;
+        let marker = generate_sync_marker();
+        let mut writer = Writer::builder()
+            .schema(&schema)
+            .writer(Vec::new())
+            .marker(marker)
+            .checksum_blocks(true)
+            .build();
+        writer.append(make_record(&schema, 1))?;
+        writer.flush()?;
+        let mut checksums = writer.block_checksums();
+        let bytes = writer.into_inner()?;
+
+        // without the position, neither sync nor the checksums can be right
+        assert!(matches!(
+            Writer::append_to(&schema, bytes.clone(), marker).with_block_checksums(),
+            Err(Error::UnknownWriterPosition)
+        ));
+        let mut writer = Writer::append_to(&schema, bytes.clone(), marker);
+        assert!(matches!(writer.sync(), Err(Error::UnknownWriterPosition)));
+
+        let mut writer = Writer::append_to(&schema, bytes.clone(), marker)
+            .at_position(bytes.len() as u64)
+            .with_block_checksums()?;
+        assert_eq!(writer.sync()?, bytes.len() as u64);
+        writer.append(make_record(&schema, 2))?;
+        let block_end = writer.sync()?;
+        checksums.blocks.extend(writer.block_checksums().blocks);
+        let result = writer.into_inner()?;
+        assert_eq!(block_end as usize, result.len());
+        assert_eq!(checksums.blocks.len(), 2);
+        assert_eq!(checksums.blocks[1].offset, bytes.len() as u64);
+
+        let report = crate::Reader::new(&result[..])?.verify(&checksums)?;
+        assert!(report.is_ok());
+        assert_eq!(report.blocks, 2);
+        Ok(())
+    }
+
     #[test]
     fn test_logical_writer() {
         const LOGICAL_TYPE_SCHEMA: &str = r#"
@@ -18,7 +18,11 @@
 use apache_avro::{
     read_marker,
     types::{Record, Value},
-    AvroResult, Reader, Schema, Writer,
+    AvroResult, Codec, Error, Reader, Schema, Writer,
+};
+use std::{
+    fs::{self, OpenOptions},
+    io::Cursor,
 };
 
 #[test]
@@ -55,6 +59,204 @@ fn avro_3630_append_to_an_existing_file() {
     }
 }
 
+const SCHEMA: &str = r#"
+    {
+        "type": "record",
+        "name": "append_to_existing_file",
+        "fields": [
+            {"name": "a", "type": "int"}
+        ]
+    }
+"#;
+
+#[test]
+fn append_to_an_existing_file_recovering_its_header() {
+    let schema = Schema::parse_str(SCHEMA).expect("Cannot parse the schema");
+    let path = std::env::temp_dir().join(format!("append_to_file_{}.avro", std::process::id()));
+
+    let mut writer = Writer::with_codec(&schema, Vec::new(), Codec::Deflate);
+    writer
+        .add_user_metadata("key".to_string(), "value")
+        .expect("Cannot add metadata");
+    writer
+        .append(create_datum(&schema, 1))
+        .expect("An error while appending data");
+    let bytes = writer.into_inner().expect("Cannot get the Avro bytes");
+    fs::write(&path, &bytes).expect("Cannot write the file");
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&path)
+        .expect("Cannot open the file");
+    let mut writer = Writer::append_to_file(&schema, file).expect("Cannot append to the file");
+    writer
+        .append(create_datum(&schema, 2))
+        .expect("An error occurred while appending more data");
+    assert_eq!(
+        writer.sync().expect("Cannot sync"),
+        fs::metadata(&path).expect("Cannot stat the file").len()
+    );
+    writer
+        .append(create_datum(&schema, 3))
+        .expect("An error occurred while appending more data");
+    writer.into_inner().expect("Cannot flush the file");
+
+    let new_bytes = fs::read(&path).expect("Cannot read the file");
+    assert_eq!(&new_bytes[..bytes.len()], &bytes[..]);
+    let reader = Reader::new(&*new_bytes).expect("Cannot read the new bytes");
+    assert_eq!(reader.user_metadata()["key"], b"value");
+    let values: Vec<_> = reader.collect();
+    assert_eq!(values.len(), 3);
+    for (i, value) in values.into_iter().enumerate() {
+        check(value, i as i32 + 1);
+    }
+
+    fs::remove_file(&path).expect("Cannot remove the file");
+}
+
+#[test]
+fn append_to_a_file_with_a_different_schema() {
+    let schema = Schema::parse_str(SCHEMA).expect("Cannot parse the schema");
+    let other_schema = Schema::parse_str(
+        r#"{"type": "record", "name": "append_to_existing_file", "fields": [{"name": "a", "type": "long"}]}"#,
+    )
+    .expect("Cannot parse the schema");
+
+    let bytes = get_avro_bytes(&schema);
+    match Writer::append_to_file(&other_schema, Cursor::new(bytes)) {
+        Err(Error::AppendSchemaMismatch { .. }) => {}
+        Err(e) => panic!("Unexpected error: {e:?}"),
+        Ok(_) => panic!("Expected an error appending with a different schema"),
+    }
+}
+
+#[cfg(feature = "encryption")]
+#[test]
+fn append_to_an_encrypted_file() {
+    use apache_avro::EncryptionKey;
+    use std::{collections::HashMap, sync::Arc};
+
+    let schema = Schema::parse_str(SCHEMA).expect("Cannot parse the schema");
+    let key = Arc::new(EncryptionKey::new("key-1", [1; 32]));
+    let mut keys = HashMap::new();
+    keys.insert("key-1".to_string(), key.clone());
+
+    let mut writer = Writer::builder()
+        .schema(&schema)
+        .writer(Vec::new())
+        .encryption_key(key)
+        .build();
+    writer
+        .append(create_datum(&schema, 1))
+        .expect("An error while appending data");
+    let bytes = writer.into_inner().expect("Cannot get the Avro bytes");
+
+    let mut writer = Writer::append_to_file(&schema, Cursor::new(bytes.clone()))
+        .expect("Cannot append to the file");
+    writer
+        .append(create_datum(&schema, 2))
+        .expect("An error occurred while appending more data");
+    match writer.flush() {
+        Err(Error::MissingEncryptionKey(key_id)) => assert_eq!(key_id, "key-1"),
+        other => panic!("Expected a missing key error, got {other:?}"),
+    }
+
+    let mut writer = Writer::append_to_file(&schema, Cursor::new(bytes))
+        .expect("Cannot append to the file")
+        .with_encryption_keys(&keys)
+        .expect("Cannot find the key");
+    writer
+        .append(create_datum(&schema, 2))
+        .expect("An error occurred while appending more data");
+    let new_bytes = writer
+        .into_inner()
+        .expect("Cannot flush the file")
+        .into_inner();
+
+    let reader = Reader::new(&*new_bytes)
+        .expect("Cannot read the new bytes")
+        .with_encryption_keys(&keys);
+    let values: Vec<_> = reader.collect();
+    assert_eq!(values.len(), 2);
+    for (i, value) in values.into_iter().enumerate() {
+        check(value, i as i32 + 1);
+    }
+}
+
+#[cfg(feature = "zstandard")]
+#[test]
+fn append_to_a_file_compressed_with_a_zstd_dictionary() {
+    use apache_avro::{CodecOptions, ZstdDictionary};
+    use std::{collections::HashMap, sync::Arc};
+
+    let schema = Schema::parse_str(SCHEMA).expect("Cannot parse the schema");
+    let samples = (0..1000).map(|i| create_datum(&schema, i % 100).into());
+    let dictionary = Arc::new(
+        ZstdDictionary::train_from_values(&schema, samples, 512)
+            .expect("Cannot train the dictionary"),
+    );
+    let mut dictionaries = HashMap::new();
+    dictionaries.insert(dictionary.id(), dictionary.clone());
+
+    let write = |embed: bool| {
+        let options = CodecOptions::builder()
+            .zstd_dictionary(dictionary.clone())
+            .zstd_embed_dictionary(embed)
+            .build();
+        let mut writer = Writer::builder()
+            .schema(&schema)
+            .writer(Vec::new())
+            .codec(Codec::Zstandard)
+            .codec_options(options)
+            .build();
+        writer
+            .append(create_datum(&schema, 1))
+            .expect("An error while appending data");
+        writer.into_inner().expect("Cannot get the Avro bytes")
+    };
+    let append = |writer: Writer<'_, Cursor<Vec<u8>>>| {
+        let mut writer = writer;
+        writer
+            .append(create_datum(&schema, 2))
+            .expect("An error occurred while appending more data");
+        let new_bytes = writer
+            .into_inner()
+            .expect("Cannot flush the file")
+            .into_inner();
+        let reader = Reader::new(&*new_bytes)
+            .expect("Cannot read the new bytes")
+            .with_zstd_dictionaries(&dictionaries);
+        let values: Vec<_> = reader.collect();
+        assert_eq!(values.len(), 2);
+        for (i, value) in values.into_iter().enumerate() {
+            check(value, i as i32 + 1);
+        }
+    };
+
+    // an embedded dictionary is restored from the header
+    let writer = Writer::append_to_file(&schema, Cursor::new(write(true)))
+        .expect("Cannot append to the file");
+    append(writer);
+
+    let bytes = write(false);
+    let mut writer = Writer::append_to_file(&schema, Cursor::new(bytes.clone()))
+        .expect("Cannot append to the file");
+    writer
+        .append(create_datum(&schema, 2))
+        .expect("An error occurred while appending more data");
+    match writer.flush() {
+        Err(Error::MissingZstdDictionary(id)) => assert_eq!(id, dictionary.id()),
+        other => panic!("Expected a missing dictionary error, got {other:?}"),
+    }
+
+    let writer = Writer::append_to_file(&schema, Cursor::new(bytes))
+        .expect("Cannot append to the file")
+        .with_zstd_dictionaries(&dictionaries)
+        .expect("Cannot find the dictionary");
+    append(writer);
+}
+
 /// Simulates reading from a pre-existing .avro file and returns its bytes
 fn get_avro_bytes(schema: &Schema) -> Vec<u8> {
     let mut writer = Writer::new(schema, Vec::new());
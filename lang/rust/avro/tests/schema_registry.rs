@@ -0,0 +1,201 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+#![cfg(feature = "registry")]
+
+use apache_avro::{
+    types::{Record, Value},
+    Error, GenericConfluentReader, GenericConfluentWriter, MockSchemaRegistry, RegisteredSchema,
+    RestSchemaRegistry, Schema, SchemaRegistry,
+};
+use serde_json::json;
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+const V1: &str = r#"{"type": "record", "name": "Event", "fields": [
+    {"name": "a", "type": "long"}
+]}"#;
+const V2: &str = r#"{"type": "record", "name": "Event", "fields": [
+    {"name": "a", "type": "long"},
+    {"name": "b", "type": "string", "default": "none"}
+]}"#;
+const INCOMPATIBLE: &str = r#"{"type": "record", "name": "Event", "fields": [
+    {"name": "c", "type": "long"}
+]}"#;
+
+/// Serves the REST API of a schema registry backed by a `MockSchemaRegistry` on a local port,
+/// counting the requests it answers.
+fn serve(registry: MockSchemaRegistry) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            counter.fetch_add(1, Ordering::SeqCst);
+
+            let mut parts = request_line.split_whitespace();
+            let method = parts.next().unwrap();
+            let path: Vec<&str> = parts.next().unwrap().split('/').skip(1).collect();
+            let schema = || {
+                let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                Schema::parse_str(body["schema"].as_str().unwrap()).unwrap()
+            };
+            let (status, response) = match (method, path.as_slice()) {
+                ("POST", ["subjects", subject, "versions"]) => {
+                    match registry.register(subject, &schema()) {
+                        Ok(id) => (200, json!({ "id": id })),
+                        Err(_) => (409, json!({ "error_code": 409 })),
+                    }
+                }
+                ("GET", ["schemas", "ids", id]) => {
+                    match registry.schema_by_id(id.parse().unwrap()).unwrap() {
+                        Some(schema) => (
+                            200,
+                            json!({ "schema": serde_json::to_string(&schema).unwrap() }),
+                        ),
+                        None => (404, json!({ "error_code": 40403 })),
+                    }
+                }
+                ("GET", ["subjects", subject, "versions", "latest"]) => {
+                    match registry.latest_version(subject).unwrap() {
+                        Some(latest) => (
+                            200,
+                            json!({
+                                "subject": latest.subject,
+                                "id": latest.id,
+                                "version": latest.version,
+                                "schema": serde_json::to_string(&latest.schema).unwrap(),
+                            }),
+                        ),
+                        None => (404, json!({ "error_code": 40401 })),
+                    }
+                }
+                ("POST", ["compatibility", "subjects", subject, "versions", "latest"]) => {
+                    if registry.latest_version(subject).unwrap().is_none() {
+                        (404, json!({ "error_code": 40401 }))
+                    } else {
+                        let is_compatible = registry.is_compatible(subject, &schema()).unwrap();
+                        (200, json!({ "is_compatible": is_compatible }))
+                    }
+                }
+                _ => (404, json!({ "error_code": 404 })),
+            };
+
+            let response = response.to_string();
+            write!(
+                stream,
+                "HTTP/1.1 {status} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{response}",
+                response.len()
+            )
+            .unwrap();
+        }
+    });
+    (url, requests)
+}
+
+#[test]
+fn rest_schema_registry_against_a_local_registry() {
+    let v1 = Schema::parse_str(V1).unwrap();
+    let v2 = Schema::parse_str(V2).unwrap();
+    let incompatible = Schema::parse_str(INCOMPATIBLE).unwrap();
+    let (url, requests) = serve(MockSchemaRegistry::new());
+    let registry = RestSchemaRegistry::new(url);
+
+    assert_eq!(registry.latest_version("events").unwrap(), None);
+    assert!(registry.is_compatible("events", &v1).unwrap());
+    assert_eq!(registry.register("events", &v1).unwrap(), 1);
+    assert!(registry.is_compatible("events", &v2).unwrap());
+    assert!(!registry.is_compatible("events", &incompatible).unwrap());
+    assert_eq!(registry.register("events", &v2).unwrap(), 2);
+    match registry.register("events", &incompatible) {
+        Err(Error::SchemaRegistryIncompatible(subject)) => assert_eq!(subject, "events"),
+        other => panic!("Expected an incompatible schema error, got {other:?}"),
+    }
+    assert_eq!(
+        registry.latest_version("events").unwrap(),
+        Some(RegisteredSchema {
+            subject: "events".to_string(),
+            id: 2,
+            version: 2,
+            schema: v2.clone(),
+        })
+    );
+
+    // registered schemas and schemas fetched by id are cached
+    let before = requests.load(Ordering::SeqCst);
+    assert_eq!(registry.register("events", &v1).unwrap(), 1);
+    assert_eq!(registry.schema_by_id(2).unwrap(), Some(v2));
+    assert_eq!(requests.load(Ordering::SeqCst), before);
+    assert_eq!(registry.schema_by_id(3).unwrap(), None);
+    assert_eq!(requests.load(Ordering::SeqCst), before + 1);
+}
+
+#[test]
+fn confluent_reader_resolving_ids_through_a_registry() {
+    let v1 = Schema::parse_str(V1).unwrap();
+    let (url, _) = serve(MockSchemaRegistry::new());
+    let registry = RestSchemaRegistry::new(url.clone());
+    let id = registry.register("events", &v1).unwrap();
+
+    let mut record = Record::new(&v1).unwrap();
+    record.put("a", 42_i64);
+    let mut bytes = Vec::new();
+    GenericConfluentWriter::new(id, v1.clone())
+        .unwrap()
+        .write_value(record.into(), &mut bytes)
+        .unwrap();
+
+    let mut reader = GenericConfluentReader::with_schema(
+        RestSchemaRegistry::new(url),
+        Schema::parse_str(V2).unwrap(),
+    );
+    assert_eq!(
+        reader.read_value(&mut &bytes[..]).unwrap(),
+        Value::Record(vec![
+            ("a".to_string(), Value::Long(42)),
+            ("b".to_string(), Value::String("none".to_string())),
+        ])
+    );
+}
@@ -17,11 +17,17 @@
 
 extern crate darling;
 
+mod case;
+
+use case::RenameRule;
 use darling::FromAttributes;
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
 
-use syn::{parse_macro_input, spanned::Spanned, AttrStyle, Attribute, DeriveInput, Type, TypePath};
+use syn::{
+    parse_macro_input, spanned::Spanned, AttrStyle, Attribute, DeriveInput, Lit, Meta, NestedMeta,
+    Type, TypePath,
+};
 
 #[derive(darling::FromAttributes)]
 #[darling(attributes(avro))]
@@ -29,13 +35,54 @@ struct FieldOptions {
     #[darling(default)]
     doc: Option<String>,
     #[darling(default)]
-    default: Option<String>,
+    default: Option<FieldDefault>,
+    #[darling(default)]
+    default_expr: Option<String>,
     #[darling(multiple)]
     alias: Vec<String>,
     #[darling(default)]
     rename: Option<String>,
     #[darling(default)]
     skip: Option<bool>,
+    #[darling(default)]
+    logical_type: Option<String>,
+    #[darling(default)]
+    decimal: Option<DecimalOptions>,
+    #[darling(default)]
+    bytes: Option<bool>,
+}
+
+/// The default value of a field, as given by `#[avro(default)]`:
+/// - `#[avro(default)]` is the `Default` of the type of the field
+/// - `#[avro(default = "...")]` is the JSON of the value
+/// - `#[avro(default = 42)]`, or any other non-string literal, is a value of the type of the field
+///
+/// The defaults of `bytes` and `fixed` fields are strings, so they can only be given as JSON.
+enum FieldDefault {
+    Default,
+    Json(String),
+    Literal(Lit),
+}
+
+impl darling::FromMeta for FieldDefault {
+    fn from_word() -> darling::Result<Self> {
+        Ok(FieldDefault::Default)
+    }
+
+    fn from_value(value: &Lit) -> darling::Result<Self> {
+        match value {
+            Lit::Str(json) => Ok(FieldDefault::Json(json.value())),
+            Lit::Int(_) | Lit::Float(_) | Lit::Bool(_) => Ok(FieldDefault::Literal(value.clone())),
+            _ => Err(darling::Error::unexpected_lit_type(value)),
+        }
+    }
+}
+
+#[derive(darling::FromMeta)]
+struct DecimalOptions {
+    precision: usize,
+    #[darling(default)]
+    scale: usize,
 }
 
 #[derive(darling::FromAttributes)]
@@ -47,6 +94,135 @@ struct NamedTypeOptions {
     doc: Option<String>,
     #[darling(multiple)]
     alias: Vec<String>,
+    #[darling(default)]
+    rename_all: Option<String>,
+    #[darling(default)]
+    name: Option<String>,
+    #[darling(default)]
+    fixed: Option<bool>,
+    #[darling(default)]
+    transparent: Option<bool>,
+    #[darling(default)]
+    schema_file: Option<String>,
+    #[darling(default)]
+    identical: Option<bool>,
+}
+
+#[derive(darling::FromAttributes)]
+#[darling(attributes(avro))]
+struct VariantOptions {
+    #[darling(default)]
+    rename: Option<String>,
+    #[darling(default)]
+    doc: Option<String>,
+    #[darling(multiple)]
+    alias: Vec<String>,
+}
+
+/// The serde attributes which change the shape of what serde serializes, which the schema has to
+/// agree with. `#[avro(...)]` options take precedence over them.
+#[derive(Default)]
+struct SerdeOptions {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    skip: bool,
+    /// Where `skip_serializing_if` is, which the schema cannot agree with.
+    skip_serializing_if: Option<Span>,
+    default: Option<SerdeDefault>,
+    flatten: bool,
+    untagged: bool,
+    tagged: bool,
+    transparent: bool,
+}
+
+/// Where `#[serde(default)]` takes the default value of a field from.
+enum SerdeDefault {
+    Default,
+    Path(syn::ExprPath),
+}
+
+impl SerdeOptions {
+    fn from_attributes(attributes: &[Attribute]) -> Result<Self, Vec<syn::Error>> {
+        let mut options = SerdeOptions::default();
+        for attr in attributes.iter().filter(|attr| attr.path.is_ident("serde")) {
+            // attributes which do not parse are left for serde to report
+            let list = match attr.parse_meta() {
+                Ok(Meta::List(list)) => list,
+                _ => continue,
+            };
+            for nested in list.nested.iter() {
+                let meta = match nested {
+                    NestedMeta::Meta(meta) => meta,
+                    NestedMeta::Lit(_) => continue,
+                };
+                let key = match meta.path().get_ident() {
+                    Some(ident) => ident.to_string(),
+                    None => continue,
+                };
+                match (key.as_str(), meta) {
+                    ("rename", _) => options.rename = serialized_name(meta),
+                    ("rename_all", _) => options.rename_all = serialized_name(meta),
+                    ("skip" | "skip_serializing", Meta::Path(_)) => options.skip = true,
+                    ("skip_serializing_if", Meta::NameValue(_)) => {
+                        options.skip_serializing_if = Some(meta.span())
+                    }
+                    ("default", Meta::Path(_)) => options.default = Some(SerdeDefault::Default),
+                    ("default", Meta::NameValue(nv)) => {
+                        if let Lit::Str(path) = &nv.lit {
+                            let path = path.parse().map_err(|e| vec![e])?;
+                            options.default = Some(SerdeDefault::Path(path));
+                        }
+                    }
+                    ("flatten", Meta::Path(_)) => options.flatten = true,
+                    ("untagged", Meta::Path(_)) => options.untagged = true,
+                    ("transparent", Meta::Path(_)) => options.transparent = true,
+                    ("tag" | "content", Meta::NameValue(_)) => options.tagged = true,
+                    _ => {}
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// The name of `rename = "..."` or of `rename(serialize = "...")`
+fn serialized_name(meta: &Meta) -> Option<String> {
+    match meta {
+        Meta::NameValue(syn::MetaNameValue {
+            lit: Lit::Str(name),
+            ..
+        }) => Some(name.value()),
+        Meta::List(list) => list.nested.iter().find_map(|nested| match nested {
+            NestedMeta::Meta(Meta::NameValue(nv)) if nv.path.is_ident("serialize") => {
+                serialized_name(&Meta::NameValue(nv.clone()))
+            }
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+/// Parses the `rename_all` rule of a type, from its avro or serde attributes
+fn get_rename_rule(
+    avro_rule: Option<String>,
+    serde_rule: Option<String>,
+    error_span: Span,
+) -> Result<Option<RenameRule>, Vec<syn::Error>> {
+    match avro_rule.or(serde_rule) {
+        Some(rule) if rule == "kebab-case" || rule == "SCREAMING-KEBAB-CASE" => {
+            Err(vec![syn::Error::new(
+                error_span,
+                format!("AvroSchema: the rename_all rule {rule:?} is not supported, as Avro names cannot contain '-'"),
+            )])
+        }
+        Some(rule) => RenameRule::from_str(&rule).map(Some).ok_or_else(|| {
+            vec![syn::Error::new(
+                error_span,
+                format!("Unknown rename_all rule: {rule:?}"),
+            )]
+        }),
+        None => Ok(None),
+    }
 }
 
 #[proc_macro_derive(AvroSchema, attributes(avro))]
@@ -59,132 +235,327 @@ pub fn proc_macro_derive_avro_schema(input: proc_macro::TokenStream) -> proc_mac
 }
 
 fn derive_avro_schema(input: &mut DeriveInput) -> Result<TokenStream, Vec<syn::Error>> {
-    let named_type_options =
+    let mut named_type_options =
         NamedTypeOptions::from_attributes(&input.attrs[..]).map_err(darling_to_syn)?;
-    let full_schema_name = vec![named_type_options.namespace, Some(input.ident.to_string())]
-        .into_iter()
-        .flatten()
-        .collect::<Vec<String>>()
-        .join(".");
+    let schema_file_test = get_schema_file_test(
+        input,
+        named_type_options.schema_file.take(),
+        named_type_options.identical.unwrap_or(false),
+    )?;
+    let schema_component_impl = derive_schema_component(input, named_type_options)?;
+    Ok(quote! {
+        #schema_component_impl
+        #schema_file_test
+    })
+}
+
+fn derive_schema_component(
+    input: &mut DeriveInput,
+    named_type_options: NamedTypeOptions,
+) -> Result<TokenStream, Vec<syn::Error>> {
+    let serde_options = SerdeOptions::from_attributes(&input.attrs[..])?;
+    let rename_rule = get_rename_rule(
+        named_type_options.rename_all,
+        serde_options.rename_all,
+        input.ident.span(),
+    )?;
+    let (schema_name_def, full_schema_name) =
+        get_schema_name(input, named_type_options.namespace, named_type_options.name)?;
     let schema_def = match &input.data {
-        syn::Data::Struct(s) => get_data_struct_schema_def(
+        // serde serializes unit structs as unit
+        syn::Data::Struct(s) if s.fields == syn::Fields::Unit => {
+            return Ok(schema_component_impl(
+                input,
+                quote! { apache_avro::schema::Schema::Null },
+            ));
+        }
+        // and newtype structs as their inner value, unless they are fixed
+        syn::Data::Struct(s)
+            if named_type_options.transparent.unwrap_or(
+                serde_options.transparent
+                    || (matches!(&s.fields, syn::Fields::Unnamed(f) if f.unnamed.len() == 1)
+                        && !named_type_options.fixed.unwrap_or(false)),
+            ) =>
+        {
+            // transparent newtypes are not named types, they are their inner type
+            let inner_schema_expr =
+                type_to_schema_expr(newtype_inner_type(s, input.ident.span())?)?;
+            return Ok(schema_component_impl(input, inner_schema_expr));
+        }
+        syn::Data::Struct(s) if named_type_options.fixed.unwrap_or(false) => get_fixed_schema_def(
             &full_schema_name,
             named_type_options
                 .doc
                 .or_else(|| extract_outer_doc(&input.attrs)),
             named_type_options.alias,
-            s,
-            input.ident.span(),
+            newtype_inner_type(s, input.ident.span())?,
         )?,
-        syn::Data::Enum(e) => get_data_enum_schema_def(
+        syn::Data::Struct(s) => get_data_struct_schema_def(
             &full_schema_name,
             named_type_options
                 .doc
                 .or_else(|| extract_outer_doc(&input.attrs)),
             named_type_options.alias,
-            e,
-            input.ident.span(),
+            rename_rule,
+            serde_options.default.is_some(),
+            s,
         )?,
+        syn::Data::Enum(e) if e.variants.iter().all(|v| syn::Fields::Unit == v.fields) => {
+            get_data_enum_schema_def(
+                &full_schema_name,
+                named_type_options
+                    .doc
+                    .or_else(|| extract_outer_doc(&input.attrs)),
+                named_type_options.alias,
+                rename_rule,
+                e,
+            )?
+        }
+        syn::Data::Enum(e) => {
+            // internally and adjacently tagged enums serialize their variants into records of
+            // their own, which unions cannot describe
+            if serde_options.tagged {
+                return Err(vec![syn::Error::new(
+                    input.ident.span(),
+                    "AvroSchema derive only works for externally tagged or untagged enums with data",
+                )]);
+            }
+            // unions are not named types, only the records of their variants get registered
+            let union_def = get_data_enum_union_schema_def(serde_options.untagged, rename_rule, e)?;
+            return Ok(schema_component_impl(
+                input,
+                quote! {
+                    #schema_name_def
+                    let enum_name = apache_avro::schema::Name::new(#full_schema_name).expect(&format!("Unable to parse schema name {}", #full_schema_name)[..]).fully_qualified_name(enclosing_namespace);
+                    let enclosing_namespace = &enum_name.namespace;
+                    #union_def
+                },
+            ));
+        }
         _ => {
             return Err(vec![syn::Error::new(
                 input.ident.span(),
-                "AvroSchema derive only works for structs and simple enums ",
+                "AvroSchema derive only works for structs and enums",
             )])
         }
     };
+    Ok(schema_component_impl(
+        input,
+        quote! {
+            #schema_name_def
+            let name =  apache_avro::schema::Name::new(#full_schema_name).expect(&format!("Unable to parse schema name {}", #full_schema_name)[..]).fully_qualified_name(enclosing_namespace);
+            let enclosing_namespace = &name.namespace;
+            if named_schemas.contains_key(&name) {
+                apache_avro::schema::Schema::Ref{name: name.clone()}
+            } else {
+                named_schemas.insert(name.clone(), apache_avro::schema::Schema::Ref{name: name.clone()});
+                #schema_def
+            }
+        },
+    ))
+}
+
+/// Returns the test checking the schema of the type against the `.avsc` file given by
+/// `#[avro(schema_file = "...")]`, relative to the directory of the manifest of the crate: they
+/// must be mutually compatible, or have the same Parsing Canonical Form with `#[avro(identical)]`.
+/// The schema of the type is only known once compiled, but the file must be valid JSON already.
+/// The test is not run for the types defined in functions, as the test harness cannot name it.
+fn get_schema_file_test(
+    input: &DeriveInput,
+    schema_file: Option<String>,
+    identical: bool,
+) -> Result<TokenStream, Vec<syn::Error>> {
+    let span = input.ident.span();
+    let schema_file = match schema_file {
+        Some(schema_file) => schema_file,
+        None if identical => {
+            return Err(vec![syn::Error::new(
+                span,
+                "AvroSchema: `identical` requires a `schema_file`",
+            )])
+        }
+        None => return Ok(quote! {}),
+    };
+    if !input.generics.params.is_empty() {
+        return Err(vec![syn::Error::new(
+            span,
+            "AvroSchema: `schema_file` is not supported on generic types",
+        )]);
+    }
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let path = std::path::Path::new(&manifest_dir).join(&schema_file);
+    let contents = std::fs::read_to_string(&path).map_err(|e| {
+        vec![syn::Error::new(
+            span,
+            format!("AvroSchema: cannot read {}: {e}", path.display()),
+        )]
+    })?;
+    if let Err(e) = serde_json::from_str::<serde_json::Value>(&contents) {
+        return Err(vec![syn::Error::new(
+            span,
+            format!("AvroSchema: {schema_file} is not valid JSON: {e}"),
+        )]);
+    }
+
     let ident = &input.ident;
-    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let test = quote::format_ident!(
+        "avro_schema_file_{}",
+        RenameRule::Snake.apply_to_variant(&ident.to_string().replace("r#", ""))
+    );
+    // the test is next to the type rather than in a module, which could not name the types
+    // defined in functions. The file is included so that the crate gets rebuilt when it changes
     Ok(quote! {
+        #[cfg(test)]
+        #[test]
+        fn #test() {
+            apache_avro::schema::derive::verify_schema_file::<#ident>(
+                #schema_file,
+                include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", #schema_file)),
+                #identical,
+            );
+        }
+    })
+}
+
+/// Returns the definition of the name of the schema, if it is computed, and the expression of the
+/// name. The names of generic types are either mangled with the names of the schemas of their
+/// parameters, e.g. `Envelope_User` for `Envelope<User>`, or given by a `#[avro(name = "...")]`
+/// template referring to them, e.g. `"{T}Envelope"`.
+fn get_schema_name(
+    input: &DeriveInput,
+    namespace: Option<String>,
+    template: Option<String>,
+) -> Result<(TokenStream, TokenStream), Vec<syn::Error>> {
+    let prefix = namespace
+        .map(|namespace| namespace + ".")
+        .unwrap_or_default();
+    let params: Vec<(&syn::Ident, TokenStream)> = input
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            syn::GenericParam::Type(param) => {
+                let ident = &param.ident;
+                Some((
+                    ident,
+                    quote! { apache_avro::schema::derive::schema_type_name::<#ident>(named_schemas, enclosing_namespace) },
+                ))
+            }
+            syn::GenericParam::Const(param) => {
+                let ident = &param.ident;
+                Some((ident, quote! { #ident }))
+            }
+            syn::GenericParam::Lifetime(_) => None,
+        })
+        .collect();
+    let (format, args) = match template {
+        Some(template) => {
+            let mut args = vec![];
+            for placeholder in template_placeholders(&template, input.ident.span())? {
+                match params.iter().find(|(ident, _)| *ident == &placeholder) {
+                    Some((ident, expr)) => args.push(quote! { #ident = #expr }),
+                    None => {
+                        return Err(vec![syn::Error::new(
+                            input.ident.span(),
+                            format!(
+                            "AvroSchema: {placeholder:?} is not a generic parameter of the type"
+                        ),
+                        )])
+                    }
+                }
+            }
+            (prefix + &template, args)
+        }
+        None if params.is_empty() => {
+            let full_schema_name = prefix + &input.ident.to_string();
+            return Ok((quote! {}, quote! { #full_schema_name }));
+        }
+        None => {
+            let format = prefix + &input.ident.to_string() + &"_{}".repeat(params.len());
+            (format, params.into_iter().map(|(_, expr)| expr).collect())
+        }
+    };
+    Ok((
+        quote! { let schema_name = format!(#format, #(#args),*); },
+        quote! { schema_name.as_str() },
+    ))
+}
+
+/// The distinct `{placeholders}` of a name template
+fn template_placeholders(template: &str, span: Span) -> Result<Vec<String>, Vec<syn::Error>> {
+    let mut placeholders: Vec<String> = vec![];
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        let end = match rest[start..].find('}') {
+            Some(end) if rest[start..].starts_with('{') => start + end,
+            _ => {
+                return Err(vec![syn::Error::new(
+                    span,
+                    format!("AvroSchema: invalid name template {template:?}"),
+                )])
+            }
+        };
+        let placeholder = &rest[start + 1..end];
+        if !placeholders.iter().any(|p| p == placeholder) {
+            placeholders.push(placeholder.to_owned());
+        }
+        rest = &rest[end + 1..];
+    }
+    Ok(placeholders)
+}
+
+/// Implements `AvroSchemaComponent` for the derived type, with the given body for `get_schema_in_ctxt`
+fn schema_component_impl(input: &DeriveInput, get_schema_body: TokenStream) -> TokenStream {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // a static in a generic function would be shared by all the instantiations of the type
+    let get_static_schema = if input.generics.params.is_empty() {
+        quote! {
+            fn get_static_schema() -> &'static apache_avro::schema::CachedSchema {
+                apache_avro::schema::derive::lazy_static! {
+                    static ref SCHEMA: apache_avro::schema::CachedSchema = apache_avro::schema::CachedSchema::new(<#ident as apache_avro::schema::AvroSchema>::get_schema());
+                }
+                &SCHEMA
+            }
+
+            fn get_fingerprinted_schema() -> std::borrow::Cow<'static, apache_avro::schema::CachedSchema> {
+                std::borrow::Cow::Borrowed(<Self as apache_avro::schema::derive::AvroSchemaComponent>::get_static_schema())
+            }
+        }
+    } else {
+        quote! {}
+    };
+    quote! {
         impl #impl_generics apache_avro::schema::derive::AvroSchemaComponent for #ident #ty_generics #where_clause {
             fn get_schema_in_ctxt(named_schemas: &mut std::collections::HashMap<apache_avro::schema::Name, apache_avro::schema::Schema>, enclosing_namespace: &Option<String>) -> apache_avro::schema::Schema {
-                let name =  apache_avro::schema::Name::new(#full_schema_name).expect(&format!("Unable to parse schema name {}", #full_schema_name)[..]).fully_qualified_name(enclosing_namespace);
-                let enclosing_namespace = &name.namespace;
-                if named_schemas.contains_key(&name) {
-                    apache_avro::schema::Schema::Ref{name: name.clone()}
-                } else {
-                    named_schemas.insert(name.clone(), apache_avro::schema::Schema::Ref{name: name.clone()});
-                    #schema_def
-                }
+                #get_schema_body
             }
+
+            #get_static_schema
         }
-    })
+    }
 }
 
 fn get_data_struct_schema_def(
-    full_schema_name: &str,
+    full_schema_name: &TokenStream,
     record_doc: Option<String>,
     aliases: Vec<String>,
+    rename_rule: Option<RenameRule>,
+    container_default: bool,
     s: &syn::DataStruct,
-    error_span: Span,
 ) -> Result<TokenStream, Vec<syn::Error>> {
-    let mut record_field_exprs = vec![];
-    match s.fields {
-        syn::Fields::Named(ref a) => {
-            let mut index: usize = 0;
-            for field in a.named.iter() {
-                let mut name = field.ident.as_ref().unwrap().to_string(); // we know everything has a name
-                if let Some(raw_name) = name.strip_prefix("r#") {
-                    name = raw_name.to_string();
-                }
-                let field_attrs =
-                    FieldOptions::from_attributes(&field.attrs[..]).map_err(darling_to_syn)?;
-                let doc = preserve_optional(field_attrs.doc);
-                if let Some(rename) = field_attrs.rename {
-                    name = rename
-                }
-                if let Some(true) = field_attrs.skip {
-                    continue;
-                }
-                let default_value = match field_attrs.default {
-                    Some(default_value) => {
-                        let _: serde_json::Value = serde_json::from_str(&default_value[..])
-                            .map_err(|e| {
-                                vec![syn::Error::new(
-                                    field.ident.span(),
-                                    format!("Invalid avro default json: \n{e}"),
-                                )]
-                            })?;
-                        quote! {
-                            Some(serde_json::from_str(#default_value).expect(format!("Invalid JSON: {:?}", #default_value).as_str()))
-                        }
-                    }
-                    None => quote! { None },
-                };
-                let aliases = preserve_vec(field_attrs.alias);
-                let schema_expr = type_to_schema_expr(&field.ty)?;
-                let position = index;
-                record_field_exprs.push(quote! {
-                    apache_avro::schema::RecordField {
-                            name: #name.to_string(),
-                            doc: #doc,
-                            default: #default_value,
-                            aliases: #aliases,
-                            schema: #schema_expr,
-                            order: apache_avro::schema::RecordFieldOrder::Ascending,
-                            position: #position,
-                            custom_attributes: Default::default(),
-                        }
-                });
-                index += 1;
-            }
-        }
-        syn::Fields::Unnamed(_) => {
-            return Err(vec![syn::Error::new(
-                error_span,
-                "AvroSchema derive does not work for tuple structs",
-            )])
-        }
-        syn::Fields::Unit => {
-            return Err(vec![syn::Error::new(
-                error_span,
-                "AvroSchema derive does not work for unit structs",
-            )])
-        }
-    }
+    // the fields of tuple structs are positional, serde does not rename them
+    let rename_rule = match s.fields {
+        syn::Fields::Named(_) => rename_rule,
+        _ => None,
+    };
+    let record_field_exprs =
+        get_record_field_exprs(s.fields.iter(), rename_rule, container_default)?;
     let record_doc = preserve_optional(record_doc);
     let record_aliases = preserve_vec(aliases);
+    let schema_fields_def = get_record_fields_def(record_field_exprs);
     Ok(quote! {
-        let schema_fields = vec![#(#record_field_exprs),*];
+        #schema_fields_def
         let name = apache_avro::schema::Name::new(#full_schema_name).expect(&format!("Unable to parse struct name for schema {}", #full_schema_name)[..]);
         let lookup: std::collections::BTreeMap<String, usize> = schema_fields
             .iter()
@@ -201,36 +572,463 @@ fn get_data_struct_schema_def(
     })
 }
 
+/// The type of the only field of a newtype struct
+fn newtype_inner_type(s: &syn::DataStruct, error_span: Span) -> Result<&Type, Vec<syn::Error>> {
+    match &s.fields {
+        syn::Fields::Named(f) if f.named.len() == 1 => Ok(&f.named[0].ty),
+        syn::Fields::Unnamed(f) if f.unnamed.len() == 1 => Ok(&f.unnamed[0].ty),
+        _ => Err(vec![syn::Error::new(
+            error_span,
+            "AvroSchema derive only works for fixed or transparent structs with a single field",
+        )]),
+    }
+}
+
+/// Generates the `fixed` schema of a newtype of a byte array, like `struct Sha256([u8; 32])`
+fn get_fixed_schema_def(
+    full_schema_name: &TokenStream,
+    doc: Option<String>,
+    aliases: Vec<String>,
+    ty: &Type,
+) -> Result<TokenStream, Vec<syn::Error>> {
+    let size = match ty {
+        Type::Array(ta) if is_u8(&ta.elem) => &ta.len,
+        _ => {
+            return Err(vec![syn::Error::new_spanned(
+                ty,
+                "AvroSchema: fixed structs must wrap a byte array `[u8; N]`",
+            )])
+        }
+    };
+    let doc = preserve_optional(doc);
+    let aliases = preserve_vec(aliases);
+    Ok(quote! {
+        apache_avro::schema::Schema::Fixed {
+            name: apache_avro::schema::Name::new(#full_schema_name).expect(&format!("Unable to parse fixed name for schema {}", #full_schema_name)[..]),
+            aliases: #aliases,
+            doc: #doc,
+            size: #size,
+            attributes: Default::default(),
+        }
+    })
+}
+
+fn is_u8(ty: &Type) -> bool {
+    matches!(ty, Type::Path(p) if p.path.is_ident("u8"))
+}
+
+/// A field of a record, or a type whose fields are flattened into the record
+enum RecordFieldExpr {
+    Field(TokenStream),
+    Flattened(TokenStream),
+}
+
+/// Generates the `RecordField` expressions of the named fields of a struct or of an enum variant
+fn get_record_field_exprs<'a>(
+    fields: impl IntoIterator<Item = &'a syn::Field>,
+    rename_rule: Option<RenameRule>,
+    container_default: bool,
+) -> Result<Vec<RecordFieldExpr>, Vec<syn::Error>> {
+    let mut record_field_exprs = vec![];
+    let mut index: usize = 0;
+    for (field_index, field) in fields.into_iter().enumerate() {
+        // the fields of tuples are named after their position among the serialized ones
+        let (member, mut name) = match &field.ident {
+            Some(ident) => (quote! { #ident }, ident.to_string()),
+            None => {
+                let member = syn::Index::from(field_index);
+                (quote! { #member }, format!("field_{index}"))
+            }
+        };
+        if let Some(raw_name) = name.strip_prefix("r#") {
+            name = raw_name.to_string();
+        }
+        let field_attrs =
+            FieldOptions::from_attributes(&field.attrs[..]).map_err(darling_to_syn)?;
+        let serde_attrs = SerdeOptions::from_attributes(&field.attrs[..])?;
+        let doc = preserve_optional(field_attrs.doc);
+        if let Some(rename) = field_attrs.rename.or(serde_attrs.rename) {
+            name = rename
+        } else if let Some(rule) = rename_rule {
+            name = rule.apply_to_field(&name)
+        }
+        if let (Some(span), false) = (serde_attrs.skip_serializing_if, serde_attrs.skip) {
+            // the fields of records are written one after the other, without their names, so
+            // leaving one out depending on its value cannot be expressed by a schema
+            return Err(vec![syn::Error::new(
+                span,
+                "AvroSchema: `skip_serializing_if` is not supported, as Avro records always have all their fields: serialize the field, e.g. `None` as null, or skip it with `#[serde(skip)]`",
+            )]);
+        }
+        if field_attrs.skip.unwrap_or(serde_attrs.skip) {
+            continue;
+        }
+        if serde_attrs.flatten {
+            let ty = &field.ty;
+            record_field_exprs.push(RecordFieldExpr::Flattened(quote! {
+                apache_avro::schema::derive::flattened_fields::<#ty>(named_schemas, enclosing_namespace)
+            }));
+            continue;
+        }
+        let ty = &field.ty;
+        // typed default values are serialized the way the field is, but the defaults of `bytes`
+        // and `fixed` fields are strings rather than the arrays their values serialize to
+        let bytes_or_fixed = option_inner_type(ty).is_none()
+            && (field_attrs.bytes.unwrap_or(false)
+                || field_attrs.decimal.is_some()
+                || matches!(ty, Type::Array(ta) if is_u8(&ta.elem))
+                || matches!(ty, Type::Path(p) if p.path.segments.last().map_or(false, |s| s.ident == "ByteBuf")));
+        let typed_default_value = |typed_default: TokenStream| {
+            quote! {
+                Some(serde_json::Value::try_from(apache_avro::to_value(#typed_default).expect("Unable to serialize the default value")).expect("Unable to convert the default value to JSON"))
+            }
+        };
+        let explicit_typed_default_value = |typed_default: TokenStream| {
+            if bytes_or_fixed {
+                return Err(vec![syn::Error::new(
+                    field.ident.span(),
+                    "AvroSchema: the default of a bytes or fixed field must be a JSON string, e.g. `#[avro(default = \"\\\"\\\\u0000\\\"\")]`",
+                )]);
+            }
+            Ok(typed_default_value(typed_default))
+        };
+        let implicit_typed_default_value = |typed_default: TokenStream| {
+            if bytes_or_fixed {
+                quote! { None }
+            } else {
+                typed_default_value(typed_default)
+            }
+        };
+        let default_value = match (field_attrs.default, field_attrs.default_expr) {
+            (Some(_), Some(_)) => {
+                return Err(vec![syn::Error::new(
+                    field.ident.span(),
+                    "AvroSchema: a field cannot have both a default and a default_expr",
+                )])
+            }
+            (Some(FieldDefault::Json(default_value)), None) => {
+                let _: serde_json::Value =
+                    serde_json::from_str(&default_value[..]).map_err(|e| {
+                        vec![syn::Error::new(
+                            field.ident.span(),
+                            format!("Invalid avro default json: \n{e}"),
+                        )]
+                    })?;
+                quote! {
+                    Some(serde_json::from_str(#default_value).expect(format!("Invalid JSON: {:?}", #default_value).as_str()))
+                }
+            }
+            (Some(FieldDefault::Default), None) => {
+                explicit_typed_default_value(quote! { <#ty as Default>::default() })?
+            }
+            (Some(FieldDefault::Literal(lit)), None) => {
+                explicit_typed_default_value(quote! { { let value: #ty = #lit; value } })?
+            }
+            (None, Some(default_expr)) => {
+                let expr = syn::parse_str::<syn::Expr>(&default_expr).map_err(|e| {
+                    vec![syn::Error::new(
+                        field.ident.span(),
+                        format!("Invalid avro default_expr: {e}"),
+                    )]
+                })?;
+                explicit_typed_default_value(quote! { { let value: #ty = #expr; value } })?
+            }
+            // the default value serde deserializes missing fields to, if any
+            (None, None) => match serde_attrs.default {
+                Some(SerdeDefault::Default) => {
+                    implicit_typed_default_value(quote! { <#ty as Default>::default() })
+                }
+                Some(SerdeDefault::Path(path)) => implicit_typed_default_value(quote! { #path() }),
+                None if container_default => {
+                    implicit_typed_default_value(quote! { <Self as Default>::default().#member })
+                }
+                None => quote! { None },
+            },
+        };
+        let aliases = preserve_vec(field_attrs.alias);
+        let schema_expr = field_schema_expr(
+            &field.ty,
+            field_attrs.logical_type,
+            field_attrs.decimal,
+            field_attrs.bytes.unwrap_or(false),
+        )?;
+        let position = index;
+        record_field_exprs.push(RecordFieldExpr::Field(quote! {
+            apache_avro::schema::RecordField {
+                    name: #name.to_string(),
+                    doc: #doc,
+                    default: #default_value,
+                    aliases: #aliases,
+                    schema: #schema_expr,
+                    order: apache_avro::schema::RecordFieldOrder::Ascending,
+                    position: #position,
+                    custom_attributes: Default::default(),
+                }
+        }));
+        index += 1;
+    }
+    Ok(record_field_exprs)
+}
+
+/// Generates the `schema_fields` of a record, renumbering them when some are flattened
+fn get_record_fields_def(record_field_exprs: Vec<RecordFieldExpr>) -> TokenStream {
+    if record_field_exprs.is_empty() {
+        return quote! {
+            let schema_fields: Vec<apache_avro::schema::RecordField> = Vec::new();
+        };
+    }
+    if record_field_exprs
+        .iter()
+        .all(|expr| matches!(expr, RecordFieldExpr::Field(_)))
+    {
+        let record_field_exprs = record_field_exprs.into_iter().map(|expr| match expr {
+            RecordFieldExpr::Field(field) | RecordFieldExpr::Flattened(field) => field,
+        });
+        return quote! {
+            let schema_fields = vec![#(#record_field_exprs),*];
+            apache_avro::schema::derive::validate_defaults(&schema_fields, named_schemas, enclosing_namespace);
+        };
+    }
+    let statements = record_field_exprs.into_iter().map(|expr| match expr {
+        RecordFieldExpr::Field(field) => quote! { schema_fields.push(#field); },
+        RecordFieldExpr::Flattened(fields) => quote! { schema_fields.extend(#fields); },
+    });
+    quote! {
+        let mut schema_fields = Vec::new();
+        #(#statements)*
+        for (position, field) in schema_fields.iter_mut().enumerate() {
+            field.position = position;
+        }
+        apache_avro::schema::derive::validate_defaults(&schema_fields, named_schemas, enclosing_namespace);
+    }
+}
+
 fn get_data_enum_schema_def(
-    full_schema_name: &str,
+    full_schema_name: &TokenStream,
     doc: Option<String>,
     aliases: Vec<String>,
+    rename_rule: Option<RenameRule>,
     e: &syn::DataEnum,
-    error_span: Span,
 ) -> Result<TokenStream, Vec<syn::Error>> {
     let doc = preserve_optional(doc);
     let enum_aliases = preserve_vec(aliases);
-    if e.variants.iter().all(|v| syn::Fields::Unit == v.fields) {
-        let symbols: Vec<String> = e
-            .variants
-            .iter()
-            .map(|variant| variant.ident.to_string())
-            .collect();
-        Ok(quote! {
-            apache_avro::schema::Schema::Enum {
-                name: apache_avro::schema::Name::new(#full_schema_name).expect(&format!("Unable to parse enum name for schema {}", #full_schema_name)[..]),
-                aliases: #enum_aliases,
-                doc: #doc,
-                symbols: vec![#(#symbols.to_owned()),*],
-                attributes: Default::default(),
+    let mut symbols = vec![];
+    for variant in e.variants.iter() {
+        let serde_attrs = SerdeOptions::from_attributes(&variant.attrs[..])?;
+        symbols.push(variant_name(
+            &variant.ident,
+            serde_attrs.rename,
+            rename_rule,
+        ));
+    }
+    Ok(quote! {
+        apache_avro::schema::Schema::Enum {
+            name: apache_avro::schema::Name::new(#full_schema_name).expect(&format!("Unable to parse enum name for schema {}", #full_schema_name)[..]),
+            aliases: #enum_aliases,
+            doc: #doc,
+            symbols: vec![#(#symbols.to_owned()),*],
+            attributes: Default::default(),
+        }
+    })
+}
+
+/// The name of a variant, as serialized by serde
+fn variant_name(
+    ident: &syn::Ident,
+    rename: Option<String>,
+    rename_rule: Option<RenameRule>,
+) -> String {
+    match (rename, rename_rule) {
+        (Some(rename), _) => rename,
+        (None, Some(rule)) => rule.apply_to_variant(&ident.to_string()),
+        (None, None) => ident.to_string(),
+    }
+}
+
+/// Enums with data are unions of their variants, in order, so that the index of a variant is its
+/// position in the union:
+/// - newtype variants are their inner type
+/// - struct variants are records of their fields
+/// - tuple variants are records of their fields, named `field_0`, `field_1`...
+/// - unit variants are empty records
+///
+/// The records are named after the enum and the variants, e.g. `EventDeleted`, in the namespace
+/// of the enum, unless renamed with `#[avro(rename = "...")]`. Untagged enums are serialized
+/// without their variant, so their unit variant is `null` instead and tuple variants with several
+/// fields are not supported.
+fn get_data_enum_union_schema_def(
+    untagged: bool,
+    rename_rule: Option<RenameRule>,
+    e: &syn::DataEnum,
+) -> Result<TokenStream, Vec<syn::Error>> {
+    let mut variant_exprs = vec![];
+    let mut null_variant = false;
+    // the unnamed types of the newtype variants, which a union can hold only once each
+    let mut unnamed_types: Vec<String> = vec![];
+    for variant in e.variants.iter() {
+        let variant_attrs =
+            VariantOptions::from_attributes(&variant.attrs[..]).map_err(darling_to_syn)?;
+        let serde_attrs = SerdeOptions::from_attributes(&variant.attrs[..])?;
+        let name = match variant_attrs.rename {
+            Some(rename) => quote! { #rename.to_owned() },
+            None => {
+                let variant_name = variant_name(&variant.ident, serde_attrs.rename, rename_rule);
+                quote! { format!("{}{}", enum_name.name, #variant_name) }
             }
-        })
-    } else {
-        Err(vec![syn::Error::new(
-            error_span,
-            "AvroSchema derive does not work for enums with non unit structs",
-        )])
+        };
+        let doc = variant_attrs
+            .doc
+            .or_else(|| extract_outer_doc(&variant.attrs));
+        let variant_expr = match &variant.fields {
+            syn::Fields::Unnamed(f) if f.unnamed.len() == 1 => {
+                let ty = &f.unnamed[0].ty;
+                match union_branch_type(ty) {
+                    Some(UnionBranchType::Union) => {
+                        return Err(vec![syn::Error::new_spanned(
+                            ty,
+                            "AvroSchema: the variants of enums with data cannot hold an Option, as unions cannot contain unions",
+                        )])
+                    }
+                    Some(UnionBranchType::Unnamed(kind)) if unnamed_types.contains(&kind) => {
+                        return Err(vec![syn::Error::new_spanned(
+                            ty,
+                            format!("AvroSchema: several variants of the enum are `{kind}`, which a union can only hold once"),
+                        )])
+                    }
+                    Some(UnionBranchType::Unnamed(kind)) => unnamed_types.push(kind),
+                    None => {}
+                }
+                type_to_schema_expr(ty)?
+            }
+            syn::Fields::Named(f) => {
+                let field_rename_rule =
+                    get_rename_rule(None, serde_attrs.rename_all, variant.ident.span())?;
+                let field_exprs = get_record_field_exprs(&f.named, field_rename_rule, false)?;
+                get_variant_record_schema_def(&name, doc, variant_attrs.alias, field_exprs)
+            }
+            syn::Fields::Unnamed(f) if !untagged => {
+                let field_exprs = get_record_field_exprs(&f.unnamed, None, false)?;
+                get_variant_record_schema_def(&name, doc, variant_attrs.alias, field_exprs)
+            }
+            syn::Fields::Unnamed(_) => {
+                return Err(vec![syn::Error::new(
+                    variant.ident.span(),
+                    "AvroSchema derive does not work for tuple variants of untagged enums",
+                )])
+            }
+            syn::Fields::Unit if untagged => {
+                if null_variant {
+                    return Err(vec![syn::Error::new(
+                        variant.ident.span(),
+                        "AvroSchema derive does not work for untagged enums with several unit variants",
+                    )]);
+                }
+                null_variant = true;
+                quote! {apache_avro::schema::Schema::Null}
+            }
+            syn::Fields::Unit => {
+                get_variant_record_schema_def(&name, doc, variant_attrs.alias, vec![])
+            }
+        };
+        variant_exprs.push(variant_expr);
     }
+    Ok(quote! {
+        apache_avro::schema::Schema::Union(
+            apache_avro::schema::UnionSchema::new(vec![#(#variant_exprs),*])
+                .expect("Unable to build the union of the variants of the enum"),
+        )
+    })
+}
+
+/// Generates the record of a variant, registered in the named schemas like any record
+/// The record of a variant is registered once built, so that another record of the same name,
+/// e.g. of a variant renamed like one of another enum, is detected as such rather than mistaken
+/// for it.
+fn get_variant_record_schema_def(
+    name: &TokenStream,
+    doc: Option<String>,
+    aliases: Vec<String>,
+    record_field_exprs: Vec<RecordFieldExpr>,
+) -> TokenStream {
+    let doc = preserve_optional(doc);
+    let aliases = preserve_vec(aliases);
+    let schema_fields_def = get_record_fields_def(record_field_exprs);
+    quote! {
+        {
+            let name = #name;
+            let name = apache_avro::schema::Name::new(&name).expect(&format!("Unable to parse variant name for schema {}", name)[..]);
+            let fully_qualified_name = name.fully_qualified_name(enclosing_namespace);
+            match named_schemas.get(&fully_qualified_name).cloned() {
+                // the record is being built, i.e. the variant is recursive
+                Some(apache_avro::schema::Schema::Ref { .. }) => apache_avro::schema::Schema::Ref{name: fully_qualified_name},
+                Some(registered) => {
+                    #schema_fields_def
+                    apache_avro::schema::derive::check_variant_record(&fully_qualified_name, &registered, &schema_fields);
+                    apache_avro::schema::Schema::Ref{name: fully_qualified_name}
+                }
+                None => {
+                    named_schemas.insert(fully_qualified_name.clone(), apache_avro::schema::Schema::Ref{name: fully_qualified_name.clone()});
+                    #schema_fields_def
+                    let lookup: std::collections::BTreeMap<String, usize> = schema_fields
+                        .iter()
+                        .map(|field| (field.name.to_owned(), field.position))
+                        .collect();
+                    let schema = apache_avro::schema::Schema::Record {
+                        name,
+                        aliases: #aliases,
+                        doc: #doc,
+                        fields: schema_fields,
+                        lookup,
+                        attributes: Default::default(),
+                    };
+                    named_schemas.insert(fully_qualified_name, schema.clone());
+                    schema
+                }
+            }
+        }
+    }
+}
+
+/// What a union needs to know of the schema of a type, as far as it can be told from its name.
+enum UnionBranchType {
+    /// The type is a union itself.
+    Union,
+    /// The type has an unnamed schema of the given kind, e.g. `long`.
+    Unnamed(String),
+}
+
+/// Returns what is known of the schema of the type of a newtype variant, for the union of the
+/// variants to be checked while deriving, or `None` for named or unknown types.
+fn union_branch_type(ty: &Type) -> Option<UnionBranchType> {
+    let segment = match ty {
+        Type::Path(p) => p.path.segments.last()?,
+        Type::Reference(r) => return union_branch_type(&r.elem),
+        _ => return None,
+    };
+    let kind = match &segment.ident.to_string()[..] {
+        "Option" => return Some(UnionBranchType::Union),
+        // smart pointers have the schema of what they point to
+        "Box" | "Rc" | "Arc" => match &segment.arguments {
+            syn::PathArguments::AngleBracketed(args) => match args.args.first()? {
+                syn::GenericArgument::Type(ty) => return union_branch_type(ty),
+                _ => return None,
+            },
+            _ => return None,
+        },
+        "bool" => "boolean",
+        "i8" | "i16" | "i32" | "u8" | "u16" => "int",
+        "u32" | "i64" => "long",
+        "f32" => "float",
+        "f64" => "double",
+        "String" | "str" | "IpAddr" | "Ipv4Addr" | "Ipv6Addr" | "Url" => "string",
+        "ByteBuf" => "bytes",
+        "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => "array",
+        "HashMap" | "BTreeMap" | "IndexMap" => "map",
+        "Uuid" => "uuid",
+        "Duration" => "duration",
+        _ => return None,
+    };
+    Some(UnionBranchType::Unnamed(kind.to_owned()))
 }
 
 /// Takes in the Tokens of a type and returns the tokens of an expression with return type `Schema`
@@ -265,6 +1063,12 @@ fn type_to_schema_expr(ty: &Type) -> Result<TokenStream, Vec<syn::Error>> {
         };
         Ok(schema)
     } else if let Type::Array(ta) = ty {
+        if is_u8(&ta.elem) {
+            // byte arrays are fixed
+            return Ok(
+                quote! {<#ta as apache_avro::schema::derive::AvroSchemaComponent>::get_schema_in_ctxt(named_schemas, enclosing_namespace)},
+            );
+        }
         let inner_schema_expr = type_to_schema_expr(&ta.elem)?;
         Ok(quote! {apache_avro::schema::Schema::Array(Box::new(#inner_schema_expr))})
     } else if let Type::Reference(tr) = ty {
@@ -277,6 +1081,222 @@ fn type_to_schema_expr(ty: &Type) -> Result<TokenStream, Vec<syn::Error>> {
     }
 }
 
+/// Takes in the Tokens of the type of a field and returns the tokens of an expression with return
+/// type `Schema`, annotated with the logical type of the field if any. Byte vectors are `bytes`
+/// instead of arrays with `#[avro(bytes)]`.
+fn field_schema_expr(
+    ty: &Type,
+    logical_type: Option<String>,
+    decimal: Option<DecimalOptions>,
+    bytes: bool,
+) -> Result<TokenStream, Vec<syn::Error>> {
+    if logical_type.is_none() && decimal.is_none() && !bytes {
+        return type_to_schema_expr(ty);
+    }
+    if let Some(inner_ty) = option_inner_type(ty) {
+        let inner_schema_expr = logical_type_schema_expr(inner_ty, logical_type, decimal, bytes)?;
+        return Ok(quote! {
+            apache_avro::schema::Schema::Union(
+                apache_avro::schema::UnionSchema::new(vec![apache_avro::schema::Schema::Null, #inner_schema_expr])
+                    .expect("Unable to build the union of an optional logical type"),
+            )
+        });
+    }
+    logical_type_schema_expr(ty, logical_type, decimal, bytes)
+}
+
+/// Returns the schema of a logical type, checking that the Rust type serializes to the type it
+/// annotates
+fn logical_type_schema_expr(
+    ty: &Type,
+    logical_type: Option<String>,
+    decimal: Option<DecimalOptions>,
+    bytes: bool,
+) -> Result<TokenStream, Vec<syn::Error>> {
+    let type_error = |logical_type: &str| {
+        Err(vec![syn::Error::new_spanned(
+            ty,
+            format!("AvroSchema: the {logical_type} logical type does not apply to this type"),
+        )])
+    };
+    if bytes {
+        if logical_type.is_some() || decimal.is_some() {
+            return Err(vec![syn::Error::new_spanned(
+                ty,
+                "AvroSchema: a bytes field cannot have a logical_type nor a decimal",
+            )]);
+        }
+        if !is_byte_vec(ty) {
+            return Err(vec![syn::Error::new_spanned(
+                ty,
+                "AvroSchema: only byte vectors can be bytes",
+            )]);
+        }
+        return Ok(quote! {apache_avro::schema::Schema::Bytes});
+    }
+    let logical_type = match (logical_type, decimal) {
+        (Some(_), Some(_)) => {
+            return Err(vec![syn::Error::new_spanned(
+                ty,
+                "AvroSchema: a field cannot have both a logical_type and a decimal",
+            )])
+        }
+        (None, Some(decimal)) => return decimal_schema_expr(ty, decimal),
+        (Some(logical_type), None) => logical_type,
+        (None, None) => return type_to_schema_expr(ty),
+    };
+    let type_name = match ty {
+        Type::Path(p) => p.path.segments.last().unwrap().ident.to_string(),
+        Type::Reference(tr) => {
+            return logical_type_schema_expr(&tr.elem, Some(logical_type), None, false)
+        }
+        _ => String::new(),
+    };
+    let schema = match logical_type.as_str() {
+        "date" | "time-millis" => {
+            if !matches!(&type_name[..], "i8" | "i16" | "i32" | "u8" | "u16") {
+                return type_error(&logical_type);
+            }
+            if logical_type == "date" {
+                quote! {apache_avro::schema::Schema::Date}
+            } else {
+                quote! {apache_avro::schema::Schema::TimeMillis}
+            }
+        }
+        "time-micros" | "timestamp-millis" | "timestamp-micros" => {
+            // `SystemTime`s are timestamps when serialized with `serde_timestamp_micros`
+            let system_time = type_name == "SystemTime" && logical_type == "timestamp-micros";
+            if !matches!(&type_name[..], "i64" | "u32") && !system_time {
+                return type_error(&logical_type);
+            }
+            match logical_type.as_str() {
+                "time-micros" => quote! {apache_avro::schema::Schema::TimeMicros},
+                "timestamp-millis" => quote! {apache_avro::schema::Schema::TimestampMillis},
+                _ => quote! {apache_avro::schema::Schema::TimestampMicros},
+            }
+        }
+        "uuid" => {
+            if !matches!(&type_name[..], "String" | "str" | "Uuid") {
+                return type_error(&logical_type);
+            }
+            quote! {apache_avro::schema::Schema::Uuid}
+        }
+        "decimal" => {
+            return Err(vec![syn::Error::new_spanned(
+                ty,
+                "AvroSchema: decimals are declared with `decimal(precision = .., scale = ..)`",
+            )])
+        }
+        _ => {
+            return Err(vec![syn::Error::new_spanned(
+                ty,
+                format!("AvroSchema: unsupported logical type {logical_type:?}, expected one of date, time-millis, time-micros, timestamp-millis, timestamp-micros or uuid"),
+            )])
+        }
+    };
+    Ok(schema)
+}
+
+/// Returns the schema of a decimal, which annotates byte vectors as `bytes` and byte arrays as
+/// `fixed`
+fn decimal_schema_expr(ty: &Type, decimal: DecimalOptions) -> Result<TokenStream, Vec<syn::Error>> {
+    let DecimalOptions { precision, scale } = decimal;
+    if precision == 0 {
+        return Err(vec![syn::Error::new_spanned(
+            ty,
+            "AvroSchema: the precision of a decimal must be greater than 0",
+        )]);
+    }
+    if scale > precision {
+        return Err(vec![syn::Error::new_spanned(
+            ty,
+            format!("AvroSchema: the scale of a decimal ({scale}) must not be greater than its precision ({precision})"),
+        )]);
+    }
+    let inner = match ty {
+        Type::Path(_) if is_byte_vec(ty) => quote! {apache_avro::schema::Schema::Bytes},
+        Type::Array(ta) if is_u8(&ta.elem) => {
+            let size = &ta.len;
+            if let syn::Expr::Lit(syn::ExprLit {
+                lit: Lit::Int(size),
+                ..
+            }) = size
+            {
+                let size: usize = size.base10_parse().map_err(|e| vec![e])?;
+                // the largest unscaled value a signed number of `size` bytes can hold
+                let max_precision = ((8 * size) as f64 - 1.0) * 2f64.log10();
+                if size == 0 || precision > max_precision.floor() as usize {
+                    return Err(vec![syn::Error::new_spanned(
+                        ty,
+                        format!("AvroSchema: {size} bytes cannot hold a decimal of precision {precision}"),
+                    )]);
+                }
+            }
+            // the fixed is named after the decimal, for it not to be mistaken for a plain one
+            quote! {
+                {
+                    let name = apache_avro::schema::Name::new(&format!("decimal_{}_{}_{}", #precision, #scale, #size))
+                        .expect("Unable to parse decimal name");
+                    let fully_qualified_name = name.fully_qualified_name(enclosing_namespace);
+                    if named_schemas.contains_key(&fully_qualified_name) {
+                        apache_avro::schema::Schema::Ref{name: fully_qualified_name}
+                    } else {
+                        named_schemas.insert(fully_qualified_name.clone(), apache_avro::schema::Schema::Ref{name: fully_qualified_name});
+                        apache_avro::schema::Schema::Fixed {
+                            name,
+                            aliases: None,
+                            doc: None,
+                            size: #size,
+                            attributes: Default::default(),
+                        }
+                    }
+                }
+            }
+        }
+        _ => {
+            return Err(vec![syn::Error::new_spanned(
+                ty,
+                "AvroSchema: decimals must be byte vectors `Vec<u8>` or byte arrays `[u8; N]`",
+            )])
+        }
+    };
+    Ok(quote! {
+        apache_avro::schema::Schema::Decimal {
+            precision: #precision,
+            scale: #scale,
+            inner: Box::new(#inner),
+        }
+    })
+}
+
+/// The `T` of `Option<T>`
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    generic_argument(ty, "Option")
+}
+
+fn is_byte_vec(ty: &Type) -> bool {
+    matches!(generic_argument(ty, "Vec"), Some(elem) if is_u8(elem))
+}
+
+/// The only generic argument of a type with the given name, e.g. the `T` of `Option<T>`
+fn generic_argument<'a>(ty: &'a Type, name: &str) -> Option<&'a Type> {
+    let segment = match ty {
+        Type::Path(p) => p.path.segments.last()?,
+        _ => return None,
+    };
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args)
+            if segment.ident == name && args.args.len() == 1 =>
+        {
+            match &args.args[0] {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
 /// Generates the schema def expression for fully qualified type paths using the associated function
 /// - `A -> <A as apache_avro::schema::derive::AvroSchemaComponent>::get_schema_in_ctxt()`
 /// - `A<T> -> <A<T> as apache_avro::schema::derive::AvroSchemaComponent>::get_schema_in_ctxt()`
@@ -359,14 +1379,14 @@ mod tests {
     }
 
     #[test]
-    fn tuple_struct_unsupported() {
+    fn tuple_struct_supported() {
         let test_tuple_struct = quote! {
             struct B (i32, String);
         };
 
         match syn::parse2::<DeriveInput>(test_tuple_struct) {
             Ok(mut input) => {
-                assert!(derive_avro_schema(&mut input).is_err())
+                assert!(derive_avro_schema(&mut input).is_ok())
             }
             Err(error) => panic!(
                 "Failed to parse as derive input when it should be able to. Error: {error:?}"
@@ -375,14 +1395,14 @@ mod tests {
     }
 
     #[test]
-    fn unit_struct_unsupported() {
-        let test_tuple_struct = quote! {
+    fn unit_struct_supported() {
+        let test_unit_struct = quote! {
             struct AbsoluteUnit;
         };
 
-        match syn::parse2::<DeriveInput>(test_tuple_struct) {
+        match syn::parse2::<DeriveInput>(test_unit_struct) {
             Ok(mut input) => {
-                assert!(derive_avro_schema(&mut input).is_err())
+                assert!(derive_avro_schema(&mut input).is_ok())
             }
             Err(error) => panic!(
                 "Failed to parse as derive input when it should be able to. Error: {error:?}"
@@ -390,6 +1410,75 @@ mod tests {
         };
     }
 
+    #[test]
+    fn generic_struct_name_template() {
+        let valid = quote! {
+            #[avro(name = "{T}Envelope")]
+            struct Envelope<T> {
+                payload: T
+            }
+        };
+        let mut input = syn::parse2::<DeriveInput>(valid).unwrap();
+        assert!(derive_avro_schema(&mut input).is_ok());
+
+        let unknown_parameter = quote! {
+            #[avro(name = "{U}Envelope")]
+            struct Envelope<T> {
+                payload: T
+            }
+        };
+        let mut input = syn::parse2::<DeriveInput>(unknown_parameter).unwrap();
+        assert!(derive_avro_schema(&mut input).is_err());
+    }
+
+    #[test]
+    fn schema_file_validation() {
+        for (attributes, error) in [
+            (quote! { #[avro(identical)] }, "requires a `schema_file`"),
+            (
+                quote! { #[avro(schema_file = "tests/schema_file/missing.avsc")] },
+                "cannot read",
+            ),
+            (
+                quote! { #[avro(schema_file = "tests/derive.rs")] },
+                "is not valid JSON",
+            ),
+        ] {
+            let test_struct = quote! {
+                #attributes
+                struct A {
+                    a: i32
+                }
+            };
+            let mut input = syn::parse2::<DeriveInput>(test_struct).unwrap();
+            let errors = derive_avro_schema(&mut input).unwrap_err();
+            assert!(errors[0].to_string().contains(error), "{}", errors[0]);
+        }
+
+        let generic_struct = quote! {
+            #[avro(schema_file = "tests/schema_file/identical.avsc")]
+            struct A<T> {
+                a: T
+            }
+        };
+        let mut input = syn::parse2::<DeriveInput>(generic_struct).unwrap();
+        assert!(derive_avro_schema(&mut input).is_err());
+
+        let test_struct = quote! {
+            #[avro(schema_file = "tests/schema_file/identical.avsc")]
+            struct TestBasic {
+                a: i32,
+                b: String
+            }
+        };
+        let mut input = syn::parse2::<DeriveInput>(test_struct).unwrap();
+        let tokens = derive_avro_schema(&mut input).unwrap().to_string();
+        assert!(
+            tokens.contains("fn avro_schema_file_test_basic ()"),
+            "{tokens}"
+        );
+    }
+
     #[test]
     fn struct_with_optional() {
         let struct_with_optional = quote! {
@@ -438,6 +1527,140 @@ mod tests {
             }
         };
         match syn::parse2::<DeriveInput>(non_basic_enum) {
+            Ok(mut input) => {
+                let schema_token_stream = derive_avro_schema(&mut input).unwrap().to_string();
+                assert!(schema_token_stream.contains("UnionSchema :: new"));
+                assert!(!schema_token_stream.contains("Schema :: Enum"));
+            }
+            Err(error) => panic!(
+                "Failed to parse as derive input when it should be able to. Error: {error:?}"
+            ),
+        };
+    }
+
+    #[test]
+    fn test_data_enum_representations() {
+        let untagged_tuple_enum = quote! {
+            #[serde(untagged)]
+            enum Untagged {
+                A(i32, String),
+                B
+            }
+        };
+        let untagged_unit_enum = quote! {
+            #[serde(untagged)]
+            enum Untagged {
+                A(i32),
+                B,
+                C
+            }
+        };
+        let internally_tagged_enum = quote! {
+            #[serde(tag = "type")]
+            enum Internal {
+                A { a: i32 },
+                B
+            }
+        };
+        for input in [
+            untagged_tuple_enum,
+            untagged_unit_enum,
+            internally_tagged_enum,
+        ] {
+            match syn::parse2::<DeriveInput>(input) {
+                Ok(mut input) => {
+                    assert!(derive_avro_schema(&mut input).is_err())
+                }
+                Err(error) => panic!(
+                    "Failed to parse as derive input when it should be able to. Error: {error:?}"
+                ),
+            };
+        }
+    }
+
+    #[test]
+    fn test_fixed_and_transparent_newtypes() {
+        let fixed_of_ints = quote! {
+            #[avro(fixed)]
+            struct A([i32; 4]);
+        };
+        let transparent_pair = quote! {
+            #[avro(transparent)]
+            struct B(i32, String);
+        };
+        for input in [fixed_of_ints, transparent_pair] {
+            match syn::parse2::<DeriveInput>(input) {
+                Ok(mut input) => {
+                    assert!(derive_avro_schema(&mut input).is_err())
+                }
+                Err(error) => panic!(
+                    "Failed to parse as derive input when it should be able to. Error: {error:?}"
+                ),
+            };
+        }
+
+        let fixed = quote! {
+            #[avro(fixed)]
+            struct C([u8; 16]);
+        };
+        match syn::parse2::<DeriveInput>(fixed) {
+            Ok(mut input) => {
+                let schema_token_stream = derive_avro_schema(&mut input).unwrap().to_string();
+                assert!(schema_token_stream.contains("Schema :: Fixed"));
+                assert!(schema_token_stream.contains("size : 16"));
+            }
+            Err(error) => panic!(
+                "Failed to parse as derive input when it should be able to. Error: {error:?}"
+            ),
+        };
+    }
+
+    #[test]
+    fn test_logical_type_validation() {
+        let invalid_fields = [
+            quote! { #[avro(logical_type = "timestamp-millis")] a: i32 },
+            quote! { #[avro(logical_type = "date")] a: String },
+            quote! { #[avro(logical_type = "uuid")] a: Vec<u8> },
+            quote! { #[avro(logical_type = "local-timestamp-nanos")] a: i64 },
+            quote! { #[avro(logical_type = "decimal")] a: Vec<u8> },
+            quote! { #[avro(decimal(precision = 0))] a: Vec<u8> },
+            quote! { #[avro(decimal(precision = 2, scale = 3))] a: Vec<u8> },
+            quote! { #[avro(decimal(precision = 5))] a: [u8; 2] },
+            quote! { #[avro(decimal(precision = 4))] a: String },
+            quote! { #[avro(logical_type = "date", decimal(precision = 4))] a: i32 },
+            quote! { #[avro(bytes)] a: String },
+            quote! { #[avro(bytes)] a: [u8; 4] },
+            quote! { #[avro(bytes, logical_type = "uuid")] a: Vec<u8> },
+        ];
+        for field in invalid_fields {
+            let test_struct = quote! {
+                struct A {
+                    #field
+                }
+            };
+            match syn::parse2::<DeriveInput>(test_struct) {
+                Ok(mut input) => {
+                    assert!(
+                        derive_avro_schema(&mut input).is_err(),
+                        "{field} should be invalid"
+                    )
+                }
+                Err(error) => panic!(
+                    "Failed to parse as derive input when it should be able to. Error: {error:?}"
+                ),
+            };
+        }
+    }
+
+    #[test]
+    fn test_unknown_rename_all_rule() {
+        let test_struct = quote! {
+            #[serde(rename_all = "Title Case")]
+            struct A {
+                a_field: i32
+            }
+        };
+        match syn::parse2::<DeriveInput>(test_struct) {
             Ok(mut input) => {
                 assert!(derive_avro_schema(&mut input).is_err())
             }
@@ -447,6 +1670,178 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_skip_serializing_if() {
+        let test_struct = quote! {
+            struct A {
+                #[serde(skip_serializing_if = "Option::is_none")]
+                a: Option<i32>,
+            }
+        };
+        match syn::parse2::<DeriveInput>(test_struct) {
+            Ok(mut input) => {
+                let errors = derive_avro_schema(&mut input).unwrap_err();
+                assert!(errors[0]
+                    .to_string()
+                    .contains("`skip_serializing_if` is not supported"));
+            }
+            Err(error) => panic!(
+                "Failed to parse as derive input when it should be able to. Error: {error:?}"
+            ),
+        };
+
+        // the field is never serialized anyway
+        let test_struct = quote! {
+            struct A {
+                #[serde(skip, skip_serializing_if = "Option::is_none")]
+                a: Option<i32>,
+            }
+        };
+        match syn::parse2::<DeriveInput>(test_struct) {
+            Ok(mut input) => assert!(derive_avro_schema(&mut input).is_ok()),
+            Err(error) => panic!(
+                "Failed to parse as derive input when it should be able to. Error: {error:?}"
+            ),
+        };
+    }
+
+    #[test]
+    fn test_kebab_case_rename_all_rules() {
+        for test_struct in [
+            quote! {
+                #[serde(rename_all = "kebab-case")]
+                struct A {
+                    a_field: i32
+                }
+            },
+            quote! {
+                #[avro(rename_all = "SCREAMING-KEBAB-CASE")]
+                enum B {
+                    AVariant
+                }
+            },
+        ] {
+            match syn::parse2::<DeriveInput>(test_struct) {
+                Ok(mut input) => {
+                    let errors = derive_avro_schema(&mut input).unwrap_err();
+                    assert!(errors[0]
+                        .to_string()
+                        .contains("Avro names cannot contain '-'"));
+                }
+                Err(error) => panic!(
+                    "Failed to parse as derive input when it should be able to. Error: {error:?}"
+                ),
+            };
+        }
+    }
+
+    #[test]
+    fn test_typed_default_values() {
+        let test_struct = quote! {
+            struct A {
+                #[avro(default = 42)]
+                a: i64,
+                #[avro(default)]
+                b: Vec<String>,
+                #[avro(default_expr = "String::from(\"b\")")]
+                c: String,
+            }
+        };
+        match syn::parse2::<DeriveInput>(test_struct) {
+            Ok(mut input) => {
+                let schema_token_stream = derive_avro_schema(&mut input).unwrap().to_string();
+                assert!(schema_token_stream.contains("{ let value : i64 = 42 ; value }"));
+                assert!(schema_token_stream.contains("< Vec < String > as Default > :: default ()"));
+                assert!(schema_token_stream
+                    .contains(r#"{ let value : String = String :: from ("b") ; value }"#));
+                assert!(schema_token_stream.contains(
+                    "apache_avro :: schema :: derive :: validate_defaults (& schema_fields"
+                ));
+            }
+            Err(error) => panic!(
+                "Failed to parse as derive input when it should be able to. Error: {error:?}"
+            ),
+        };
+
+        for field in [
+            quote! { #[avro(default = 1, default_expr = "2")] a: i32 },
+            quote! { #[avro(default = 'c')] a: char },
+            quote! { #[avro(default_expr = "1 +")] a: i32 },
+            quote! { #[avro(default)] a: [u8; 4] },
+            quote! { #[avro(default_expr = "vec![1]", bytes)] a: Vec<u8> },
+        ] {
+            let test_struct = quote! {
+                struct A {
+                    #field
+                }
+            };
+            match syn::parse2::<DeriveInput>(test_struct) {
+                Ok(mut input) => {
+                    assert!(derive_avro_schema(&mut input).is_err())
+                }
+                Err(error) => panic!(
+                    "Failed to parse as derive input when it should be able to. Error: {error:?}"
+                ),
+            };
+        }
+    }
+
+    #[test]
+    fn test_data_enum_variant_attributes() {
+        let data_enum = quote! {
+            enum Event {
+                #[avro(rename = "Removal", alias = "Removed", doc = "a doc")]
+                Deleted { id: i64 },
+                Noop
+            }
+        };
+        match syn::parse2::<DeriveInput>(data_enum) {
+            Ok(mut input) => {
+                let schema_token_stream = derive_avro_schema(&mut input).unwrap().to_string();
+                assert!(schema_token_stream.contains(r#""Removal" . to_owned ()"#));
+                assert!(schema_token_stream.contains(r#"Some (vec ! ["Removed" . into ()])"#));
+                assert!(schema_token_stream.contains(r#"Some ("a doc" . into ())"#));
+                assert!(schema_token_stream
+                    .contains(r#"format ! ("{}{}" , enum_name . name , "Noop")"#));
+            }
+            Err(error) => panic!(
+                "Failed to parse as derive input when it should be able to. Error: {error:?}"
+            ),
+        };
+    }
+
+    #[test]
+    fn test_data_enum_invalid_unions() {
+        for data_enum in [
+            quote! {
+                enum Duplicate {
+                    X(i64),
+                    Y(i64),
+                }
+            },
+            quote! {
+                enum SameKind {
+                    X(u32),
+                    Y(Box<i64>),
+                }
+            },
+            quote! {
+                enum Nested {
+                    X(Option<String>),
+                }
+            },
+        ] {
+            match syn::parse2::<DeriveInput>(data_enum) {
+                Ok(mut input) => {
+                    assert!(derive_avro_schema(&mut input).is_err())
+                }
+                Err(error) => panic!(
+                    "Failed to parse as derive input when it should be able to. Error: {error:?}"
+                ),
+            };
+        }
+    }
+
     #[test]
     fn test_namespace() {
         let test_struct = quote! {
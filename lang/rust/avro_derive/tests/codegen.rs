@@ -0,0 +1,122 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! The code generated from the schemas of `tests/codegen`, which `generated.rs` is the output of.
+
+use apache_avro::{codegen, from_value, schema::AvroSchema, to_value, Reader, Schema, Writer};
+use std::collections::HashMap;
+
+mod generated {
+    include!("codegen/generated.rs");
+}
+
+use generated::*;
+
+const SCHEMAS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/codegen");
+
+fn parse_schemas() -> Vec<Schema> {
+    let kind = std::fs::read_to_string(format!("{SCHEMAS_DIR}/kind.avsc")).unwrap();
+    let user = std::fs::read_to_string(format!("{SCHEMAS_DIR}/user.avsc")).unwrap();
+    Schema::parse_list(&[&kind, &user]).unwrap()
+}
+
+#[test]
+fn test_generated_code_is_up_to_date() {
+    let output = std::env::temp_dir().join(format!("codegen-{}.rs", std::process::id()));
+    codegen::compile_schemas(&[SCHEMAS_DIR], &output).unwrap();
+    let code = std::fs::read_to_string(&output).unwrap();
+    std::fs::remove_file(&output).unwrap();
+    assert_eq!(code, include_str!("codegen/generated.rs"));
+    assert_eq!(codegen::generate(&parse_schemas()).unwrap(), code);
+}
+
+#[test]
+fn test_generated_schemas() {
+    let schemas = parse_schemas();
+    assert_eq!(
+        Kind::get_schema().canonical_form(),
+        schemas[0].canonical_form()
+    );
+    // the parsed schema refers to `Kind` by name, and leaves the names it defines unqualified
+    let user = r#"{"name":"com.example.User","type":"record","fields":[
+        {"name":"userId","type":"long"},
+        {"name":"type","type":{"name":"com.example.Kind","type":"enum","symbols":["ADMIN","GUEST"]}},
+        {"name":"name","type":"string"},
+        {"name":"avatar","type":["null","bytes"]},
+        {"name":"score","type":"double"},
+        {"name":"tags","type":{"type":"map","values":"string"}},
+        {"name":"friends","type":{"type":"array","items":"com.example.User"}},
+        {"name":"best_friend","type":["null","com.example.User"]},
+        {"name":"fingerprint","type":{"name":"com.example.Md5","type":"fixed","size":16}},
+        {"name":"created","type":{"type":"long","logicalType":"timestamp-millis"}},
+        {"name":"birthday","type":["null",{"type":"int","logicalType":"date"}]},
+        {"name":"status","type":["string","null"]},
+        {"name":"contact","type":[
+            {"name":"com.example.Email","type":"record","fields":[{"name":"address","type":"string"}]},
+            {"name":"com.example.Phone","type":"record","fields":[{"name":"number","type":"long"}]},
+            "string"
+        ]}
+    ]}"#;
+    assert_eq!(
+        User::get_schema().canonical_form(),
+        user.split_whitespace().collect::<String>()
+    );
+}
+
+#[test]
+fn test_generated_types_roundtrip() {
+    let friend = User {
+        user_id: 2,
+        r#type: Kind::Guest,
+        name: "friend".to_owned(),
+        avatar: None,
+        score: 0.5,
+        tags: HashMap::new(),
+        friends: vec![],
+        best_friend: None,
+        fingerprint: Md5([2; 16]),
+        created: 1_000,
+        birthday: None,
+        status: UserStatus::Null,
+        contact: UserContact::String("nowhere".to_owned()),
+    };
+    let user = User {
+        user_id: 1,
+        r#type: Kind::Admin,
+        name: "user".to_owned(),
+        avatar: Some(vec![1, 2, 3]),
+        score: 1.5,
+        tags: HashMap::from([("team".to_owned(), "avro".to_owned())]),
+        friends: vec![friend.clone()],
+        best_friend: Some(Box::new(friend)),
+        fingerprint: Md5([1; 16]),
+        created: 2_000,
+        birthday: Some(19_000),
+        status: UserStatus::String("active".to_owned()),
+        contact: UserContact::Phone(Phone { number: 42 }),
+    };
+
+    let schema = User::get_schema();
+    let mut writer = Writer::new(&schema, Vec::new());
+    writer.append(to_value(&user).unwrap()).unwrap();
+    let bytes = writer.into_inner().unwrap();
+    let values: Vec<_> = Reader::with_schema(&schema, &bytes[..])
+        .unwrap()
+        .map(|value| from_value::<User>(&value.unwrap()).unwrap())
+        .collect();
+    assert_eq!(values, vec![user]);
+}
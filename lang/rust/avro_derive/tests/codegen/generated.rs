@@ -0,0 +1,88 @@
+// Generated by apache_avro::codegen, do not edit.
+
+/// The kind of a user
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize, apache_avro::AvroSchema)]
+#[avro(doc = "The kind of a user")]
+#[avro(namespace = "com.example")]
+pub enum Kind {
+    #[serde(rename = "ADMIN")]
+    Admin,
+    #[serde(rename = "GUEST")]
+    Guest,
+}
+
+/// A user
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, apache_avro::AvroSchema)]
+#[avro(doc = "A user")]
+#[avro(namespace = "com.example")]
+pub struct User {
+    /// The id of the user
+    #[avro(doc = "The id of the user")]
+    #[serde(rename = "userId")]
+    pub user_id: i64,
+
+    #[avro(default = "\"GUEST\"")]
+    pub r#type: Kind,
+
+    #[avro(alias = "login")]
+    pub name: String,
+
+    #[avro(default = "null")]
+    #[avro(bytes)]
+    pub avatar: Option<Vec<u8>>,
+
+    #[avro(default = "0.5")]
+    pub score: f64,
+
+    pub tags: std::collections::HashMap<String, String>,
+
+    pub friends: Vec<User>,
+
+    #[avro(default = "null")]
+    pub best_friend: Option<Box<User>>,
+
+    pub fingerprint: Md5,
+
+    #[avro(logical_type = "timestamp-millis")]
+    pub created: i64,
+
+    #[avro(logical_type = "date")]
+    pub birthday: Option<i32>,
+
+    pub status: UserStatus,
+
+    pub contact: UserContact,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, apache_avro::AvroSchema)]
+#[avro(namespace = "com.example")]
+#[serde(untagged)]
+pub enum UserStatus {
+    String(String),
+    Null,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, apache_avro::AvroSchema)]
+#[avro(namespace = "com.example")]
+pub enum UserContact {
+    Email(Email),
+    Phone(Phone),
+    String(String),
+}
+
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, serde::Serialize, serde::Deserialize, apache_avro::AvroSchema)]
+#[avro(namespace = "com.example")]
+#[avro(fixed)]
+pub struct Md5(pub [u8; 16]);
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, apache_avro::AvroSchema)]
+#[avro(namespace = "com.example")]
+pub struct Email {
+    pub address: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, apache_avro::AvroSchema)]
+#[avro(namespace = "com.example")]
+pub struct Phone {
+    pub number: i64,
+}
@@ -434,7 +434,7 @@ mod test_derive {
         let schema = r#"
         {
             "type":"record",
-            "name":"TestGeneric",
+            "name":"TestGeneric_int",
             "fields":[
                 {
                     "name":"a",
@@ -473,7 +473,7 @@ mod test_derive {
         let schema = r#"
         {
             "type":"record",
-            "name":"TestGeneric",
+            "name":"TestGeneric_TestAllSupportedBaseTypes",
             "fields":[
                 {
                     "name":"a",
@@ -626,6 +626,218 @@ mod test_derive {
         serde_assert(enum_included);
     }
 
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    enum TestDataEnum {
+        Created(TestBasic),
+        #[avro(rename = "DeletedEvent")]
+        Deleted {
+            id: i64,
+        },
+        Noop,
+        Pair(i32, String),
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    struct TestDataEnumNested {
+        a: TestDataEnum,
+        b: TestDataEnum,
+    }
+
+    #[test]
+    fn test_data_enum() {
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestDataEnumNested",
+            "fields":[
+                {
+                    "name":"a",
+                    "type":[
+                        {
+                            "type":"record",
+                            "name":"TestBasic",
+                            "fields":[
+                                {"name":"a", "type":"int"},
+                                {"name":"b", "type":"string"}
+                            ]
+                        },
+                        {
+                            "type":"record",
+                            "name":"DeletedEvent",
+                            "fields":[{"name":"id", "type":"long"}]
+                        },
+                        {"type":"record", "name":"TestDataEnumNoop", "fields":[]},
+                        {
+                            "type":"record",
+                            "name":"TestDataEnumPair",
+                            "fields":[
+                                {"name":"field_0", "type":"int"},
+                                {"name":"field_1", "type":"string"}
+                            ]
+                        }
+                    ]
+                },
+                {
+                    "name":"b",
+                    "type":["TestBasic", "DeletedEvent", "TestDataEnumNoop", "TestDataEnumPair"]
+                }
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        assert_eq!(schema, TestDataEnumNested::get_schema());
+        let variants = [
+            TestDataEnum::Created(TestBasic {
+                a: 27,
+                b: "foo".to_owned(),
+            }),
+            TestDataEnum::Deleted { id: 3 },
+            TestDataEnum::Noop,
+            TestDataEnum::Pair(-1, "bar".to_owned()),
+        ];
+        for a in variants.iter() {
+            for b in variants.iter() {
+                serde_assert(TestDataEnumNested {
+                    a: a.clone(),
+                    b: b.clone(),
+                });
+            }
+        }
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    #[serde(untagged)]
+    enum TestUntaggedEnum {
+        Number(i64),
+        Text(String),
+        Named { first: String, last: String },
+        Missing,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    struct TestUntaggedEnumNested {
+        a: TestUntaggedEnum,
+    }
+
+    #[test]
+    fn test_untagged_data_enum() {
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestUntaggedEnumNested",
+            "fields":[
+                {
+                    "name":"a",
+                    "type":[
+                        "long",
+                        "string",
+                        {
+                            "type":"record",
+                            "name":"TestUntaggedEnumNamed",
+                            "fields":[
+                                {"name":"first", "type":"string"},
+                                {"name":"last", "type":"string"}
+                            ]
+                        },
+                        "null"
+                    ]
+                }
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        assert_eq!(schema, TestUntaggedEnumNested::get_schema());
+        for a in [
+            TestUntaggedEnum::Number(42),
+            TestUntaggedEnum::Text("forty-two".to_owned()),
+            TestUntaggedEnum::Named {
+                first: "Ford".to_owned(),
+                last: "Prefect".to_owned(),
+            },
+            TestUntaggedEnum::Missing,
+        ] {
+            serde_assert(TestUntaggedEnumNested { a });
+        }
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    enum TestUserEvent {
+        Deleted { id: i64 },
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    enum TestOrderEvent {
+        Deleted { reason: String },
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    struct TestEvents {
+        user: TestUserEvent,
+        order: TestOrderEvent,
+    }
+
+    #[test]
+    fn test_data_enums_with_same_variants() {
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestEvents",
+            "fields":[
+                {
+                    "name":"user",
+                    "type":[{
+                        "type":"record",
+                        "name":"TestUserEventDeleted",
+                        "fields":[{"name":"id", "type":"long"}]
+                    }]
+                },
+                {
+                    "name":"order",
+                    "type":[{
+                        "type":"record",
+                        "name":"TestOrderEventDeleted",
+                        "fields":[{"name":"reason", "type":"string"}]
+                    }]
+                }
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        assert_eq!(schema, TestEvents::get_schema());
+        serde_assert(TestEvents {
+            user: TestUserEvent::Deleted { id: 3 },
+            order: TestOrderEvent::Deleted {
+                reason: "cancelled".to_owned(),
+            },
+        });
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    enum TestRenamedUserEvent {
+        #[avro(rename = "Deleted")]
+        Deleted { id: i64 },
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    enum TestRenamedOrderEvent {
+        #[avro(rename = "Deleted")]
+        Deleted { reason: String },
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    struct TestRenamedEvents {
+        user: TestRenamedUserEvent,
+        order: TestRenamedOrderEvent,
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Unable to name the record of an enum variant Deleted, which is already the name of another schema"
+    )]
+    fn test_data_enums_with_same_renamed_variants() {
+        TestRenamedEvents::get_schema();
+    }
+
     #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
     struct ConsList {
         value: i32,
@@ -673,7 +885,7 @@ mod test_derive {
         let schema = r#"
         {
             "type":"record",
-            "name":"ConsListGeneric",
+            "name":"ConsListGeneric_TestAllowedEnumNested",
             "fields":[
                 {
                     "name":"value",
@@ -698,7 +910,7 @@ mod test_derive {
                 },
                 {
                     "name":"next",
-                    "type":["null","ConsListGeneric"]
+                    "type":["null","ConsListGeneric_TestAllowedEnumNested"]
                 }
             ]
         }
@@ -763,7 +975,7 @@ mod test_derive {
         let schema = r#"
         {
             "type":"record",
-            "name":"TestComplexArray",
+            "name":"TestComplexArray_TestBasic",
             "fields":[
                 {
                     "name":"a",
@@ -822,6 +1034,130 @@ mod test_derive {
         // don't check for schema equality to allow for transitioning to bytes or fixed types in the future
     }}
 
+    /// A SHA-256 digest
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    #[avro(fixed, namespace = "com.testing")]
+    struct Sha256([u8; 32]);
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    #[avro(transparent)]
+    struct UserId(i64);
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    #[serde(transparent)]
+    struct UserName {
+        name: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    struct TestFixedAndTransparent {
+        hash: Sha256,
+        a: [u8; 4],
+        b: [u8; 4],
+        id: UserId,
+        name: UserName,
+    }
+
+    #[test]
+    fn test_fixed_and_transparent() {
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestFixedAndTransparent",
+            "fields":[
+                {
+                    "name":"hash",
+                    "type":{
+                        "type":"fixed",
+                        "name":"Sha256",
+                        "namespace":"com.testing",
+                        "doc":"A SHA-256 digest",
+                        "size":32
+                    }
+                },
+                {
+                    "name":"a",
+                    "type":{"type":"fixed", "name":"fixed_4", "size":4}
+                },
+                {"name":"b", "type":"fixed_4"},
+                {"name":"id", "type":"long"},
+                {"name":"name", "type":"string"}
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        assert_eq!(schema, TestFixedAndTransparent::get_schema());
+        serde_assert(TestFixedAndTransparent {
+            hash: Sha256([7; 32]),
+            a: [0, 1, 2, 255],
+            b: [3; 4],
+            id: UserId(42),
+            name: UserName {
+                name: "Zaphod".to_owned(),
+            },
+        });
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    struct TestLogicalTypes {
+        #[avro(logical_type = "timestamp-micros")]
+        created: i64,
+        #[avro(logical_type = "timestamp-millis")]
+        deleted: Option<i64>,
+        #[avro(logical_type = "date")]
+        day: i32,
+        #[avro(logical_type = "time-millis")]
+        time: i32,
+        #[avro(logical_type = "uuid")]
+        id: String,
+        #[avro(decimal(precision = 10, scale = 2))]
+        amount: Vec<u8>,
+        #[avro(decimal(precision = 4))]
+        small_amount: [u8; 2],
+    }
+
+    #[test]
+    fn test_logical_types() {
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestLogicalTypes",
+            "fields":[
+                {"name":"created", "type":{"type":"long", "logicalType":"timestamp-micros"}},
+                {"name":"deleted", "type":["null", {"type":"long", "logicalType":"timestamp-millis"}]},
+                {"name":"day", "type":{"type":"int", "logicalType":"date"}},
+                {"name":"time", "type":{"type":"int", "logicalType":"time-millis"}},
+                {"name":"id", "type":{"type":"string", "logicalType":"uuid"}},
+                {
+                    "name":"amount",
+                    "type":{"type":"bytes", "logicalType":"decimal", "precision":10, "scale":2}
+                },
+                {
+                    "name":"small_amount",
+                    "type":{
+                        "type":{"type":"fixed", "name":"decimal_4_0_2", "size":2},
+                        "logicalType":"decimal",
+                        "precision":4
+                    }
+                }
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        assert_eq!(schema, TestLogicalTypes::get_schema());
+        for deleted in [None, Some(1_681_000_000_000)] {
+            serde_assert(TestLogicalTypes {
+                created: 1_681_000_000_000_000,
+                deleted,
+                day: 19_458,
+                time: 3_600_000,
+                id: "c1b9e53e-3f5c-4b3e-8a1d-6e0a6cdd8b51".to_owned(),
+                amount: vec![1, 24],
+                small_amount: [0, 42],
+            });
+        }
+    }
+
     #[derive(Debug, Serialize, Deserialize, AvroSchema)]
     struct TestSmartPointers<'a> {
         a: String,
@@ -1544,6 +1880,179 @@ mod test_derive {
         });
     }
 
+    fn default_nickname() -> String {
+        "anonymous".to_owned()
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    struct TestSerdeAttributes {
+        first_name: String,
+        #[serde(rename = "surname")]
+        last_name: String,
+        #[serde(skip)]
+        cache: Option<i64>,
+        #[serde(skip_serializing, default)]
+        secret: String,
+        #[serde(default)]
+        visit_count: i32,
+        #[serde(default = "default_nickname")]
+        nick_name: String,
+    }
+
+    #[test]
+    fn test_serde_attributes() {
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestSerdeAttributes",
+            "fields":[
+                {"name":"firstName", "type":"string"},
+                {"name":"surname", "type":"string"},
+                {"name":"visitCount", "type":"int", "default":0},
+                {"name":"nickName", "type":"string", "default":"anonymous"}
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        assert_eq!(schema, TestSerdeAttributes::get_schema());
+        if let Schema::Record { fields, .. } = TestSerdeAttributes::get_schema() {
+            assert_eq!(fields[2].default, Some(serde_json::json!(0)));
+            assert_eq!(fields[3].default, Some(serde_json::json!("anonymous")));
+        }
+        serde_assert(TestSerdeAttributes {
+            first_name: "Arthur".to_owned(),
+            last_name: "Dent".to_owned(),
+            cache: None,
+            secret: String::new(),
+            visit_count: 42,
+            nick_name: "Earthman".to_owned(),
+        });
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Default)]
+    #[serde(default)]
+    #[avro(rename_all = "PascalCase")]
+    struct TestContainerDefault {
+        user_id: i64,
+        user_name: String,
+    }
+
+    #[test]
+    fn test_avro_rename_all_and_container_default() {
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestContainerDefault",
+            "fields":[
+                {"name":"UserId", "type":"long", "default":0},
+                {"name":"UserName", "type":"string", "default":""}
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        assert_eq!(schema, TestContainerDefault::get_schema());
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    struct TestFlattenInner {
+        b: String,
+        c: TestBasic,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    struct TestFlatten {
+        a: i32,
+        #[serde(flatten)]
+        inner: TestFlattenInner,
+        d: TestBasic,
+    }
+
+    #[test]
+    fn test_serde_flatten() {
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestFlatten",
+            "fields":[
+                {"name":"a", "type":"int"},
+                {"name":"b", "type":"string"},
+                {
+                    "name":"c",
+                    "type":{
+                        "type":"record",
+                        "name":"TestBasic",
+                        "fields":[
+                            {"name":"a", "type":"int"},
+                            {"name":"b", "type":"string"}
+                        ]
+                    }
+                },
+                {"name":"d", "type":"TestBasic"}
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        let derived_schema = TestFlatten::get_schema();
+        assert_eq!(schema, derived_schema);
+        if let Schema::Record { fields, lookup, .. } = derived_schema {
+            assert_eq!(fields[3].position, 3);
+            assert_eq!(lookup["d"], 3);
+        }
+        serde_assert(TestFlatten {
+            a: 1,
+            inner: TestFlattenInner {
+                b: "flat".to_owned(),
+                c: TestBasic {
+                    a: 2,
+                    b: "c".to_owned(),
+                },
+            },
+            d: TestBasic {
+                a: 3,
+                b: "d".to_owned(),
+            },
+        });
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    enum TestRenamedEnum {
+        DarkRed,
+        #[serde(rename = "BLUE")]
+        LightBlue,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Eq)]
+    struct TestRenamedEnumNested {
+        a: TestRenamedEnum,
+    }
+
+    #[test]
+    fn test_serde_renamed_enum() {
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestRenamedEnumNested",
+            "fields":[
+                {
+                    "name":"a",
+                    "type":{
+                        "type":"enum",
+                        "name":"TestRenamedEnum",
+                        "symbols":["DARK_RED","BLUE"]
+                    }
+                }
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        assert_eq!(schema, TestRenamedEnumNested::get_schema());
+        serde_assert(TestRenamedEnumNested {
+            a: TestRenamedEnum::LightBlue,
+        });
+    }
+
     #[test]
     fn test_avro_3663_raw_identifier_field_name() {
         #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
@@ -1559,4 +2068,492 @@ mod test_derive {
             panic!("Unexpected schema type for {derived_schema:?}")
         }
     }
+
+    fn default_label() -> String {
+        "none".to_owned()
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    struct TestTypedDefaults {
+        #[avro(default)]
+        a: Vec<i32>,
+        #[avro(default = 42)]
+        b: i64,
+        #[avro(default = 1.5)]
+        c: f32,
+        #[avro(default = true)]
+        d: bool,
+        #[avro(default_expr = "default_label()")]
+        e: String,
+        #[avro(default)]
+        f: Option<i32>,
+        #[avro(default_expr = "TestBasic { a: 1, b: \"b\".to_owned() }")]
+        g: TestBasic,
+        #[avro(default = "\"BLUE\"")]
+        h: TestRenamedEnum,
+    }
+
+    #[test]
+    fn test_typed_defaults() {
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestTypedDefaults",
+            "fields":[
+                {"name":"a", "type":{"type":"array", "items":"int"}, "default":[]},
+                {"name":"b", "type":"long", "default":42},
+                {"name":"c", "type":"float", "default":1.5},
+                {"name":"d", "type":"boolean", "default":true},
+                {"name":"e", "type":"string", "default":"none"},
+                {"name":"f", "type":["null", "int"], "default":null},
+                {
+                    "name":"g",
+                    "type":{
+                        "type":"record",
+                        "name":"TestBasic",
+                        "fields":[
+                            {"name":"a", "type":"int"},
+                            {"name":"b", "type":"string"}
+                        ]
+                    },
+                    "default":{"a":1, "b":"b"}
+                },
+                {
+                    "name":"h",
+                    "type":{
+                        "type":"enum",
+                        "name":"TestRenamedEnum",
+                        "symbols":["DARK_RED","BLUE"]
+                    },
+                    "default":"BLUE"
+                }
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        let derived_schema = TestTypedDefaults::get_schema();
+        assert_eq!(schema, derived_schema);
+        // the Parsing Canonical Form the schemas are compared with has no defaults
+        if let Schema::Record { fields, .. } = derived_schema {
+            let defaults: Vec<_> = fields.into_iter().map(|field| field.default).collect();
+            assert_eq!(
+                defaults,
+                vec![
+                    Some(serde_json::json!([])),
+                    Some(serde_json::json!(42)),
+                    Some(serde_json::json!(1.5)),
+                    Some(serde_json::json!(true)),
+                    Some(serde_json::json!("none")),
+                    Some(serde_json::json!(null)),
+                    Some(serde_json::json!({"a": 1, "b": "b"})),
+                    Some(serde_json::json!("BLUE")),
+                ]
+            );
+        } else {
+            panic!("TestTypedDefaults schema must be a record schema")
+        }
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq, Default)]
+    #[serde(default)]
+    struct TestBytesDefaults {
+        #[avro(default = "\"\\u0000\\u007f\"")]
+        a: [u8; 2],
+        b: [u8; 2],
+    }
+
+    #[test]
+    fn test_bytes_defaults() {
+        // the defaults of fixed fields are strings, which typed defaults cannot give
+        if let Schema::Record { fields, .. } = TestBytesDefaults::get_schema() {
+            assert_eq!(fields[0].default, Some(serde_json::json!("\u{0}\u{7f}")));
+            assert_eq!(fields[1].default, None);
+        } else {
+            panic!("TestBytesDefaults schema must be a record schema")
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid default value \"a string\" for field \"a\"")]
+    fn test_default_not_matching_schema() {
+        #[derive(AvroSchema)]
+        #[allow(dead_code)]
+        struct TestWrongDefault {
+            #[avro(default = "\"a string\"")]
+            a: i32,
+        }
+
+        TestWrongDefault::get_schema();
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid default value 3 for field \"a\"")]
+    fn test_default_not_matching_first_union_branch() {
+        #[derive(AvroSchema)]
+        #[allow(dead_code)]
+        struct TestWrongUnionDefault {
+            #[avro(default_expr = "Some(3)")]
+            a: Option<i32>,
+        }
+
+        TestWrongUnionDefault::get_schema();
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    struct TestTupleStruct(i32, String);
+
+    #[test]
+    fn test_tuple_struct() {
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestTupleStruct",
+            "fields":[
+                {"name":"field_0", "type":"int"},
+                {"name":"field_1", "type":"string"}
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        assert_eq!(schema, TestTupleStruct::get_schema());
+        serde_assert(TestTupleStruct(27, "foo".to_owned()));
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    struct TestUnitStruct;
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    struct TestNewtype(i64);
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    struct TestUnitAndNewtype {
+        a: TestUnitStruct,
+        b: TestNewtype,
+    }
+
+    #[test]
+    fn test_unit_struct_and_newtype() {
+        assert_eq!(Schema::Null, TestUnitStruct::get_schema());
+        assert_eq!(Schema::Long, TestNewtype::get_schema());
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestUnitAndNewtype",
+            "fields":[
+                {"name":"a", "type":"null"},
+                {"name":"b", "type":"long"}
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        assert_eq!(schema, TestUnitAndNewtype::get_schema());
+        serde_assert(TestUnitAndNewtype {
+            a: TestUnitStruct,
+            b: TestNewtype(42),
+        });
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    struct TestEnvelope<T: AvroSchemaComponent> {
+        payload: T,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    #[avro(name = "{T}Batch")]
+    struct TestBatch<T: AvroSchemaComponent> {
+        items: Vec<T>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    struct TestEnvelopes {
+        basic: TestEnvelope<TestBasic>,
+        numbers: TestEnvelope<Option<Vec<i64>>>,
+        batch: TestBatch<TestBasic>,
+    }
+
+    #[test]
+    fn test_generic_instantiations() {
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestEnvelopes",
+            "fields":[
+                {
+                    "name":"basic",
+                    "type":{
+                        "type":"record",
+                        "name":"TestEnvelope_TestBasic",
+                        "fields":[
+                            {
+                                "name":"payload",
+                                "type":{
+                                    "type":"record",
+                                    "name":"TestBasic",
+                                    "fields":[
+                                        {"name":"a", "type":"int"},
+                                        {"name":"b", "type":"string"}
+                                    ]
+                                }
+                            }
+                        ]
+                    }
+                },
+                {
+                    "name":"numbers",
+                    "type":{
+                        "type":"record",
+                        "name":"TestEnvelope_union_null_array_long",
+                        "fields":[
+                            {
+                                "name":"payload",
+                                "type":["null", {"type":"array", "items":"long"}]
+                            }
+                        ]
+                    }
+                },
+                {
+                    "name":"batch",
+                    "type":{
+                        "type":"record",
+                        "name":"TestBasicBatch",
+                        "fields":[
+                            {"name":"items", "type":{"type":"array", "items":"TestBasic"}}
+                        ]
+                    }
+                }
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        assert_eq!(schema, TestEnvelopes::get_schema());
+        serde_assert(TestEnvelopes {
+            basic: TestEnvelope {
+                payload: TestBasic {
+                    a: 1,
+                    b: "b".to_owned(),
+                },
+            },
+            numbers: TestEnvelope {
+                payload: Some(vec![1, 2, 3]),
+            },
+            batch: TestBatch {
+                items: vec![TestBasic {
+                    a: 2,
+                    b: "c".to_owned(),
+                }],
+            },
+        });
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    struct TestStdTypes {
+        a: std::collections::BTreeMap<String, i32>,
+        b: std::collections::HashSet<String>,
+        c: std::collections::BTreeSet<i64>,
+        d: std::collections::VecDeque<String>,
+        e: std::sync::Arc<String>,
+        f: std::rc::Rc<TestBasic>,
+        g: std::net::IpAddr,
+        h: std::time::SystemTime,
+        #[serde(with = "apache_avro::serde_timestamp_micros")]
+        #[avro(logical_type = "timestamp-micros")]
+        i: std::time::SystemTime,
+        #[serde(with = "apache_avro::serde_timestamp_micros::option")]
+        #[avro(logical_type = "timestamp-micros")]
+        j: Option<std::time::SystemTime>,
+    }
+
+    #[test]
+    fn test_std_types() {
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestStdTypes",
+            "fields":[
+                {"name":"a", "type":{"type":"map", "values":"int"}},
+                {"name":"b", "type":{"type":"array", "items":"string"}},
+                {"name":"c", "type":{"type":"array", "items":"long"}},
+                {"name":"d", "type":{"type":"array", "items":"string"}},
+                {"name":"e", "type":"string"},
+                {
+                    "name":"f",
+                    "type":{
+                        "type":"record",
+                        "name":"TestBasic",
+                        "fields":[
+                            {"name":"a", "type":"int"},
+                            {"name":"b", "type":"string"}
+                        ]
+                    }
+                },
+                {"name":"g", "type":"string"},
+                {
+                    "name":"h",
+                    "type":{
+                        "type":"record",
+                        "name":"SystemTime",
+                        "fields":[
+                            {"name":"secs_since_epoch", "type":"long"},
+                            {"name":"nanos_since_epoch", "type":"long"}
+                        ]
+                    }
+                },
+                {"name":"i", "type":{"type":"long", "logicalType":"timestamp-micros"}},
+                {"name":"j", "type":["null", {"type":"long", "logicalType":"timestamp-micros"}]}
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        assert_eq!(schema, TestStdTypes::get_schema());
+        serde_assert(TestStdTypes {
+            a: [("one".to_owned(), 1), ("two".to_owned(), 2)].into(),
+            b: ["foo".to_owned(), "bar".to_owned()].into(),
+            c: [3, 1, 2].into(),
+            d: ["first".to_owned(), "last".to_owned()].into(),
+            e: std::sync::Arc::new("shared".to_owned()),
+            f: std::rc::Rc::new(TestBasic {
+                a: 27,
+                b: "foo".to_owned(),
+            }),
+            g: "::1".parse().unwrap(),
+            h: std::time::UNIX_EPOCH + std::time::Duration::new(1_681_000_000, 123_456_789),
+            i: std::time::UNIX_EPOCH + std::time::Duration::from_micros(1_681_000_000_123_456),
+            j: None,
+        });
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    struct TestEcosystemTypes {
+        a: serde_bytes::ByteBuf,
+        b: url::Url,
+        c: indexmap::IndexMap<String, i32>,
+        d: Option<url::Url>,
+    }
+
+    #[test]
+    fn test_ecosystem_types() {
+        let schema = r#"
+        {
+            "type":"record",
+            "name":"TestEcosystemTypes",
+            "fields":[
+                {"name":"a", "type":"bytes"},
+                {"name":"b", "type":"string"},
+                {"name":"c", "type":{"type":"map", "values":"int"}},
+                {"name":"d", "type":["null", "string"]}
+            ]
+        }
+        "#;
+        let schema = Schema::parse_str(schema).unwrap();
+        assert_eq!(schema, TestEcosystemTypes::get_schema());
+        serde_assert(TestEcosystemTypes {
+            a: serde_bytes::ByteBuf::from(vec![0, 1, 255]),
+            b: "https://avro.apache.org/docs/".parse().unwrap(),
+            c: [("b".to_owned(), 2), ("a".to_owned(), 1)]
+                .into_iter()
+                .collect(),
+            d: Some("https://example.com/".parse().unwrap()),
+        });
+    }
+
+    #[test]
+    fn test_cached_schema() {
+        let cached = TestBasic::get_cached_schema();
+        assert_eq!(cached.schema(), &TestBasic::get_schema());
+        assert!(std::ptr::eq(cached, TestBasic::get_cached_schema()));
+        assert_eq!(
+            TestBasic::get_canonical_form(),
+            TestBasic::get_schema().canonical_form()
+        );
+
+        // the instantiations of a generic type are cached apart
+        let int_generic = TestGeneric::<i32>::get_cached_schema();
+        let basic_generic = TestGeneric::<TestAllSupportedBaseTypes>::get_cached_schema();
+        assert_eq!(int_generic.schema(), &TestGeneric::<i32>::get_schema());
+        assert_eq!(
+            basic_generic.schema(),
+            &TestGeneric::<TestAllSupportedBaseTypes>::get_schema()
+        );
+        assert_ne!(
+            int_generic.rabin_fingerprint(),
+            basic_generic.rabin_fingerprint()
+        );
+        assert!(std::ptr::eq(
+            int_generic,
+            TestGeneric::<i32>::get_cached_schema()
+        ));
+
+        // the single object encoding takes the cached schema of types without generics
+        match TestBasic::get_schema_with_fingerprint() {
+            Cow::Borrowed(schema) => assert!(std::ptr::eq(schema, cached)),
+            Cow::Owned(_) => panic!("Expected the cached schema of TestBasic"),
+        }
+        let computed = TestGeneric::<i32>::get_schema_with_fingerprint();
+        assert!(matches!(computed, Cow::Owned(_)));
+        assert_eq!(
+            computed.rabin_fingerprint(),
+            int_generic.rabin_fingerprint()
+        );
+        let computed = TestReference::get_schema_with_fingerprint();
+        assert!(matches!(computed, Cow::Owned(_)));
+        assert_eq!(computed.schema(), &TestReference::get_schema());
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    #[avro(schema_file = "tests/schema_file/identical.avsc", identical)]
+    struct TestSchemaFileIdentical {
+        a: i32,
+        b: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+    #[avro(schema_file = "tests/schema_file/evolved.avsc")]
+    struct TestSchemaFileCompatible {
+        a: i64,
+        b: String,
+    }
+
+    #[test]
+    #[allow(unnameable_test_items)]
+    fn test_schema_file_of_local_type() {
+        #[derive(Debug, Serialize, Deserialize, AvroSchema, Clone, PartialEq)]
+        #[avro(
+            name = "TestSchemaFileIdentical",
+            schema_file = "tests/schema_file/identical.avsc",
+            identical
+        )]
+        struct TestSchemaFileLocal {
+            a: i32,
+            b: String,
+        }
+
+        // the generated test cannot be run for types defined in functions
+        avro_schema_file_test_schema_file_local();
+        serde_assert(TestSchemaFileLocal {
+            a: 27,
+            b: "foo".to_owned(),
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "The schema is not the one of tests/schema_file/evolved.avsc")]
+    fn test_schema_file_not_identical() {
+        apache_avro::schema::derive::verify_schema_file::<TestSchemaFileCompatible>(
+            "tests/schema_file/evolved.avsc",
+            include_str!("schema_file/evolved.avsc"),
+            true,
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The schema is not compatible with the one of tests/schema_file/identical.avsc"
+    )]
+    fn test_schema_file_not_compatible() {
+        apache_avro::schema::derive::verify_schema_file::<TestBasicWithU32>(
+            "tests/schema_file/identical.avsc",
+            include_str!("schema_file/identical.avsc"),
+            false,
+        );
+    }
 }